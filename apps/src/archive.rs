@@ -0,0 +1,141 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use alloy::primitives::Bytes;
+use anyhow::{Context, Result};
+
+use crate::output::RunRecord;
+
+/// Guest input the run sent, as encoded.
+const INPUT_FILE: &str = "input.bin";
+/// Journal committed by the guest, hex encoded.
+const JOURNAL_FILE: &str = "journal.hex";
+/// Seal of the fulfilled request, hex encoded.
+const SEAL_FILE: &str = "seal.hex";
+/// The run record as JSON.
+const RESULT_FILE: &str = "result.json";
+/// Hash of the setSignal transaction, only written if one was sent.
+const TX_HASH_FILE: &str = "tx_hash.txt";
+
+/// What a proving run sent and got back, archived in a folder of its own.
+pub struct RunArchive<'a> {
+    pub input: &'a [u8],
+    pub journal: &'a Bytes,
+    pub seal: &'a Bytes,
+    pub record: &'a RunRecord,
+}
+
+impl RunArchive<'_> {
+    /// Writes the artifacts to a new folder of `dir` named after the timestamp of the run, with a
+    /// counter appended if runs share a second, and returns the folder.
+    pub fn write(&self, dir: &Path) -> Result<PathBuf> {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("failed to create output directory {}", dir.display()))?;
+        let folder = new_folder(dir, self.record.timestamp)?;
+        let write = |name: &str, contents: &[u8]| {
+            let path = folder.join(name);
+            std::fs::write(&path, contents)
+                .with_context(|| format!("failed to write {}", path.display()))
+        };
+        write(INPUT_FILE, self.input)?;
+        write(JOURNAL_FILE, hex::encode(self.journal).as_bytes())?;
+        write(SEAL_FILE, hex::encode(self.seal).as_bytes())?;
+        let result =
+            serde_json::to_vec_pretty(self.record).context("failed to serialize run record")?;
+        write(RESULT_FILE, &result)?;
+        if let Some(tx_hash) = self.record.tx_hash {
+            write(TX_HASH_FILE, tx_hash.to_string().as_bytes())?;
+        }
+        Ok(folder)
+    }
+}
+
+// Creates the first free folder of `dir` named `timestamp`, then `timestamp-1` and so on
+fn new_folder(dir: &Path, timestamp: u64) -> Result<PathBuf> {
+    for attempt in 0u32.. {
+        let name = match attempt {
+            0 => timestamp.to_string(),
+            _ => format!("{}-{}", timestamp, attempt),
+        };
+        let folder = dir.join(name);
+        match std::fs::create_dir(&folder) {
+            Ok(()) => return Ok(folder),
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => continue,
+            Err(err) => {
+                return Err(err).with_context(|| format!("failed to create {}", folder.display()))
+            }
+        }
+    }
+    unreachable!("every folder name is taken")
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::B256;
+
+    use super::*;
+    use crate::signal::{Action, GuestOutput};
+
+    #[test]
+    fn a_run_populates_its_own_folder() {
+        let dir = tempfile::tempdir().unwrap();
+        let record = RunRecord {
+            timestamp: 1_700_000_000,
+            ..RunRecord::new(
+                Action::Buy,
+                &GuestOutput::default(),
+                Some(B256::repeat_byte(0x22)),
+            )
+        };
+        let archive = RunArchive {
+            input: &[0, 1, 2],
+            journal: &Bytes::from(vec![0xab; 4]),
+            seal: &Bytes::from(vec![0xcd; 2]),
+            record: &record,
+        };
+
+        let folder = archive.write(dir.path()).unwrap();
+        assert_eq!(folder, dir.path().join("1700000000"));
+        let read = |name: &str| std::fs::read(folder.join(name)).unwrap();
+        assert_eq!(read(INPUT_FILE), [0, 1, 2]);
+        assert_eq!(read(JOURNAL_FILE), b"abababab");
+        assert_eq!(read(SEAL_FILE), b"cdcd");
+        let result: serde_json::Value = serde_json::from_slice(&read(RESULT_FILE)).unwrap();
+        assert_eq!(result["action"], "BUY");
+        assert_eq!(
+            read(TX_HASH_FILE),
+            B256::repeat_byte(0x22).to_string().as_bytes()
+        );
+
+        // A second run in the same second gets a folder of its own, without a tx hash if it sent
+        // no transaction
+        let held = RunRecord {
+            tx_hash: None,
+            ..record.clone()
+        };
+        let folder = RunArchive {
+            record: &held,
+            ..archive
+        }
+        .write(dir.path())
+        .unwrap();
+        assert_eq!(folder, dir.path().join("1700000000-1"));
+        assert!(!folder.join(TX_HASH_FILE).exists());
+    }
+}
@@ -0,0 +1,235 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::{anyhow, bail, Result};
+use clap::ValueEnum;
+
+use crate::{history::PricePoint, signal::Action};
+
+/// Outcome of walking a strategy forward over a price history.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BacktestReport {
+    /// Number of days predicted.
+    pub predictions: usize,
+    /// Number of BUY or SELL predictions, i.e. those with a direction.
+    pub directional: usize,
+    /// Directional predictions followed by a move in the predicted direction.
+    pub correct: usize,
+    /// Compounded return of buying on BUY, shorting on SELL and staying flat on HOLD, as a
+    /// fraction (0.05 = +5%).
+    pub cumulative_return: f64,
+}
+
+impl BacktestReport {
+    /// Fraction of directional predictions that were right, or `None` if there were none.
+    pub fn directional_accuracy(&self) -> Option<f64> {
+        (self.directional > 0).then(|| self.correct as f64 / self.directional as f64)
+    }
+
+    /// Logs the report.
+    pub fn log(&self) {
+        tracing::info!(
+            "Backtest over {} days: {} directional signals",
+            self.predictions,
+            self.directional
+        );
+        match self.directional_accuracy() {
+            Some(accuracy) => tracing::info!(
+                "Directional accuracy: {:.1}% ({}/{})",
+                accuracy * 100.0,
+                self.correct,
+                self.directional
+            ),
+            None => tracing::info!("Directional accuracy: n/a (every signal was HOLD)"),
+        }
+        tracing::info!(
+            "Cumulative return following the signal: {:+.2}%",
+            self.cumulative_return * 100.0
+        );
+    }
+}
+
+/// Walks the history forward one day at a time: for each day k from `min_train_len`, `predict`
+/// is given days 1..=k and the latest price, and its action is scored against the price of day
+/// k + 1.
+pub fn run(
+    history: &[PricePoint],
+    min_train_len: usize,
+    mut predict: impl FnMut(&[PricePoint], u64) -> Result<Action>,
+) -> Result<BacktestReport> {
+    if min_train_len < 2 || history.len() <= min_train_len {
+        bail!(
+            "backtest needs at least 2 training points and more history than --min-train-len {}, got {} points",
+            min_train_len,
+            history.len()
+        );
+    }
+
+    let mut report = BacktestReport {
+        predictions: 0,
+        directional: 0,
+        correct: 0,
+        cumulative_return: 0.0,
+    };
+    let mut equity = 1.0;
+    for k in min_train_len..history.len() {
+        let current_price = history[k - 1].1;
+        let next_price = history[k].1;
+        let action = predict(&history[..k], current_price)?;
+
+        let daily_return = (next_price as f64 - current_price as f64) / current_price as f64;
+        report.predictions += 1;
+        match action {
+            Action::Buy => {
+                report.directional += 1;
+                report.correct += usize::from(next_price > current_price);
+                equity *= 1.0 + daily_return;
+            }
+            Action::Sell => {
+                report.directional += 1;
+                report.correct += usize::from(next_price < current_price);
+                equity *= 1.0 - daily_return;
+            }
+            Action::Hold => {}
+        }
+    }
+    report.cumulative_return = equity - 1.0;
+    Ok(report)
+}
+
+/// What `tune` maximizes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum TuneObjective {
+    /// Cumulative return of following the signal.
+    #[default]
+    Return,
+    /// Directional accuracy; thresholds producing only HOLDs score lowest.
+    Accuracy,
+}
+
+impl TuneObjective {
+    fn score(self, report: &BacktestReport) -> f64 {
+        match self {
+            Self::Return => report.cumulative_return,
+            Self::Accuracy => report.directional_accuracy().unwrap_or(f64::NEG_INFINITY),
+        }
+    }
+}
+
+/// Backtests each threshold in basis points and returns the one with the best `objective`, with
+/// its report. Ties go to the higher threshold, which trades less for the same result.
+pub fn tune(
+    history: &[PricePoint],
+    min_train_len: usize,
+    thresholds_bps: impl IntoIterator<Item = u64>,
+    objective: TuneObjective,
+    mut predict: impl FnMut(u64, &[PricePoint], u64) -> Result<Action>,
+) -> Result<(u64, BacktestReport)> {
+    let mut best: Option<(u64, BacktestReport)> = None;
+    for threshold_bps in thresholds_bps {
+        let report = run(history, min_train_len, |train, current_price| {
+            predict(threshold_bps, train, current_price)
+        })?;
+        tracing::info!(
+            "Threshold {} bps: return {:+.2}%, {} directional signals",
+            threshold_bps,
+            report.cumulative_return * 100.0,
+            report.directional
+        );
+        if best
+            .as_ref()
+            .is_none_or(|(_, best)| objective.score(&report) >= objective.score(best))
+        {
+            best = Some((threshold_bps, report));
+        }
+    }
+    best.ok_or_else(|| anyhow!("no thresholds to tune over"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scores_actions_against_next_day() {
+        // Days 2 -> 3 fall, days 3 -> 4 are flat
+        let history = vec![(1, 100), (2, 110), (3, 99), (4, 99)];
+        let mut actions = [Action::Buy, Action::Sell].into_iter();
+        let report = run(&history, 2, |_, _| Ok(actions.next().unwrap())).unwrap();
+
+        assert_eq!(report.predictions, 2);
+        assert_eq!(report.directional, 2);
+        // BUY before the drop to 99 is wrong, SELL before the flat day is not a hit either
+        assert_eq!(report.correct, 0);
+        assert!((report.cumulative_return - (1.0 - 11.0 / 110.0 - 1.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn tunes_threshold_below_trend_move() {
+        // A steady $25/day rise from $3025 moves 62-83 bps a day; a momentum predictor expecting
+        // the same move again should only BUY every day with a threshold under 62 bps
+        let history: Vec<PricePoint> = (1..=40).map(|day| (day, 3000 + 25 * day)).collect();
+        let (best, report) = tune(
+            &history,
+            10,
+            (0..=200).step_by(10),
+            TuneObjective::Return,
+            |threshold_bps, train, current_price| {
+                let previous = train[train.len() - 2].1;
+                let predicted = 2 * current_price - previous;
+                Ok(
+                    if (predicted - current_price) * 10_000 > current_price * threshold_bps {
+                        Action::Buy
+                    } else {
+                        Action::Hold
+                    },
+                )
+            },
+        )
+        .unwrap();
+
+        assert!((50..62).contains(&best), "unexpected threshold {best}");
+        assert_eq!(report.directional, report.predictions);
+    }
+
+    #[test]
+    fn follows_trending_series_through_the_guest() {
+        use alloy::primitives::U256;
+        use trading_journal::GuestInput;
+
+        use crate::{executor, signal::GuestOutput};
+
+        let history: Vec<PricePoint> = (1..=40).map(|day| (day, 3000 + 25 * day)).collect();
+        let report = run(&history, 10, |train, current_price| {
+            let input = GuestInput {
+                current_price: U256::from(current_price),
+                history: train.to_vec(),
+                ..Default::default()
+            }
+            .encode();
+            let session_info = executor::execute(&input)?;
+            let output = GuestOutput::decode(&session_info.journal.bytes, 0)?;
+            Ok(output.signal)
+        })
+        .unwrap();
+
+        assert_eq!(report.predictions, 30);
+        assert!(
+            report.directional_accuracy().unwrap() > 0.95,
+            "{:?}",
+            report
+        );
+        assert!(report.cumulative_return > 0.0);
+    }
+}
@@ -0,0 +1,85 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::{history::PricePoint, signal::Model};
+
+/// One line of a `--batch-file`: the inputs of a single run, over the command line arguments.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BatchEntry {
+    /// Current ETH price, in place of `--current-price`.
+    pub current_price: u64,
+    /// Price history as `[day, price]` pairs, in place of the configured history source.
+    pub history: Option<Vec<PricePoint>>,
+    /// Models to run, in place of `--ensemble`.
+    pub models: Option<Vec<Model>>,
+    /// BUY threshold in basis points, in place of `--buy-threshold-bps`.
+    pub buy_threshold_bps: Option<u64>,
+    /// SELL threshold in basis points, in place of `--sell-threshold-bps`.
+    pub sell_threshold_bps: Option<u64>,
+}
+
+/// Reads a batch file of one JSON [BatchEntry] per line, skipping blank lines.
+pub fn load(path: &Path) -> Result<Vec<BatchEntry>> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read batch file {}", path.display()))?;
+    parse(&contents).with_context(|| format!("invalid batch file {}", path.display()))
+}
+
+/// Parses batch file contents, see [load].
+pub fn parse(contents: &str) -> Result<Vec<BatchEntry>> {
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line).with_context(|| format!("line {}", index + 1))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_one_entry_per_line() {
+        let entries = parse(concat!(
+            "{\"current_price\": 3200}\n",
+            "\n",
+            "{\"current_price\": 3100, \"history\": [[1, 3000], [2, 3050]], ",
+            "\"models\": [\"linreg\", \"sma-crossover\"], \"buy_threshold_bps\": 20}\n",
+        ))
+        .unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].current_price, 3200);
+        assert_eq!(entries[1].history, Some(vec![(1, 3000), (2, 3050)]));
+        assert_eq!(
+            entries[1].models,
+            Some(vec![Model::Linreg, Model::SmaCrossover])
+        );
+        assert_eq!(
+            (entries[1].buy_threshold_bps, entries[1].sell_threshold_bps),
+            (Some(20), None)
+        );
+
+        let err = parse("{\"current_price\": 3200}\n{\"price\": 3100}\n").unwrap_err();
+        assert_eq!(err.to_string(), "line 2");
+    }
+}
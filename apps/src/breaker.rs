@@ -0,0 +1,119 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{future::Future, time::Duration};
+
+use anyhow::{bail, Result};
+
+/// Counts consecutive failures and trips once `max_failures` is reached.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    max_failures: Option<u32>,
+    failures: u32,
+}
+
+impl CircuitBreaker {
+    /// Creates a breaker that trips after `max_failures` failures in a row, or never if `None`.
+    pub fn new(max_failures: Option<u32>) -> Self {
+        Self {
+            max_failures,
+            failures: 0,
+        }
+    }
+
+    /// Resets the failure count after a successful run.
+    pub fn record_success(&mut self) {
+        self.failures = 0;
+    }
+
+    /// Counts a failed run, returning an error once the breaker trips.
+    pub fn record_failure(&mut self) -> Result<()> {
+        self.failures += 1;
+        match self.max_failures {
+            Some(max) if self.failures >= max => {
+                bail!(
+                    "circuit breaker tripped after {} consecutive failures",
+                    self.failures
+                )
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Calls `step` every `interval`, passing successful results to `on_success` and logging
+/// failures. Only returns once `breaker` trips.
+pub async fn run_loop<T, F, Fut>(
+    interval: Duration,
+    mut breaker: CircuitBreaker,
+    mut step: F,
+    mut on_success: impl FnMut(T),
+) -> Result<()>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    loop {
+        match step().await {
+            Ok(value) => {
+                breaker.record_success();
+                on_success(value);
+            }
+            Err(e) => {
+                tracing::error!("Trading signal run failed: {:?}", e);
+                breaker.record_failure()?;
+            }
+        }
+        tracing::info!("Next run in {}s", interval.as_secs());
+        tokio::time::sleep(interval).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use anyhow::anyhow;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn loop_exits_after_consecutive_failures() {
+        // One success in the middle resets the count, so the breaker trips on the fifth call.
+        let calls = Cell::new(0);
+        let err = run_loop(
+            Duration::ZERO,
+            CircuitBreaker::new(Some(3)),
+            || {
+                calls.set(calls.get() + 1);
+                let call = calls.get();
+                async move {
+                    if call == 2 {
+                        Ok(())
+                    } else {
+                        Err(anyhow!("rpc unavailable"))
+                    }
+                }
+            },
+            |_| {},
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "circuit breaker tripped after 3 consecutive failures"
+        );
+        assert_eq!(calls.get(), 5);
+    }
+}
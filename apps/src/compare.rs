@@ -0,0 +1,117 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Write;
+
+use anyhow::Result;
+use clap::ValueEnum;
+
+use crate::signal::{Action, GuestOutput, Model};
+
+/// One model's output in a side-by-side comparison.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ComparisonRow {
+    /// Model name as given to `--ensemble`, or "ensemble" for all models combined.
+    pub name: String,
+    pub action: Action,
+    pub confidence: u64,
+    pub predicted_price: u64,
+}
+
+/// Runs every model alone, then all of them as an ensemble, on the same input. `run` executes the
+/// guest with the given models selected.
+pub fn compare(mut run: impl FnMut(&[Model]) -> Result<GuestOutput>) -> Result<Vec<ComparisonRow>> {
+    let models = Model::value_variants();
+    let runs = models
+        .iter()
+        .map(|model| (model_name(*model), std::slice::from_ref(model)))
+        .chain([("ensemble".to_string(), models)]);
+
+    let mut rows = Vec::new();
+    for (name, models) in runs {
+        let output = run(models)?;
+        rows.push(ComparisonRow {
+            name,
+            action: output.signal,
+            confidence: output.confidence,
+            predicted_price: output.predicted_price,
+        });
+    }
+    Ok(rows)
+}
+
+/// Formats the rows as an aligned text table.
+pub fn table(rows: &[ComparisonRow]) -> String {
+    let width = rows
+        .iter()
+        .map(|row| row.name.len())
+        .chain(["model".len()])
+        .max()
+        .unwrap_or_default();
+    let mut table = format!(
+        "{:width$}  {:6}  {:>10}  {:>15}\n",
+        "model", "signal", "confidence", "predicted (USD)"
+    );
+    for row in rows {
+        // Writing to a String cannot fail
+        let _ = writeln!(
+            table,
+            "{:width$}  {:6}  {:>9}%  {:>15}",
+            row.name,
+            row.action.to_string(),
+            row.confidence,
+            row.predicted_price
+        );
+    }
+    table
+}
+
+fn model_name(model: Model) -> String {
+    model
+        .to_possible_value()
+        .map(|value| value.get_name().to_string())
+        .unwrap_or_else(|| format!("{model:?}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_model_appears_in_the_table() {
+        let mut runs = Vec::new();
+        let rows = compare(|models| {
+            runs.push(Model::mask(models));
+            Ok(GuestOutput {
+                signal: Action::Buy,
+                confidence: 90,
+                predicted_price: 3300,
+                ..Default::default()
+            })
+        })
+        .unwrap();
+        let table = table(&rows);
+
+        assert_eq!(rows.len(), Model::value_variants().len() + 1);
+        for model in Model::value_variants() {
+            let name = model_name(*model);
+            assert!(
+                table.lines().any(|line| line.starts_with(&name)),
+                "{name} missing from:\n{table}"
+            );
+        }
+        assert!(table.lines().any(|line| line.starts_with("ensemble")));
+        assert_eq!(runs.last(), Some(&Model::mask(Model::value_variants())));
+    }
+}
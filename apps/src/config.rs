@@ -0,0 +1,177 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{ffi::OsString, path::Path};
+
+use anyhow::{bail, Context, Result};
+use clap::Command;
+
+/// Expands a `--config <path>` TOML file into command line arguments.
+///
+/// The file's values are placed before the user's arguments, so with `args_override_self` any
+/// flag given on the command line wins. Values whose environment variable is set are skipped,
+/// giving the precedence: CLI flag > environment (including `.env`) > config file > default.
+pub fn args_with_config_file(command: &Command, args: Vec<OsString>) -> Result<Vec<OsString>> {
+    let Some(path) = find_config_path(&args) else {
+        return Ok(args);
+    };
+    let contents = std::fs::read_to_string(&path)
+        .with_context(|| format!("failed to read config file {}", Path::new(&path).display()))?;
+    let table: toml::Table = contents
+        .parse()
+        .with_context(|| format!("failed to parse config file {}", Path::new(&path).display()))?;
+
+    let mut merged = Vec::with_capacity(args.len() + table.len());
+    merged.extend(args.first().cloned());
+    merged.extend(config_to_args(command, &table)?);
+    merged.extend(args.into_iter().skip(1));
+    Ok(merged)
+}
+
+/// Returns the value of `--config` if present on the command line.
+fn find_config_path(args: &[OsString]) -> Option<OsString> {
+    let mut iter = args.iter().skip(1);
+    while let Some(arg) = iter.next() {
+        let arg = arg.to_string_lossy();
+        if arg == "--config" {
+            return iter.next().cloned();
+        }
+        if let Some(path) = arg.strip_prefix("--config=") {
+            return Some(path.into());
+        }
+    }
+    None
+}
+
+/// Converts the entries of a config table into `--long=value` arguments.
+fn config_to_args(command: &Command, table: &toml::Table) -> Result<Vec<OsString>> {
+    let mut args = Vec::new();
+    for (key, value) in table {
+        let id = key.replace('-', "_");
+        let Some(arg) = command
+            .get_arguments()
+            .find(|arg| arg.get_id() == id.as_str())
+        else {
+            bail!("unknown config key `{}`", key);
+        };
+        let Some(long) = arg.get_long() else {
+            bail!("config key `{}` cannot be set from a file", key);
+        };
+        if arg
+            .get_env()
+            .is_some_and(|env| std::env::var_os(env).is_some())
+        {
+            continue;
+        }
+
+        let values = match value {
+            toml::Value::Array(values) => values.iter().collect(),
+            value => vec![value],
+        };
+        for value in values {
+            match value {
+                toml::Value::Boolean(true) => args.push(format!("--{long}").into()),
+                toml::Value::Boolean(false) => {}
+                toml::Value::String(s) => args.push(format!("--{long}={s}").into()),
+                toml::Value::Integer(i) => args.push(format!("--{long}={i}").into()),
+                toml::Value::Float(f) => args.push(format!("--{long}={f}").into()),
+                _ => bail!("unsupported value for config key `{}`", key),
+            }
+        }
+    }
+    Ok(args)
+}
+
+/// Serializes tests that set environment variables with those relying on them being unset, since
+/// any variable can fill in an argument.
+#[cfg(test)]
+pub static ENV_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use clap::{CommandFactory, Parser};
+
+    use super::*;
+    use crate::Args;
+
+    #[test]
+    fn config_values_are_used_and_cli_overrides() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            r#"
+rpc-url = "http://localhost:8545"
+private_key = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80"
+trading_signal_address = "0x0000000000000000000000000000000000000001"
+current_price = 3500
+outlier_k = 3
+"#
+        )
+        .unwrap();
+        let config_path = file.path().to_str().unwrap();
+
+        let args = args_with_config_file(
+            &Args::command(),
+            ["app", "--config", config_path, "--current-price", "4000"]
+                .map(OsString::from)
+                .to_vec(),
+        )
+        .unwrap();
+        let args = Args::try_parse_from(args).unwrap();
+
+        assert_eq!(args.outlier_k, 3);
+        assert_eq!(args.rpc_url[0].as_str(), "http://localhost:8545/");
+        assert_eq!(args.current_price, 4000);
+    }
+
+    #[test]
+    fn precedence_is_flag_then_env_then_dotenv_then_file() {
+        let mut config_file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(config_file, "current_price = 3500\noutlier_k = 3").unwrap();
+        let config_path = config_file.path().to_str().unwrap();
+        let mut dotenv_file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(dotenv_file, "CURRENT_PRICE=3100").unwrap();
+
+        let _env = ENV_LOCK.lock().unwrap();
+        std::env::set_var("CURRENT_PRICE", "3300");
+        // `.env` values do not replace variables already set in the environment
+        dotenvy::from_path(dotenv_file.path()).unwrap();
+        let parse = |extra: &[&str]| {
+            let mut args = vec!["app", "--dry-run", "--config", config_path];
+            args.extend_from_slice(extra);
+            let args = args_with_config_file(
+                &Args::command(),
+                args.into_iter().map(OsString::from).collect(),
+            )
+            .unwrap();
+            Args::try_parse_from(args).unwrap()
+        };
+        let from_env = parse(&[]);
+        let from_flag = parse(&["--current-price", "4000"]);
+        std::env::remove_var("CURRENT_PRICE");
+
+        assert_eq!(from_env.current_price, 3300);
+        assert_eq!(from_env.outlier_k, 3);
+        assert_eq!(from_flag.current_price, 4000);
+    }
+
+    #[test]
+    fn unknown_config_keys_are_rejected() {
+        let table: toml::Table = "no_such_flag = 1".parse().unwrap();
+        let err = config_to_args(&Args::command(), &table).unwrap_err();
+        assert!(err.to_string().contains("no_such_flag"));
+    }
+}
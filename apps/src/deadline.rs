@@ -0,0 +1,60 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{future::Future, time::Duration};
+
+use anyhow::{anyhow, Result};
+
+/// Runs `future` to completion, or fails with a "deadline exceeded" error once `deadline` has
+/// elapsed. The future is dropped on timeout, cancelling any work still in flight.
+pub async fn run_with_deadline<T>(
+    deadline: Option<Duration>,
+    future: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    let Some(deadline) = deadline else {
+        return future.await;
+    };
+    tokio::time::timeout(deadline, future)
+        .await
+        .map_err(|_| anyhow!("deadline exceeded after {}s", deadline.as_secs_f64()))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn slow_step_trips_deadline() {
+        let slow = async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(())
+        };
+        let err = run_with_deadline(Some(Duration::from_millis(10)), slow)
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("deadline exceeded"),
+            "unexpected error: {err}"
+        );
+
+        let fast = async { Ok(42) };
+        assert_eq!(
+            run_with_deadline(Some(Duration::from_secs(5)), fast)
+                .await
+                .unwrap(),
+            42
+        );
+        assert_eq!(run_with_deadline(None, async { Ok(7) }).await.unwrap(), 7);
+    }
+}
@@ -0,0 +1,396 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{fmt, str::FromStr};
+
+use anyhow::{bail, Context, Result};
+
+use crate::signal::GuestOutput;
+
+/// Names a decision expression can refer to, in the order of [Variables].
+pub const VARIABLES: [&str; 10] = [
+    "predicted",
+    "current",
+    "confidence",
+    "rsi",
+    "macd",
+    "sma_fast",
+    "sma_slow",
+    "regression",
+    "slope",
+    "last",
+];
+
+/// Values of the [VARIABLES] for one run.
+pub type Variables = [f64; VARIABLES.len()];
+
+/// The values a decision expression sees: the predicted and current prices and the indicator
+/// panel in whole USD (the MACD histogram too), the confidence in percent, the regression slope in
+/// USD per day and the last price of the input series.
+pub fn variables(current_price: u64, output: &GuestOutput) -> Variables {
+    [
+        output.predicted_price as f64,
+        current_price as f64,
+        output.confidence as f64,
+        output.panel.rsi as f64,
+        output.panel.macd_histogram as f64 / 1e6,
+        output.panel.sma_fast as f64,
+        output.panel.sma_slow as f64,
+        output.panel.regression_price as f64,
+        output.slope as f64,
+        output.last_price as f64,
+    ]
+}
+
+/// A boolean rule over the guest's outputs, such as `predicted > current * 1.01 && rsi < 60`.
+///
+/// Numbers, the [VARIABLES], `+ - * /`, comparisons (`< <= > >= == !=`), `&& || !` and
+/// parentheses are supported, with the usual precedence. The expression is checked when parsed,
+/// so a parsed rule always evaluates.
+#[derive(Clone, PartialEq)]
+pub struct DecisionExpr {
+    source: String,
+    root: Node,
+}
+
+impl DecisionExpr {
+    /// Whether the rule holds for `values`.
+    pub fn holds(&self, values: &Variables) -> bool {
+        self.root.eval(values) != 0.0
+    }
+}
+
+impl FromStr for DecisionExpr {
+    type Err = anyhow::Error;
+
+    fn from_str(source: &str) -> Result<Self> {
+        let mut parser = Parser {
+            tokens: tokenize(source)?,
+            next: 0,
+        };
+        let root = parser.or()?;
+        if let Some(token) = parser.tokens.get(parser.next) {
+            bail!("unexpected {} after the expression", token);
+        }
+        if root.kind()? != Kind::Bool {
+            bail!("the expression must be a condition, not a number");
+        }
+        Ok(Self {
+            source: source.to_string(),
+            root,
+        })
+    }
+}
+
+impl fmt::Debug for DecisionExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.source)
+    }
+}
+
+impl fmt::Display for DecisionExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Name(String),
+    Symbol(&'static str),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Number(value) => write!(f, "number {}", value),
+            Self::Name(name) => write!(f, "'{}'", name),
+            Self::Symbol(symbol) => write!(f, "'{}'", symbol),
+        }
+    }
+}
+
+// Longest first, so that `<=` is not read as `<` followed by `=`
+const SYMBOLS: [&str; 15] = [
+    "&&", "||", "<=", ">=", "==", "!=", "<", ">", "+", "-", "*", "/", "!", "(", ")",
+];
+
+fn tokenize(source: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut rest = source.trim_start();
+    while !rest.is_empty() {
+        let len = if rest.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+            let len = rest
+                .find(|c: char| !c.is_ascii_digit() && c != '.')
+                .unwrap_or(rest.len());
+            let number = &rest[..len];
+            tokens.push(Token::Number(
+                number
+                    .parse()
+                    .with_context(|| format!("invalid number '{}'", number))?,
+            ));
+            len
+        } else if rest.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+            let len = rest
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                .unwrap_or(rest.len());
+            tokens.push(Token::Name(rest[..len].to_string()));
+            len
+        } else if let Some(symbol) = SYMBOLS.iter().find(|symbol| rest.starts_with(**symbol)) {
+            tokens.push(Token::Symbol(symbol));
+            symbol.len()
+        } else {
+            bail!("unexpected character '{}'", rest.chars().next().unwrap());
+        };
+        rest = rest[len..].trim_start();
+    }
+    Ok(tokens)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Kind {
+    Number,
+    Bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Node {
+    Number(f64),
+    Variable(usize),
+    Negate(Box<Node>),
+    Not(Box<Node>),
+    Binary(&'static str, Box<Node>, Box<Node>),
+}
+
+impl Node {
+    /// What the node evaluates to, or why its operands do not fit its operator.
+    fn kind(&self) -> Result<Kind> {
+        let expect = |node: &Node, kind: Kind, op: &str| -> Result<()> {
+            if node.kind()? != kind {
+                bail!("'{}' needs {} operands", op, describe(kind));
+            }
+            Ok(())
+        };
+        match self {
+            Self::Number(_) | Self::Variable(_) => Ok(Kind::Number),
+            Self::Negate(operand) => expect(operand, Kind::Number, "-").map(|_| Kind::Number),
+            Self::Not(operand) => expect(operand, Kind::Bool, "!").map(|_| Kind::Bool),
+            Self::Binary(op, lhs, rhs) => {
+                let (operands, result) = match *op {
+                    "&&" | "||" => (Kind::Bool, Kind::Bool),
+                    "+" | "-" | "*" | "/" => (Kind::Number, Kind::Number),
+                    _ => (Kind::Number, Kind::Bool),
+                };
+                expect(lhs, operands, op)?;
+                expect(rhs, operands, op)?;
+                Ok(result)
+            }
+        }
+    }
+
+    /// Evaluates the node, with conditions as 1 for true and 0 for false.
+    fn eval(&self, values: &Variables) -> f64 {
+        let truth = |condition: bool| if condition { 1.0 } else { 0.0 };
+        match self {
+            Self::Number(value) => *value,
+            Self::Variable(index) => values[*index],
+            Self::Negate(operand) => -operand.eval(values),
+            Self::Not(operand) => truth(operand.eval(values) == 0.0),
+            Self::Binary(op, lhs, rhs) => {
+                let lhs = lhs.eval(values);
+                // `&&` and `||` only look at the right-hand side when they have to
+                match *op {
+                    "&&" => return truth(lhs != 0.0 && rhs.eval(values) != 0.0),
+                    "||" => return truth(lhs != 0.0 || rhs.eval(values) != 0.0),
+                    _ => {}
+                }
+                let rhs = rhs.eval(values);
+                match *op {
+                    "+" => lhs + rhs,
+                    "-" => lhs - rhs,
+                    "*" => lhs * rhs,
+                    "/" => lhs / rhs,
+                    "<" => truth(lhs < rhs),
+                    "<=" => truth(lhs <= rhs),
+                    ">" => truth(lhs > rhs),
+                    ">=" => truth(lhs >= rhs),
+                    "==" => truth(lhs == rhs),
+                    _ => truth(lhs != rhs),
+                }
+            }
+        }
+    }
+}
+
+fn describe(kind: Kind) -> &'static str {
+    match kind {
+        Kind::Number => "numeric",
+        Kind::Bool => "condition",
+    }
+}
+
+// Recursive descent, one method per precedence level from the loosest
+struct Parser {
+    tokens: Vec<Token>,
+    next: usize,
+}
+
+impl Parser {
+    fn or(&mut self) -> Result<Node> {
+        self.binary(&["||"], Self::and)
+    }
+
+    fn and(&mut self) -> Result<Node> {
+        self.binary(&["&&"], Self::comparison)
+    }
+
+    fn comparison(&mut self) -> Result<Node> {
+        let lhs = self.sum()?;
+        match self.symbol(&["<", "<=", ">", ">=", "==", "!="]) {
+            Some(op) => Ok(Node::Binary(op, Box::new(lhs), Box::new(self.sum()?))),
+            None => Ok(lhs),
+        }
+    }
+
+    fn sum(&mut self) -> Result<Node> {
+        self.binary(&["+", "-"], Self::product)
+    }
+
+    fn product(&mut self) -> Result<Node> {
+        self.binary(&["*", "/"], Self::unary)
+    }
+
+    fn unary(&mut self) -> Result<Node> {
+        match self.symbol(&["-", "!"]) {
+            Some("-") => Ok(Node::Negate(Box::new(self.unary()?))),
+            Some(_) => Ok(Node::Not(Box::new(self.unary()?))),
+            None => self.primary(),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Node> {
+        let token = self
+            .tokens
+            .get(self.next)
+            .cloned()
+            .context("the expression ends too early")?;
+        self.next += 1;
+        match token {
+            Token::Number(value) => Ok(Node::Number(value)),
+            Token::Name(name) => VARIABLES
+                .iter()
+                .position(|variable| *variable == name)
+                .map(Node::Variable)
+                .with_context(|| {
+                    format!(
+                        "unknown variable '{}', expected one of {}",
+                        name,
+                        VARIABLES.join(", ")
+                    )
+                }),
+            Token::Symbol("(") => {
+                let inner = self.or()?;
+                if self.symbol(&[")"]).is_none() {
+                    bail!("unclosed '('");
+                }
+                Ok(inner)
+            }
+            token => bail!("unexpected {}", token),
+        }
+    }
+
+    // Left-associative chain of `ops` between operands parsed by `operand`
+    fn binary(
+        &mut self,
+        ops: &[&'static str],
+        operand: fn(&mut Self) -> Result<Node>,
+    ) -> Result<Node> {
+        let mut lhs = operand(self)?;
+        while let Some(op) = self.symbol(ops) {
+            lhs = Node::Binary(op, Box::new(lhs), Box::new(operand(self)?));
+        }
+        Ok(lhs)
+    }
+
+    // Consumes the next token if it is one of `symbols`
+    fn symbol(&mut self, symbols: &[&'static str]) -> Option<&'static str> {
+        match self.tokens.get(self.next) {
+            Some(Token::Symbol(symbol)) if symbols.contains(symbol) => {
+                self.next += 1;
+                Some(*symbol)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signal::IndicatorPanel;
+
+    #[test]
+    fn evaluates_a_rule_over_the_panel() {
+        let expr: DecisionExpr = "predicted > current * 1.01 && rsi < 60".parse().unwrap();
+        let output = |predicted_price: u64, rsi: u64| GuestOutput {
+            predicted_price,
+            panel: IndicatorPanel {
+                rsi,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        assert!(expr.holds(&variables(3200, &output(3264, 55))));
+        assert!(
+            !expr.holds(&variables(3200, &output(3210, 55))),
+            "a 0.3% rise is too small"
+        );
+        assert!(
+            !expr.holds(&variables(3200, &output(3264, 72))),
+            "the market is overbought"
+        );
+
+        let expr: DecisionExpr = "!(macd < 0) || -slope >= 2 * (sma_fast - sma_slow)"
+            .parse()
+            .unwrap();
+        let mut bearish = output(3264, 55);
+        bearish.panel.macd_histogram = -1_500_000;
+        bearish.slope = -4;
+        bearish.panel.sma_fast = 3202;
+        bearish.panel.sma_slow = 3200;
+        assert!(expr.holds(&variables(3200, &bearish)));
+    }
+
+    #[test]
+    fn rejects_malformed_expressions() {
+        for source in [
+            "",
+            "predicted >",
+            "volume > 3",
+            "predicted + 1",
+            "(rsi < 60",
+            "rsi < 60 && 1",
+            "rsi # 3",
+            "rsi < 60 60",
+        ] {
+            assert!(
+                source.parse::<DecisionExpr>().is_err(),
+                "{:?} should not parse",
+                source
+            );
+        }
+    }
+}
@@ -0,0 +1,169 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{fmt, path::Path};
+
+use alloy::primitives::Address;
+use anyhow::{Context, Result};
+use boundless_market::Deployment;
+use serde::Deserialize;
+
+/// A Boundless deployment as written in a `--deployment-file`, with the fields of [Deployment].
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DeploymentFile {
+    chain_id: Option<u64>,
+    boundless_market_address: Address,
+    verifier_router_address: Option<Address>,
+    set_verifier_address: Address,
+    collateral_token_address: Option<Address>,
+    order_stream_url: Option<String>,
+}
+
+/// Reads a deployment from a JSON file, for networks the chain ID does not resolve correctly.
+pub fn load(path: &Path) -> Result<Deployment> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read deployment from {}", path.display()))?;
+    let file: DeploymentFile = serde_json::from_str(&contents)
+        .with_context(|| format!("failed to parse deployment file {}", path.display()))?;
+
+    let mut builder = Deployment::builder();
+    builder
+        .boundless_market_address(file.boundless_market_address)
+        .set_verifier_address(file.set_verifier_address);
+    if let Some(chain_id) = file.chain_id {
+        builder.chain_id(chain_id);
+    }
+    if let Some(address) = file.verifier_router_address {
+        builder.verifier_router_address(address);
+    }
+    if let Some(address) = file.collateral_token_address {
+        builder.collateral_token_address(address);
+    }
+    if let Some(url) = file.order_stream_url {
+        builder.order_stream_url(url);
+    }
+    builder
+        .build()
+        .with_context(|| format!("invalid deployment file {}", path.display()))
+}
+
+/// Returns `configured` if set, and otherwise the deployment the Boundless client resolves for
+/// `chain_id`.
+pub fn resolve(configured: Option<Deployment>, chain_id: u64) -> Result<Deployment> {
+    match configured {
+        Some(deployment) => Ok(deployment),
+        None => Deployment::from_chain_id(chain_id).with_context(|| {
+            format!("no known Boundless deployment for chain ID {chain_id}, pass --deployment-file")
+        }),
+    }
+}
+
+/// Lists the fields of `deployment`, one per line, with `-` for those it leaves unset.
+pub fn describe(deployment: &Deployment) -> String {
+    fn or_dash(value: Option<impl fmt::Display>) -> String {
+        value.map_or_else(|| "-".to_string(), |value| value.to_string())
+    }
+    [
+        ("Chain ID", or_dash(deployment.chain_id)),
+        (
+            "Boundless market",
+            deployment.boundless_market_address.to_string(),
+        ),
+        (
+            "Verifier router",
+            or_dash(deployment.verifier_router_address),
+        ),
+        ("Set verifier", deployment.set_verifier_address.to_string()),
+        (
+            "Collateral token",
+            or_dash(deployment.collateral_token_address),
+        ),
+        (
+            "Order stream",
+            or_dash(deployment.order_stream_url.as_ref()),
+        ),
+    ]
+    .map(|(name, value)| format!("{name}: {value}"))
+    .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::address;
+
+    use super::*;
+
+    #[test]
+    fn loads_deployment_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("deployment.json");
+        std::fs::write(
+            &path,
+            r#"{
+                "chain_id": 11155111,
+                "boundless_market_address": "0x0000000000000000000000000000000000000001",
+                "set_verifier_address": "0x0000000000000000000000000000000000000002",
+                "order_stream_url": "https://order-stream.example.com"
+            }"#,
+        )
+        .unwrap();
+
+        let deployment = load(&path).unwrap();
+        assert_eq!(deployment.chain_id, Some(11155111));
+        assert_eq!(
+            deployment.boundless_market_address,
+            address!("0x0000000000000000000000000000000000000001")
+        );
+        assert_eq!(
+            deployment.set_verifier_address,
+            address!("0x0000000000000000000000000000000000000002")
+        );
+        assert_eq!(deployment.verifier_router_address, None);
+        assert_eq!(
+            deployment.order_stream_url.as_deref(),
+            Some("https://order-stream.example.com")
+        );
+
+        std::fs::write(&path, r#"{"chain_id": 1}"#).unwrap();
+        assert!(load(&path).is_err());
+    }
+
+    #[test]
+    fn describes_the_deployment_of_a_known_chain() {
+        // Sepolia
+        let deployment = resolve(None, 11155111).unwrap();
+        let description = describe(&deployment);
+        assert_eq!(description.lines().count(), 6);
+        assert!(description.starts_with("Chain ID: 11155111\n"));
+        assert!(
+            description.lines().all(|line| !line.ends_with(": -")),
+            "every field should be populated:\n{description}"
+        );
+        assert!(description.contains(&format!(
+            "Boundless market: {}",
+            deployment.boundless_market_address
+        )));
+
+        assert!(resolve(None, 1337).is_err());
+        let configured = Deployment::builder()
+            .boundless_market_address(Address::with_last_byte(1))
+            .set_verifier_address(Address::with_last_byte(2))
+            .build()
+            .unwrap();
+        let description = describe(&resolve(Some(configured), 11155111).unwrap());
+        assert!(description.contains("Chain ID: -"));
+        assert!(description.contains("Verifier router: -"));
+    }
+}
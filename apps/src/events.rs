@@ -0,0 +1,157 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloy::{
+    eips::BlockNumberOrTag,
+    primitives::{Address, B256, U256},
+    providers::Provider,
+    rpc::types::{Filter, Log},
+    sol_types::SolEvent,
+};
+use anyhow::{Context, Result};
+use trading_journal::format_wei;
+
+use crate::{signal::Action, trading_signal::ITradingSignal::SignalUpdated};
+
+/// A signal published to the TradingSignal contract, as recorded by its `SignalUpdated` event.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignalEvent {
+    pub block_number: Option<u64>,
+    pub tx_hash: Option<B256>,
+    pub action: Action,
+    pub confidence: U256,
+    /// Predicted price in base units, as stored by the contract.
+    pub predicted_price: U256,
+    pub input_hash: B256,
+    /// Block timestamp at which the contract stored the signal.
+    pub timestamp: U256,
+}
+
+impl SignalEvent {
+    /// Formats the event as one line of the signal history, with the predicted price in units
+    /// with `decimals` decimals.
+    pub fn line(&self, decimals: u8) -> String {
+        format!(
+            "block {} tx {} at {}: {} with {}% confidence, predicted ${}, input {}",
+            self.block_number
+                .map_or_else(|| "pending".to_string(), |n| n.to_string()),
+            self.tx_hash
+                .map_or_else(|| "unknown".to_string(), |hash| hash.to_string()),
+            self.timestamp,
+            self.action,
+            self.confidence,
+            format_wei(self.predicted_price, decimals),
+            self.input_hash
+        )
+    }
+}
+
+/// Decodes a `SignalUpdated` log emitted by the TradingSignal contract.
+pub fn decode_signal_log(log: &Log) -> Result<SignalEvent> {
+    let event = log
+        .log_decode::<SignalUpdated>()
+        .context("failed to decode SignalUpdated log")?;
+    let SignalUpdated {
+        action,
+        confidence,
+        predictedPrice,
+        inputHash,
+        timestamp,
+    } = event.inner.data;
+    Ok(SignalEvent {
+        block_number: log.block_number,
+        tx_hash: log.transaction_hash,
+        action: Action::try_from(action).context("SignalUpdated log has an invalid action")?,
+        confidence,
+        predicted_price: predictedPrice,
+        input_hash: inputHash,
+        timestamp,
+    })
+}
+
+/// Reads the signals the contract at `address` published from `since_block` to the latest block,
+/// oldest first.
+pub async fn scan_signals<P: Provider>(
+    provider: P,
+    address: Address,
+    since_block: u64,
+) -> Result<Vec<SignalEvent>> {
+    let filter = Filter::new()
+        .address(address)
+        .event_signature(SignalUpdated::SIGNATURE_HASH)
+        .from_block(since_block)
+        .to_block(BlockNumberOrTag::Latest);
+    let logs = provider
+        .get_logs(&filter)
+        .await
+        .with_context(|| format!("failed to read SignalUpdated logs of contract {}", address))?;
+    logs.iter().map(decode_signal_log).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::{address, b256, Bytes, LogData};
+
+    use super::*;
+
+    #[test]
+    fn decodes_signal_updated_log() {
+        // SignalUpdated(uint8 indexed action, uint256 confidence, uint256 predictedPrice,
+        //               bytes32 inputHash, uint256 timestamp)
+        let input_hash = B256::repeat_byte(0x22);
+        let predicted_price = U256::from(3750u64) * U256::from(10u64).pow(U256::from(18u64));
+        let data = [
+            U256::from(85u64).to_be_bytes::<32>(),
+            predicted_price.to_be_bytes::<32>(),
+            input_hash.0,
+            U256::from(1_700_000_000u64).to_be_bytes::<32>(),
+        ]
+        .concat();
+        let tx_hash = b256!("0x00000000000000000000000000000000000000000000000000000000000000aa");
+        let log = Log {
+            inner: alloy::primitives::Log {
+                address: address!("0x0000000000000000000000000000000000000001"),
+                data: LogData::new_unchecked(
+                    vec![SignalUpdated::SIGNATURE_HASH, B256::with_last_byte(1)],
+                    Bytes::from(data),
+                ),
+            },
+            block_number: Some(42),
+            transaction_hash: Some(tx_hash),
+            ..Default::default()
+        };
+
+        let event = decode_signal_log(&log).unwrap();
+        assert_eq!(
+            event,
+            SignalEvent {
+                block_number: Some(42),
+                tx_hash: Some(tx_hash),
+                action: Action::Buy,
+                confidence: U256::from(85u64),
+                predicted_price,
+                input_hash,
+                timestamp: U256::from(1_700_000_000u64),
+            }
+        );
+        assert!(event
+            .line(18)
+            .contains("BUY with 85% confidence, predicted $3750"));
+
+        // Other events of the contract are not signals
+        let mut other = log.clone();
+        other.inner.data = LogData::new_unchecked(vec![B256::repeat_byte(0x33)], Bytes::new());
+        assert!(decode_signal_log(&other).is_err());
+    }
+}
@@ -0,0 +1,192 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::{bail, Context, Result};
+use guests::TRADING_SIGNAL_ELF;
+use risc0_zkvm::{default_executor, ExecutorEnv, ExitCode, SessionInfo};
+use trading_journal::EXIT_INVALID_INPUT;
+
+/// Executes the trading signal guest locally on the given input, without proving.
+///
+/// Fails unless the guest halts successfully, reporting input it rejected as such.
+pub fn execute(input: &[u8]) -> Result<SessionInfo> {
+    execute_with_limit(input, None)
+}
+
+/// Like [execute], but aborts the execution once it exceeds `max_cycles` cycles, if set, so that
+/// an input too expensive to prove fails fast.
+pub fn execute_with_limit(input: &[u8], max_cycles: Option<u64>) -> Result<SessionInfo> {
+    let env = ExecutorEnv::builder()
+        .write_slice(input)
+        .session_limit(max_cycles)
+        .build()
+        .context("failed to build executor environment")?;
+    let session_info = default_executor()
+        .execute(env, TRADING_SIGNAL_ELF)
+        .with_context(|| match max_cycles {
+            Some(max_cycles) => format!(
+                "failed to execute trading signal guest within --max-cycles {}",
+                max_cycles
+            ),
+            None => "failed to execute trading signal guest".to_string(),
+        })?;
+    match session_info.exit_code {
+        ExitCode::Halted(0) => Ok(session_info),
+        ExitCode::Halted(code) if code == EXIT_INVALID_INPUT as u32 => {
+            bail!("invalid guest input: the guest could not decode it or rejected its parameters")
+        }
+        code => bail!("trading signal guest exited with {:?}", code),
+    }
+}
+
+/// Re-executes the guest locally on `input` and checks that it commits `journal`.
+///
+/// This catches a prover returning a journal other than the one the guest computes, independently
+/// of the seal, which only proves that *some* execution of the image committed it.
+pub fn verify_journal(input: &[u8], journal: &[u8]) -> Result<()> {
+    let session_info = execute(input)?;
+    compare_journals(&session_info.journal.bytes, journal)
+}
+
+/// Fails with the first differing 32-byte word if `returned` is not the `expected` journal.
+pub fn compare_journals(expected: &[u8], returned: &[u8]) -> Result<()> {
+    if expected.len() != returned.len() {
+        bail!(
+            "returned journal is {} bytes, local execution committed {}",
+            returned.len(),
+            expected.len()
+        );
+    }
+    if let Some(word) = expected
+        .chunks(32)
+        .zip(returned.chunks(32))
+        .position(|(expected, returned)| expected != returned)
+    {
+        bail!(
+            "returned journal differs from local execution at word {}: expected 0x{}, got 0x{}",
+            word,
+            hex::encode(expected.chunks(32).nth(word).unwrap_or_default()),
+            hex::encode(returned.chunks(32).nth(word).unwrap_or_default())
+        );
+    }
+    Ok(())
+}
+
+/// Cycle usage of a guest execution, used to estimate proving cost.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CycleReport {
+    /// Total user cycles across all segments.
+    pub total_cycles: u64,
+    /// (user cycles, po2) for each segment.
+    pub segments: Vec<(u64, u32)>,
+}
+
+impl CycleReport {
+    pub fn from_session(session_info: &SessionInfo) -> Self {
+        Self {
+            total_cycles: session_info.cycles(),
+            segments: session_info
+                .segments
+                .iter()
+                .map(|segment| (segment.cycles as u64, segment.po2))
+                .collect(),
+        }
+    }
+
+    /// Logs the report.
+    pub fn log(&self) {
+        tracing::info!(
+            "Guest used {} user cycles across {} segment(s)",
+            self.total_cycles,
+            self.segments.len()
+        );
+        for (index, (cycles, po2)) in self.segments.iter().enumerate() {
+            tracing::info!("  segment {}: {} user cycles (po2 {})", index, cycles, po2);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{primitives::U256, sol_types::SolValue};
+    use trading_journal::GuestInput;
+
+    use super::*;
+
+    fn input(history_len: u64) -> Vec<u8> {
+        let history: Vec<(u64, u64)> = (1..=history_len).map(|day| (day, 3000 + day)).collect();
+        GuestInput {
+            current_price: U256::from(3200u64),
+            history,
+            buy_bps: 50,
+            sell_bps: 50,
+            ..Default::default()
+        }
+        .encode()
+    }
+
+    fn profile(history_len: u64) -> CycleReport {
+        CycleReport::from_session(&execute(&input(history_len)).unwrap())
+    }
+
+    #[test]
+    fn altered_journal_fails_comparison() {
+        let journal = (U256::from(1u64), U256::from(80u64), U256::from(3250u64)).abi_encode();
+        compare_journals(&journal, &journal).unwrap();
+
+        let mut altered = journal.clone();
+        altered[95] ^= 1;
+        let err = compare_journals(&journal, &altered).unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("returned journal differs from local execution at word 2"),
+            "unexpected error: {err}"
+        );
+
+        let err = compare_journals(&journal, &journal[..64]).unwrap_err();
+        assert!(
+            err.to_string().contains("64 bytes"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn cycle_count_grows_with_input_length() {
+        let short = profile(10);
+        let long = profile(200);
+
+        assert!(short.total_cycles > 0);
+        assert!(!short.segments.is_empty());
+        assert!(
+            long.total_cycles > short.total_cycles,
+            "{} cycles for 200 points should exceed {} for 10",
+            long.total_cycles,
+            short.total_cycles
+        );
+    }
+
+    #[test]
+    fn max_cycles_aborts_an_oversized_execution() {
+        let err = execute_with_limit(&input(1000), Some(100_000)).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("failed to execute trading signal guest within --max-cycles 100000"),
+            "unexpected error: {err:#}"
+        );
+        assert!(
+            format!("{err:#}").contains("Session limit exceeded"),
+            "unexpected error: {err:#}"
+        );
+    }
+}
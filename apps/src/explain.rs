@@ -0,0 +1,196 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{history::PricePoint, signal::Action};
+
+/// Intermediate values of the guest's linear regression model, recomputed on the host.
+///
+/// This is informational: it mirrors the guest's integer arithmetic for a plain regression, but
+/// ignores clamping, outlier trimming, the slope prior and other models, and is not proven.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Explanation {
+    pub sum_x: i64,
+    pub sum_y: i64,
+    pub mean_x: i64,
+    pub mean_y: i64,
+    pub slope: i64,
+    pub intercept: i64,
+    /// Coefficient of determination as a percentage (0-100), used as the confidence.
+    pub r_squared: u64,
+    /// Day the prediction is made for, the day after the last point.
+    pub next_day: i64,
+    /// Predicted USD price on `next_day`, clamped at zero.
+    pub predicted_price: u64,
+    /// Price above which the prediction signals BUY.
+    pub buy_threshold: u128,
+    /// Price below which the prediction signals SELL.
+    pub sell_threshold: u128,
+    pub action: Action,
+}
+
+impl Explanation {
+    /// Fits `history` as the guest does and classifies the prediction against `current_price`.
+    pub fn compute(
+        history: &[PricePoint],
+        current_price: u64,
+        buy_bps: u64,
+        sell_bps: u64,
+    ) -> Self {
+        let n = history.len() as i64;
+        let sum_x: i64 = history.iter().map(|(x, _)| *x as i64).sum();
+        let sum_y: i64 = history.iter().map(|(_, y)| *y as i64).sum();
+        let mean_x = sum_x / n;
+        let mean_y = sum_y / n;
+
+        let (mut numerator, mut denominator, mut sum_squared_total) = (0i64, 0i64, 0i64);
+        for (x, y) in history {
+            let x_diff = *x as i64 - mean_x;
+            let y_diff = *y as i64 - mean_y;
+            numerator += x_diff * y_diff;
+            denominator += x_diff * x_diff;
+            sum_squared_total += y_diff * y_diff;
+        }
+        let slope = if denominator != 0 {
+            numerator / denominator
+        } else {
+            0
+        };
+        let intercept = mean_y - slope * mean_x;
+
+        let sum_squared_errors: i64 = history
+            .iter()
+            .map(|(x, y)| {
+                let error = *y as i64 - (slope * *x as i64 + intercept);
+                error * error
+            })
+            .sum();
+        let r_squared = if sum_squared_total > 0 {
+            ((sum_squared_total - sum_squared_errors) * 100 / sum_squared_total).clamp(0, 100)
+                as u64
+        } else {
+            0
+        };
+
+        let next_day = history[history.len() - 1].0 as i64 + 1;
+        let predicted_price = (slope * next_day + intercept).max(0) as u64;
+
+        // The guest compares in basis points without dividing; these are the equivalent prices
+        let buy_threshold = current_price as u128 * (10_000 + buy_bps as u128) / 10_000;
+        let sell_threshold =
+            current_price as u128 * 10_000u128.saturating_sub(sell_bps as u128) / 10_000;
+        let scaled = predicted_price as u128 * 10_000;
+        let action = if scaled > current_price as u128 * (10_000 + buy_bps as u128) {
+            Action::Buy
+        } else if scaled < current_price as u128 * 10_000u128.saturating_sub(sell_bps as u128) {
+            Action::Sell
+        } else {
+            Action::Hold
+        };
+
+        Self {
+            sum_x,
+            sum_y,
+            mean_x,
+            mean_y,
+            slope,
+            intercept,
+            r_squared,
+            next_day,
+            predicted_price,
+            buy_threshold,
+            sell_threshold,
+            action,
+        }
+    }
+
+    /// Prints each step of the computation.
+    pub fn log(&self) {
+        tracing::info!("Explaining the linear regression model (informational, not proven)");
+        tracing::info!("1. sum_x = {}, sum_y = {}", self.sum_x, self.sum_y);
+        tracing::info!(
+            "2. mean_x = {}, mean_y = {} (integer division)",
+            self.mean_x,
+            self.mean_y
+        );
+        tracing::info!(
+            "3. slope = Σ(x - mean_x)(y - mean_y) / Σ(x - mean_x)² = {} USD/day",
+            self.slope
+        );
+        tracing::info!(
+            "4. intercept = mean_y - slope * mean_x = {}",
+            self.intercept
+        );
+        tracing::info!("5. R² = {}% (the confidence)", self.r_squared);
+        tracing::info!(
+            "6. predicted price on day {} = slope * {} + intercept = ${} USD",
+            self.next_day,
+            self.next_day,
+            self.predicted_price
+        );
+        tracing::info!(
+            "7. BUY above ${} USD, SELL below ${} USD",
+            self.buy_threshold,
+            self.sell_threshold
+        );
+        tracing::info!("8. signal = {}", self.action);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::U256;
+    use trading_journal::GuestInput;
+
+    use super::*;
+    use crate::{executor, signal::GuestOutput};
+
+    #[test]
+    fn explains_exact_line() {
+        // An odd number of days keeps the integer means exact
+        let history: Vec<PricePoint> = (1..=11).map(|day| (day, 3000 + 10 * day)).collect();
+        let explanation = Explanation::compute(&history, 3000, 50, 50);
+        assert_eq!((explanation.sum_x, explanation.sum_y), (66, 33660));
+        assert_eq!((explanation.slope, explanation.intercept), (10, 3000));
+        assert_eq!(explanation.predicted_price, 3120);
+        assert_eq!(
+            (explanation.buy_threshold, explanation.sell_threshold),
+            (3015, 2985)
+        );
+        assert_eq!(explanation.action, Action::Buy);
+    }
+
+    #[test]
+    fn explained_slope_matches_guest() {
+        let history: Vec<PricePoint> = (1..=30)
+            .map(|day| (day, 3000 + 17 * day + (day * day) % 23))
+            .collect();
+        let explanation = Explanation::compute(&history, 3400, 50, 50);
+
+        let input = GuestInput {
+            current_price: U256::from(3400u64),
+            history,
+            buy_bps: 50,
+            sell_bps: 50,
+            ..Default::default()
+        }
+        .encode();
+        let session_info = executor::execute(&input).unwrap();
+        let output = GuestOutput::decode(&session_info.journal.bytes, 0).unwrap();
+
+        assert_eq!(explanation.slope, output.slope);
+        assert_eq!(explanation.r_squared, output.confidence);
+        assert_eq!(explanation.predicted_price, output.predicted_price);
+        assert_eq!(explanation.action, output.signal);
+    }
+}
@@ -0,0 +1,104 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloy::primitives::U256;
+use trading_journal::format_wei;
+
+/// Decimals of ETH amounts in wei.
+const ETHER_DECIMALS: u8 = 18;
+/// Decimals of gas prices in wei, shown in gwei.
+const GWEI_DECIMALS: u8 = 9;
+
+/// Gas budgeted for `setSignal`, dominated by on-chain proof verification.
+///
+/// The call cannot be estimated against the node before a proof exists, since verification of a
+/// placeholder seal would revert, so a conservative fixed figure is used instead.
+pub const SET_SIGNAL_GAS_ESTIMATE: u64 = 350_000;
+
+/// Estimated cost of a proving round, in wei.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FeeEstimate {
+    /// Price the proving market starts the request's auction at.
+    pub min_proving_price: U256,
+    /// Highest price the request offers to pay a prover.
+    pub max_proving_price: U256,
+    /// Current gas price of the chain.
+    pub gas_price: u128,
+    /// Gas expected for the `setSignal` transaction.
+    pub set_signal_gas: u64,
+}
+
+impl FeeEstimate {
+    /// Cost of the `setSignal` transaction.
+    pub fn set_signal_cost(&self) -> U256 {
+        U256::from(self.gas_price) * U256::from(self.set_signal_gas)
+    }
+
+    /// Lowest and highest total cost, depending on where the proving auction settles.
+    pub fn total_range(&self) -> (U256, U256) {
+        let gas_cost = self.set_signal_cost();
+        (
+            self.min_proving_price + gas_cost,
+            self.max_proving_price + gas_cost,
+        )
+    }
+
+    /// Logs the estimate.
+    pub fn log(&self) {
+        let (min_total, max_total) = self.total_range();
+        tracing::info!(
+            "Proving price: {} - {} ETH",
+            format_wei(self.min_proving_price, ETHER_DECIMALS),
+            format_wei(self.max_proving_price, ETHER_DECIMALS)
+        );
+        tracing::info!(
+            "setSignal gas: ~{} at {} gwei = {} ETH",
+            self.set_signal_gas,
+            format_wei(U256::from(self.gas_price), GWEI_DECIMALS),
+            format_wei(self.set_signal_cost(), ETHER_DECIMALS)
+        );
+        tracing::info!(
+            "Estimated total: {} - {} ETH",
+            format_wei(min_total, ETHER_DECIMALS),
+            format_wei(max_total, ETHER_DECIMALS)
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn total_adds_gas_cost_to_proving_price_range() {
+        let estimate = FeeEstimate {
+            min_proving_price: U256::from(1_000_000_000_000u64),
+            max_proving_price: U256::from(5_000_000_000_000u64),
+            gas_price: 2_000_000_000, // 2 gwei
+            set_signal_gas: 300_000,
+        };
+
+        assert_eq!(
+            estimate.set_signal_cost(),
+            U256::from(600_000_000_000_000u64)
+        );
+        assert_eq!(
+            estimate.total_range(),
+            (
+                U256::from(601_000_000_000_000u64),
+                U256::from(605_000_000_000_000u64)
+            )
+        );
+    }
+}
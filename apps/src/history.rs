@@ -0,0 +1,448 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{io::Write, path::Path};
+
+use alloy::{primitives::Address, providers::Provider};
+use anyhow::{bail, Context, Result};
+use clap::ValueEnum;
+use trading_journal::PRICE_HISTORY;
+
+use crate::history::price_history::IPriceHistory;
+
+mod price_history {
+    alloy::sol!(
+        #![sol(rpc)]
+        "../contracts/src/IPriceHistory.sol"
+    );
+}
+
+/// A single point of the price history: (day_index, usd_price_per_eth).
+pub type PricePoint = (u64, u64);
+
+/// A daily OHLC candle: (day_index, open, high, low, close), in USD per ETH.
+pub type Candle = (u64, u64, u64, u64, u64);
+
+/// Where the price history sent to the guest comes from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum HistorySource {
+    /// `--price-history-csv`, or the guest's embedded series without it.
+    #[default]
+    Csv,
+    /// `getPriceHistory()` on `--history-contract`.
+    Onchain,
+}
+
+/// Reads a price history from a contract implementing `IPriceHistory`.
+pub async fn read_onchain_history<P: Provider>(
+    provider: P,
+    address: Address,
+) -> Result<Vec<PricePoint>> {
+    let points = IPriceHistory::new(address, provider)
+        .getPriceHistory()
+        .call()
+        .await
+        .with_context(|| format!("failed to read price history from contract {}", address))?;
+    Ok(points
+        .into_iter()
+        .map(|point| (point.day, point.price))
+        .collect())
+}
+
+/// Reads a price history from a CSV file with `day,price` rows.
+///
+/// A leading header row is skipped if its first column is not a number.
+pub fn load_price_history(path: &Path) -> Result<Vec<PricePoint>> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read price history from {}", path.display()))?;
+    parse_price_history(&contents)
+}
+
+/// Parses `day,price` rows into price points.
+pub fn parse_price_history(contents: &str) -> Result<Vec<PricePoint>> {
+    let mut history = Vec::new();
+    for (line_no, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let mut columns = line.split(',').map(str::trim);
+        let (Some(day), Some(price), None) = (columns.next(), columns.next(), columns.next())
+        else {
+            bail!("line {}: expected `day,price`, got `{}`", line_no + 1, line);
+        };
+        let Ok(day) = day.parse::<u64>() else {
+            if line_no == 0 {
+                // Header row.
+                continue;
+            }
+            bail!("line {}: invalid day index `{}`", line_no + 1, day);
+        };
+        let price = price
+            .parse::<u64>()
+            .with_context(|| format!("line {}: invalid price `{}`", line_no + 1, price))?;
+        history.push((day, price));
+    }
+    Ok(history)
+}
+
+/// Reads OHLC candles from a CSV file with `day,open,high,low,close` rows.
+///
+/// A leading header row is skipped if its first column is not a number.
+pub fn load_candles(path: &Path) -> Result<Vec<Candle>> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read candles from {}", path.display()))?;
+    parse_candles(&contents)
+}
+
+/// Parses `day,open,high,low,close` rows into candles, rejecting any whose open or close is
+/// outside its low and high.
+pub fn parse_candles(contents: &str) -> Result<Vec<Candle>> {
+    let mut candles = Vec::new();
+    for (line_no, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let columns: Vec<&str> = line.split(',').map(str::trim).collect();
+        let [day, open, high, low, close] = columns[..] else {
+            bail!(
+                "line {}: expected `day,open,high,low,close`, got `{}`",
+                line_no + 1,
+                line
+            );
+        };
+        let Ok(day) = day.parse::<u64>() else {
+            if line_no == 0 {
+                // Header row.
+                continue;
+            }
+            bail!("line {}: invalid day index `{}`", line_no + 1, day);
+        };
+        let [open, high, low, close] = [open, high, low, close].map(|price| {
+            price
+                .parse::<u64>()
+                .with_context(|| format!("line {}: invalid price `{}`", line_no + 1, price))
+        });
+        let (open, high, low, close) = (open?, high?, low?, close?);
+        if low > open.min(close) || high < open.max(close) {
+            bail!(
+                "line {}: open {} and close {} are not within low {} and high {}",
+                line_no + 1,
+                open,
+                close,
+                low,
+                high
+            );
+        }
+        candles.push((day, open, high, low, close));
+    }
+    Ok(candles)
+}
+
+/// Writes [PRICE_HISTORY], the series the guest embeds, to a new `day,price` CSV file at `path`.
+pub fn write_template(path: &Path) -> Result<()> {
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .with_context(|| format!("failed to create price history template {}", path.display()))?;
+    let mut contents = String::from("day,price\n");
+    for (day, price) in PRICE_HISTORY {
+        contents.push_str(&format!("{},{}\n", day, price));
+    }
+    file.write_all(contents.as_bytes())
+        .with_context(|| format!("failed to write price history template {}", path.display()))
+}
+
+/// Reads a series file of `day,price,timestamp` rows, as [append_to_series] writes them, into its
+/// price points and their Unix timestamps. The timestamps are empty if any row lacks one, as the
+/// `day,price` rows of older series files do.
+pub fn load_series(path: &Path) -> Result<(Vec<PricePoint>, Vec<u64>)> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read series file {}", path.display()))?;
+    parse_series(&contents)
+}
+
+/// Parses `day,price,timestamp` rows, or `day,price` rows without a timestamp, see
+/// [load_series].
+pub fn parse_series(contents: &str) -> Result<(Vec<PricePoint>, Vec<u64>)> {
+    // The prices are parsed as a price history, keeping the line numbers of its errors
+    let mut rows = String::new();
+    let mut timestamps = Vec::new();
+    let mut timed = true;
+    for (line_no, line) in contents.lines().enumerate() {
+        let columns: Vec<&str> = line.split(',').map(str::trim).collect();
+        match columns[..] {
+            [day, price, timestamp] => {
+                match timestamp.parse::<u64>() {
+                    Ok(timestamp) => timestamps.push(timestamp),
+                    // Header row.
+                    Err(_) if line_no == 0 => {}
+                    Err(_) => bail!("line {}: invalid timestamp `{}`", line_no + 1, timestamp),
+                }
+                rows.push_str(&format!("{},{}", day, price));
+            }
+            [""] => {}
+            [day, ..] if line_no == 0 && day.parse::<u64>().is_err() => rows.push_str(line),
+            _ => {
+                timed = false;
+                rows.push_str(line);
+            }
+        }
+        rows.push('\n');
+    }
+    let series = parse_price_history(&rows)?;
+    Ok((series, if timed { timestamps } else { Vec::new() }))
+}
+
+/// Loads the series in the CSV file at `path` as [append_to_series] would fit it, without appending
+/// to it: empty if the file does not exist yet.
+pub fn read_series(path: &Path) -> Result<(Vec<PricePoint>, Vec<u64>)> {
+    if path.exists() {
+        load_series(path)
+    } else {
+        Ok((Vec::new(), Vec::new()))
+    }
+}
+
+/// Appends `price` at Unix time `timestamp` to the series in the CSV file at `path`, as the day
+/// after its last point or day 1 if the file does not exist yet, and returns the series as it was
+/// before, see [load_series], so that every run fits the prices of the runs before it.
+pub fn append_to_series(
+    path: &Path,
+    price: u64,
+    timestamp: u64,
+) -> Result<(Vec<PricePoint>, Vec<u64>)> {
+    let (series, timestamps) = read_series(path)?;
+    let day = series.last().map_or(1, |(day, _)| day + 1);
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("failed to open series file {}", path.display()))?;
+    writeln!(file, "{},{},{}", day, price, timestamp)
+        .with_context(|| format!("failed to append to series file {}", path.display()))?;
+    Ok((series, timestamps))
+}
+
+/// Rejects histories, of prices or candles, longer than `max_len` before they are sent to the
+/// guest.
+///
+/// Every point costs guest cycles, so an oversized series directly translates into proving cost.
+pub fn check_history_len<T>(history: &[T], max_len: usize) -> Result<()> {
+    if history.len() > max_len {
+        bail!(
+            "price history has {} points, which exceeds --max-history-len {}",
+            history.len(),
+            max_len
+        );
+    }
+    Ok(())
+}
+
+/// Replaces each run of `k` consecutive points with their mean day and mean price, the last run
+/// possibly shorter, so that the guest fits a series `k` times shorter.
+///
+/// Averaging the days along with the prices keeps a linear trend on the same line, but the guest
+/// then predicts the day after the last mean day, up to `k / 2` days before the real next day.
+pub fn downsample(history: &[PricePoint], k: usize) -> Vec<PricePoint> {
+    history
+        .chunks(k.max(1))
+        .map(|chunk| {
+            let n = chunk.len() as u64;
+            let day = chunk.iter().map(|(day, _)| day).sum::<u64>() / n;
+            let price = chunk.iter().map(|(_, price)| price).sum::<u64>() / n;
+            (day, price)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        primitives::hex,
+        providers::ProviderBuilder,
+        sol_types::{SolCall, SolValue},
+    };
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    use super::*;
+
+    /// Answers a single `eth_call` of `getPriceHistory()` with `points`.
+    async fn serve_price_history(listener: TcpListener, points: Vec<IPriceHistory::PricePoint>) {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        let mut buf = [0u8; 1024];
+        let body = loop {
+            let n = stream.read(&mut buf).await.unwrap();
+            request.extend_from_slice(&buf[..n]);
+            let text = String::from_utf8_lossy(&request);
+            if let Some(header_end) = text.find("\r\n\r\n") {
+                if let Ok(body) =
+                    serde_json::from_slice::<serde_json::Value>(&request[header_end + 4..])
+                {
+                    break body;
+                }
+            }
+        };
+        assert_eq!(body["method"], "eth_call");
+        let call = &body["params"][0];
+        let input = call["input"].as_str().or(call["data"].as_str()).unwrap();
+        assert_eq!(
+            input,
+            hex::encode_prefixed(IPriceHistory::getPriceHistoryCall::SELECTOR)
+        );
+
+        let result = hex::encode_prefixed(points.abi_encode());
+        let response =
+            serde_json::json!({ "jsonrpc": "2.0", "id": body["id"], "result": result }).to_string();
+        stream
+            .write_all(
+                format!(
+                    "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{}",
+                    response.len(),
+                    response
+                )
+                .as_bytes(),
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn reads_history_from_contract() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let points = vec![
+            IPriceHistory::PricePoint {
+                day: 1,
+                price: 3200,
+            },
+            IPriceHistory::PricePoint {
+                day: 2,
+                price: 3215,
+            },
+            IPriceHistory::PricePoint {
+                day: 4,
+                price: 3189,
+            },
+        ];
+        tokio::spawn(serve_price_history(listener, points));
+
+        let provider = ProviderBuilder::new().connect_http(url.parse().unwrap());
+        let history = read_onchain_history(provider, Address::repeat_byte(0x33))
+            .await
+            .unwrap();
+        assert_eq!(history, vec![(1, 3200), (2, 3215), (4, 3189)]);
+    }
+
+    #[test]
+    fn parses_rows_and_skips_header() {
+        let history = parse_price_history("day,price\n1,3200\n2, 3215\n\n3,3189\n").unwrap();
+        assert_eq!(history, vec![(1, 3200), (2, 3215), (3, 3189)]);
+    }
+
+    #[test]
+    fn rejects_malformed_rows() {
+        let err = parse_price_history("1,3200\n2\n").unwrap_err();
+        assert!(
+            err.to_string().contains("line 2"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn parses_candles_and_rejects_inconsistent_ones() {
+        let candles = parse_candles("day,open,high,low,close\n1,3000,3040,2990,3000\n").unwrap();
+        assert_eq!(candles, vec![(1, 3000, 3040, 2990, 3000)]);
+        // A close above the high
+        assert!(parse_candles("1,3000,3040,2990,3050\n").is_err());
+        assert!(parse_candles("1,3000,3040,2990\n").is_err());
+    }
+
+    #[test]
+    fn template_parses_back_to_the_embedded_series() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.csv");
+        write_template(&path).unwrap();
+        assert_eq!(load_price_history(&path).unwrap(), PRICE_HISTORY.to_vec());
+        // An existing file is not overwritten
+        assert!(write_template(&path).is_err());
+    }
+
+    #[test]
+    fn rejects_over_length_history() {
+        let history: Vec<PricePoint> = (1..=366).map(|day| (day, 3200)).collect();
+        let err = check_history_len(&history, 365).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "price history has 366 points, which exceeds --max-history-len 365"
+        );
+        check_history_len(&history[..365], 365).unwrap();
+    }
+
+    #[test]
+    fn appends_each_run_to_the_series() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("series.csv");
+
+        // Each run fits the prices of the runs before it
+        assert_eq!(
+            append_to_series(&path, 3200, 100).unwrap(),
+            (vec![], vec![])
+        );
+        assert_eq!(
+            append_to_series(&path, 3210, 200).unwrap(),
+            (vec![(1, 3200)], vec![100])
+        );
+        assert_eq!(
+            append_to_series(&path, 3225, 300).unwrap(),
+            (vec![(1, 3200), (2, 3210)], vec![100, 200])
+        );
+        assert_eq!(
+            load_series(&path).unwrap(),
+            (vec![(1, 3200), (2, 3210), (3, 3225)], vec![100, 200, 300])
+        );
+    }
+
+    #[test]
+    fn series_rows_without_timestamps_have_none() {
+        assert_eq!(
+            parse_series("day,price\n1,3200\n2,3210,200\n").unwrap(),
+            (vec![(1, 3200), (2, 3210)], vec![])
+        );
+        assert_eq!(
+            parse_series("day,price,timestamp\n1,3200,100\n").unwrap(),
+            (vec![(1, 3200)], vec![100])
+        );
+        let err = parse_series("1,3200,100\n2,3210,soon\n").unwrap_err();
+        assert_eq!(err.to_string(), "line 2: invalid timestamp `soon`");
+    }
+
+    #[test]
+    fn downsamples_to_chunk_means() {
+        let history: Vec<PricePoint> = (1..=100).map(|day| (day, 3000 + 10 * day)).collect();
+        let downsampled = downsample(&history, 5);
+        assert_eq!(downsampled.len(), 20);
+        // A straight line stays on the same line
+        assert_eq!(downsampled[0], (3, 3030));
+        assert_eq!(downsampled[19], (98, 3980));
+        assert_eq!(downsample(&history, 1), history);
+        assert_eq!(downsample(&history[..7], 5), [(3, 3030), (6, 3065)]);
+    }
+}
@@ -0,0 +1,81 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloy::{
+    primitives::{Address, B256},
+    providers::Provider,
+};
+use anyhow::{bail, Context, Result};
+use guests::TRADING_SIGNAL_ID;
+use risc0_zkvm::sha::Digest;
+
+use crate::trading_signal::ITradingSignal::ITradingSignalInstance;
+
+/// Image ID of the trading signal guest built into this binary, as the contract stores it.
+pub fn local_image_id() -> B256 {
+    B256::from_slice(Digest::from(TRADING_SIGNAL_ID).as_bytes())
+}
+
+/// Reads the image ID the TradingSignal contract at `address` verifies proofs against.
+pub async fn read_image_id<P: Provider>(provider: P, address: Address) -> Result<B256> {
+    ITradingSignalInstance::new(address, provider)
+        .IMAGE_ID()
+        .call()
+        .await
+        .with_context(|| format!("failed to read the image ID of TradingSignal {}", address))
+}
+
+/// Fails if the contract expects proofs of an image other than the `local` one, in which case
+/// `setSignal` would revert after paying for the proof.
+pub fn check_image_id(expected: B256, local: B256) -> Result<()> {
+    if expected != local {
+        bail!(
+            "the contract expects image ID {}, but this build of the guest has {}; \
+             deploy the guest or update the contract with setImageId",
+            expected,
+            local
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        providers::RootProvider, rpc::client::RpcClient, sol_types::SolValue,
+        transports::mock::Asserter,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn mismatched_image_id_is_rejected() {
+        let asserter = Asserter::new();
+        let provider = RootProvider::new(RpcClient::mocked(asserter.clone()));
+        let local = B256::repeat_byte(0x11);
+
+        asserter.push_success(&local.abi_encode());
+        let expected = read_image_id(&provider, Address::ZERO).await.unwrap();
+        check_image_id(expected, local).unwrap();
+
+        asserter.push_success(&B256::repeat_byte(0x22).abi_encode());
+        let expected = read_image_id(&provider, Address::ZERO).await.unwrap();
+        let err = check_image_id(expected, local).unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with(&format!("the contract expects image ID {}", expected)),
+            "unexpected error: {err}"
+        );
+    }
+}
@@ -0,0 +1,84 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::future::Future;
+
+use anyhow::{Context, Result};
+use boundless_market::{GuestEnv, RequestInput};
+use url::Url;
+
+/// Largest encoded guest input sent inline in the request, matching the Boundless default.
+/// Larger inputs are uploaded to the storage provider and referenced by URL.
+pub const INLINE_INPUT_MAX_BYTES: usize = 2048;
+
+/// Encodes `stdin` as a guest environment and places it in the request: inline if it fits in
+/// [INLINE_INPUT_MAX_BYTES], otherwise uploaded with `upload` and referenced by URL.
+///
+/// This is decided here rather than left to the request builder so that inputs are placed the
+/// same way whether or not the program was uploaded during the run.
+pub async fn request_input<F, Fut>(stdin: Vec<u8>, upload: F) -> Result<RequestInput>
+where
+    F: FnOnce(Vec<u8>) -> Fut,
+    Fut: Future<Output = Result<Url>>,
+{
+    let encoded = GuestEnv::from_stdin(stdin)
+        .encode()
+        .context("failed to encode guest input")?;
+    if encoded.len() <= INLINE_INPUT_MAX_BYTES {
+        return Ok(RequestInput::inline(encoded));
+    }
+    let size = encoded.len();
+    let input_url = upload(encoded).await.context("failed to upload input")?;
+    tracing::info!("Uploaded {} byte input to {}", size, input_url);
+    Ok(RequestInput::url(input_url))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use boundless_market::contracts::RequestInputType;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn large_input_is_uploaded() {
+        let uploaded = Mutex::new(Vec::new());
+        let upload = |encoded: Vec<u8>| {
+            uploaded.lock().unwrap().push(encoded);
+            async { Ok(Url::parse("https://storage.example/input.bin").unwrap()) }
+        };
+
+        let input = request_input(vec![0x42; 64 * 1024], upload).await.unwrap();
+        assert_eq!(input.inputType, RequestInputType::Url);
+        assert_eq!(&input.data[..], b"https://storage.example/input.bin");
+        let uploaded = uploaded.into_inner().unwrap();
+        assert_eq!(uploaded.len(), 1);
+        assert!(uploaded[0].len() > INLINE_INPUT_MAX_BYTES);
+    }
+
+    #[tokio::test]
+    async fn small_input_is_inline() {
+        let input = request_input(vec![0x42; 64], |_| async {
+            anyhow::bail!("small inputs should not be uploaded")
+        })
+        .await
+        .unwrap();
+        assert_eq!(input.inputType, RequestInputType::Inline);
+        assert_eq!(
+            input.data,
+            GuestEnv::from_stdin(vec![0x42; 64]).encode().unwrap()
+        );
+    }
+}
@@ -0,0 +1,208 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{history, signal::Action};
+
+/// A recorded price tick: (unix_timestamp, usd_price_per_eth).
+pub type Tick = (u64, u64);
+
+/// Paper-trading position of one ETH, opened on BUY and closed on SELL at the current price.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ledger {
+    /// USD price the open position was bought at, if one is open.
+    pub open_price: Option<u64>,
+    /// Profit and loss of the closed positions, in USD.
+    pub realized_pnl: i64,
+    /// Number of closed positions.
+    pub closed_trades: u64,
+}
+
+impl Ledger {
+    /// Reads the ledger written by [Ledger::save], or starts a flat one if `path` does not exist.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read ledger from {}", path.display()))?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("failed to parse ledger {}", path.display()))
+    }
+
+    /// Writes the ledger as JSON.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let contents = serde_json::to_string_pretty(self).context("failed to serialize ledger")?;
+        std::fs::write(path, contents)
+            .with_context(|| format!("failed to write ledger to {}", path.display()))
+    }
+
+    /// Applies an action at `price`: BUY opens a position unless one is open, SELL closes it.
+    pub fn apply(&mut self, action: Action, price: u64) {
+        match (action, self.open_price) {
+            (Action::Buy, None) => self.open_price = Some(price),
+            (Action::Sell, Some(open_price)) => {
+                self.realized_pnl += price as i64 - open_price as i64;
+                self.closed_trades += 1;
+                self.open_price = None;
+            }
+            _ => {}
+        }
+    }
+
+    /// Profit and loss of the open position at `price`, 0 if flat.
+    pub fn unrealized_pnl(&self, price: u64) -> i64 {
+        self.open_price
+            .map_or(0, |open_price| price as i64 - open_price as i64)
+    }
+
+    /// Logs the ledger valued at `price`.
+    pub fn log(&self, price: u64) {
+        tracing::info!(
+            "Paper trading: {}, realized PnL ${} over {} trades, unrealized PnL ${}",
+            match self.open_price {
+                Some(open_price) => format!("long 1 ETH from ${}", open_price),
+                None => "flat".to_string(),
+            },
+            self.realized_pnl,
+            self.closed_trades,
+            self.unrealized_pnl(price)
+        );
+    }
+}
+
+/// Applies the action of a run to the ledger at `path`, at the run's current price.
+pub fn record(path: &Path, action: Action, price: u64) -> Result<Ledger> {
+    let mut ledger = Ledger::load(path)?;
+    ledger.apply(action, price);
+    ledger.save(path)?;
+    Ok(ledger)
+}
+
+/// Reads ticks from a CSV file with `timestamp,price` rows, in increasing timestamp order.
+pub fn load_ticks(path: &Path) -> Result<Vec<Tick>> {
+    let ticks = history::load_price_history(path)?;
+    if let Some(pair) = ticks.windows(2).find(|pair| pair[1].0 <= pair[0].0) {
+        bail!(
+            "tick timestamps must increase, got {} after {}",
+            pair[1].0,
+            pair[0].0
+        );
+    }
+    Ok(ticks)
+}
+
+/// Replays `ticks` through a flat ledger the way the loop mode would have traded them: `decide`
+/// is given the price of each tick it wakes up for and returns the action taken, which is applied
+/// at that price.
+///
+/// With an `interval_secs`, ticks less than that long after the last one decided on are skipped,
+/// as the loop only reads the price once per interval.
+pub fn replay(
+    ticks: &[Tick],
+    interval_secs: Option<u64>,
+    mut decide: impl FnMut(u64) -> Result<Action>,
+) -> Result<Ledger> {
+    let mut ledger = Ledger::default();
+    let mut last_decided: Option<u64> = None;
+    for &(timestamp, price) in ticks {
+        if let (Some(interval_secs), Some(last_decided)) = (interval_secs, last_decided) {
+            if timestamp - last_decided < interval_secs {
+                continue;
+            }
+        }
+        last_decided = Some(timestamp);
+        let action = decide(price)
+            .with_context(|| format!("failed to decide on the tick at {}", timestamp))?;
+        ledger.apply(action, price);
+    }
+    Ok(ledger)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buy_then_sell_realizes_pnl() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ledger.json");
+
+        let ledger = record(&path, Action::Buy, 3200).unwrap();
+        assert_eq!(ledger.open_price, Some(3200));
+        assert_eq!(ledger.unrealized_pnl(3250), 50);
+
+        // A second BUY does not add to the position, HOLD changes nothing
+        record(&path, Action::Buy, 3300).unwrap();
+        record(&path, Action::Hold, 3400).unwrap();
+        let ledger = record(&path, Action::Sell, 3350).unwrap();
+        assert_eq!(
+            ledger,
+            Ledger {
+                open_price: None,
+                realized_pnl: 150,
+                closed_trades: 1,
+            }
+        );
+
+        // SELL while flat changes nothing; a losing round trip is subtracted
+        record(&path, Action::Sell, 3000).unwrap();
+        record(&path, Action::Buy, 3100).unwrap();
+        record(&path, Action::Sell, 3020).unwrap();
+        let ledger = Ledger::load(&path).unwrap();
+        assert_eq!(ledger.realized_pnl, 70);
+        assert_eq!(ledger.closed_trades, 2);
+    }
+
+    #[test]
+    fn replays_ticks_at_the_loop_interval() {
+        // Buys under $3000 and sells over $3100
+        let decide = |price| {
+            Ok(if price < 3000 {
+                Action::Buy
+            } else if price > 3100 {
+                Action::Sell
+            } else {
+                Action::Hold
+            })
+        };
+        let ticks = vec![
+            (0, 2990),
+            (60, 3150),
+            (90, 2950),
+            (120, 3050),
+            (180, 2980),
+            (240, 3120),
+        ];
+
+        // Without an interval every tick is decided on, so the dip at 90s is bought
+        let ledger = replay(&ticks, None, decide).unwrap();
+        assert_eq!(ledger.closed_trades, 2);
+        assert_eq!(ledger.realized_pnl, (3150 - 2990) + (3120 - 2950));
+
+        // A 60s loop never wakes up for the dip at 90s
+        let ledger = replay(&ticks, Some(60), decide).unwrap();
+        assert_eq!(ledger.closed_trades, 2);
+        assert_eq!(ledger.realized_pnl, (3150 - 2990) + (3120 - 2980));
+
+        // A 120s loop only sees the ticks at 0s, 120s and 240s
+        let ledger = replay(&ticks, Some(120), decide).unwrap();
+        assert_eq!(ledger.closed_trades, 1);
+        assert_eq!(ledger.realized_pnl, 3120 - 2990);
+    }
+}
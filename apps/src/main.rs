@@ -12,21 +12,80 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::time::Duration;
+use std::{
+    ffi::OsString,
+    future::Future,
+    io::BufRead,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
-use crate::trading_signal::ITradingSignal::ITradingSignalInstance;
+use crate::trading_signal::ITradingSignal::{self, ITradingSignalInstance};
 use alloy::{
-    primitives::{Address, U256},
+    contract::{CallBuilder, CallDecoder},
+    network::Network,
+    primitives::{keccak256, Address, Bytes, B256, U256},
+    providers::{PendingTransactionBuilder, Provider},
     signers::local::PrivateKeySigner,
-    sol_types::SolValue,
+    sol_types::{SolCall, SolValue},
 };
 use anyhow::{bail, Context, Result};
-use boundless_market::{Client, Deployment, StorageProviderConfig};
-use clap::Parser;
+use archive::RunArchive;
+use backtest::TuneObjective;
+use boundless_market::{
+    client::ClientError,
+    contracts::boundless_market::MarketError,
+    request_builder::{OfferParams, RequestParams},
+    Client, Deployment, StorageProviderConfig,
+};
+use breaker::CircuitBreaker;
+use clap::{CommandFactory, Parser, Subcommand};
+use decision::DecisionExpr;
 use guests::TRADING_SIGNAL_ELF;
+use executor::CycleReport;
+use explain::Explanation;
+use fees::FeeEstimate;
+use history::{Candle, HistorySource, PricePoint};
+use output::{OutputFormat, RunRecord};
+use receipt::{SavedReceipt, SignalSink};
+use signal::{
+    Action, ConfidenceAggregation, GuestOutput, Model, RawPrediction, Rounding, SignalHistory,
+    Strategy,
+};
+use thresholds::ThresholdSource;
+use tracing::Instrument;
 use url::Url;
 
-/// Timeout for the transaction to be confirmed.
+mod archive;
+mod backtest;
+mod batch;
+mod breaker;
+mod compare;
+mod config;
+mod deadline;
+mod decision;
+mod deployment;
+mod events;
+mod executor;
+mod explain;
+mod fees;
+mod history;
+mod image_id;
+mod input;
+mod ledger;
+mod oracle;
+mod output;
+mod price_feed;
+mod receipt;
+mod rpc;
+mod signal;
+mod stress;
+mod telemetry;
+mod thresholds;
+mod watch;
+mod webhook;
+
+/// Default timeout for the transaction to be confirmed.
 pub const TX_TIMEOUT: Duration = Duration::from_secs(30);
 
 mod trading_signal {
@@ -37,24 +96,349 @@ mod trading_signal {
 }
 
 /// Arguments of the trading signal CLI.
-#[derive(Parser, Debug)]
-#[clap(author, version, about, long_about = None)]
+#[derive(Parser, Clone, Debug)]
+#[clap(author, version, about, long_about = None, args_override_self = true)]
 struct Args {
-    /// Current ETH price in USD (e.g., 3200 means $3200 per ETH).
-    #[clap(long, default_value = "3200")]
+    #[clap(subcommand)]
+    command: Option<Command>,
+    /// TOML file with default values for any of the flags below, keyed by flag name.
+    ///
+    /// Command line flags and environment variables take precedence over the file.
+    #[clap(long)]
+    config: Option<PathBuf>,
+    /// Current ETH price in USD (e.g., 3200 means $3200 per ETH), or `-` to read it from the first
+    /// line of stdin, for shell pipelines.
+    #[clap(long, env, default_value = "3200", value_parser = current_price_arg)]
     current_price: u64,
-    /// URL of the Ethereum RPC endpoint.
-    #[clap(short, long, env)]
-    rpc_url: Url,
+    /// Chainlink ETH/USD aggregator to read the current price from on every run, instead of
+    /// `--current-price`. Read through the first reachable `--rpc-url`.
+    #[clap(long, env)]
+    price_feed: Option<Address>,
+    /// Abort the run if the `--price-feed` round is older than this many seconds, rather than
+    /// trade on a stale price.
+    #[clap(long, env, requires = "price_feed")]
+    max_price_age_secs: Option<u64>,
+    /// CSV file of `day,price` rows to use as price history instead of the embedded series.
+    #[clap(long, env)]
+    price_history_csv: Option<PathBuf>,
+    /// CSV file of `day,open,high,low,close` candles to send in place of the price history; the
+    /// guest fits the typical price `(high + low + close) / 3` of each day.
+    #[clap(
+        long,
+        env,
+        conflicts_with_all = ["price_history_csv", "series_file", "downsample"]
+    )]
+    ohlc_csv: Option<PathBuf>,
+    /// Where the price history comes from: `csv` uses `--price-history-csv` (or the embedded
+    /// series without it) and `onchain` reads it from `--history-contract`.
+    #[clap(long, env, value_enum, default_value_t = HistorySource::Csv)]
+    history_source: HistorySource,
+    /// Contract implementing `IPriceHistory` to read the price history from, with
+    /// `--history-source onchain`. Read through the first reachable `--rpc-url`.
+    #[clap(long, env, required_if_eq("history_source", "onchain"))]
+    history_contract: Option<Address>,
+    /// Maximum number of price history points accepted before encoding the guest input.
+    #[clap(long, env, default_value = "365")]
+    max_history_len: usize,
+    /// Average every this many consecutive price points into one before encoding the guest
+    /// input, trading accuracy for cycles on long histories. Applies before
+    /// `--max-history-len`; 1 keeps every point.
+    #[clap(long, env, default_value = "1", value_parser = clap::value_parser!(u64).range(1..))]
+    downsample: u64,
+    /// Drop price points more than this many standard deviations from the fitted line before
+    /// refitting. 0 disables outlier trimming.
+    #[clap(long, env, default_value = "0")]
+    outlier_k: u64,
+    /// Clamp prices to within this factor of the median before fitting, so a corrupt tick cannot
+    /// wreck the prediction. 0 disables clamping.
+    #[clap(long, env, default_value = "0")]
+    clamp_factor: u64,
+    /// Seed of every random draw in the guest, so that runs are reproducible.
+    #[clap(long, env, default_value = "0")]
+    seed: u64,
+    /// Base the regression's confidence on how many of this many fits to resampled histories
+    /// agree with its signal, instead of on R². 0 disables bootstrapping.
+    #[clap(long, env, default_value = "0", value_parser = clap::value_parser!(u64).range(0..=256))]
+    bootstrap_samples: u64,
+    /// Base the regression's confidence on its residuals over only the last this many price
+    /// points, instead of on R² over the whole history, so that a calm recent market is trusted
+    /// however noisy the distant past was. 0 uses the whole history.
+    #[clap(long, env, default_value = "0", conflicts_with = "bootstrap_samples")]
+    residual_window: u64,
+    /// Commit the majority signal of the last this many windows of the history, each one point
+    /// shorter than the next, with the share of windows that agree as the confidence, so that a
+    /// single noisy window does not flip the signal. 0 or 1 uses the whole history alone.
+    #[clap(long, env, default_value = "0")]
+    vote_windows: u64,
+    /// Have the guest signal HOLD unless the regression slope points the same way as the last
+    /// day-over-day change.
+    #[clap(long, env)]
+    require_agreement: bool,
+    /// Have the guest signal HOLD, with the R² as its confidence, when the regression is among
+    /// the `--ensemble` models and fits the history with an R² below this percentage. 0 disables
+    /// the gate.
+    #[clap(long, env, default_value = "0", value_parser = clap::value_parser!(u64).range(0..=100))]
+    min_r2: u64,
+    /// Cap the committed confidence at this percentage, since even a perfect fit to past prices
+    /// is no certainty about the next one.
+    #[clap(long, env, default_value = "100", value_parser = clap::value_parser!(u64).range(1..=100))]
+    max_confidence: u64,
+    /// Journal layout for the guest to commit: 1 keeps only what the contract stores, for cheaper
+    /// calldata, 2 adds the model diagnostics and the indicator panel, 3 adds the upside and
+    /// downside confidence, 4 the number of price points used, 5 the confidence in basis
+    /// points, 6 the reason for a HOLD, 7 the predicted return, 8 the hash of the strategy
+    /// parameters, 9 the last price of the input series and 10 the decimals of the predicted
+    /// price, which the contract stores with it.
+    #[clap(
+        long,
+        env,
+        default_value_t = trading_journal::LATEST_JOURNAL_VERSION,
+        value_parser = clap::value_parser!(u8).range(1..=10)
+    )]
+    journal_version: u8,
+    /// Decode journal words too large for their 64-bit field by saturating them, instead of the
+    /// default strict decoding that fails the run on them. The signal, reason and version words
+    /// must be valid either way.
+    #[clap(long, env)]
+    lenient_decode: bool,
+    /// Have the guest keep the confidence in basis points (0-10000) rather than whole percent. The
+    /// contract still receives the percentage; the basis points follow it from journal version 5
+    /// and replace it with `--raw-prediction`.
+    #[clap(long, env)]
+    bps_confidence: bool,
+    /// Have the guest commit the predicted return over the current price in basis points,
+    /// computed from the prices in base units, from journal version 7.
+    #[clap(long, env, conflicts_with = "raw_prediction")]
+    predicted_return: bool,
+    /// Have the guest commit the regression residual of every price point, for plotting the fit,
+    /// in a version 11 journal on top of version 10. It costs a word per point, so the guest
+    /// refuses series of more than 256 points.
+    #[clap(long, env, conflicts_with_all = ["raw_prediction", "legacy_encoding"])]
+    commit_residuals: bool,
+    /// Comma separated horizons, in days after the last price point (1-64), that the guest
+    /// predicts besides the next day. BUY or SELL only stands if the signal is the same at every
+    /// horizon, and the signal of each is committed in a version 12 journal.
+    #[clap(
+        long,
+        env,
+        value_delimiter = ',',
+        value_parser = clap::value_parser!(u64).range(1..=64),
+        conflicts_with_all = ["raw_prediction", "legacy_encoding"]
+    )]
+    horizons: Vec<u64>,
+    /// Have the guest commit the strategy ID, the keccak256 of this build's image ID and the
+    /// parameters' hash, in a version 13 journal, and publish it with `setSignalForStrategy` to
+    /// contracts that only accept approved strategies. `strategy-id` prints the ID to approve.
+    #[clap(long, env, conflicts_with_all = ["raw_prediction", "legacy_encoding"])]
+    commit_strategy_id: bool,
+    /// Have the guest commit the unversioned, hand-encoded journal it committed before layouts
+    /// were versioned, for consumers that still parse it. Overrides `--journal-version`.
+    #[clap(long, env)]
+    legacy_encoding: bool,
+    /// Comma separated models for the guest to run. With more than one, the guest only signals
+    /// BUY or SELL when they all agree, and HOLD otherwise.
+    #[clap(long, env, value_enum, value_delimiter = ',', default_value = "linreg")]
+    ensemble: Vec<Model>,
+    /// Number of recent prices the `percentile-rank` model ranks the current price among. 0 ranks
+    /// it among the whole history.
+    #[clap(long, env, default_value = "0")]
+    percentile_window: u64,
+    /// How an ensemble combines the confidences of its models.
+    #[clap(long, env, value_enum, default_value_t = ConfidenceAggregation::Mean)]
+    ensemble_confidence: ConfidenceAggregation,
+    /// How the guest rounds the predicted price to base units.
+    #[clap(long, env, value_enum, default_value_t = Rounding::Trunc)]
+    rounding: Rounding,
+    /// Have the guest signal HOLD until the history is long enough for every selected model to
+    /// have warmed up, such as the 20 points of the SMA crossover's slow average.
+    #[clap(long, env)]
+    warmup: bool,
+    /// Smoothing factor of the `holt` model's level, as a percentage (1-100).
+    #[clap(long, env, default_value = "50", value_parser = clap::value_parser!(u64).range(1..=100))]
+    holt_alpha: u64,
+    /// Smoothing factor of the `holt` model's trend, as a percentage (1-100).
+    #[clap(long, env, default_value = "20", value_parser = clap::value_parser!(u64).range(1..=100))]
+    holt_beta: u64,
+    /// External view of the price trend in USD per day, blended into the regression slope.
+    #[clap(long, env, default_value = "0", allow_hyphen_values = true)]
+    prior_slope: i64,
+    /// Confidence in `--prior-slope` as a percentage (0-100); 0 ignores the prior.
+    #[clap(long, env, default_value = "0", value_parser = clap::value_parser!(u64).range(0..=100))]
+    prior_weight: u64,
+    /// Have the guest also commit a panel of indicators (regression prediction, RSI, MACD
+    /// histogram, fast and slow SMA) and log it.
+    #[clap(long, env)]
+    report: bool,
+    /// Have the guest commit only the predicted price and confidence, without a BUY/SELL
+    /// decision. The contract is not updated in this mode.
+    #[clap(long, env)]
+    raw_prediction: bool,
+    /// Number of decimals of the on-chain price unit. The guest receives the current price and
+    /// commits its prediction in base units (price * 10^decimals); 0 keeps whole USD.
+    #[clap(long, env, default_value = "0", value_parser = clap::value_parser!(u8).range(0..=36))]
+    decimals: u8,
+    /// The guest signals BUY when the predicted price is more than this many basis points above
+    /// the current price.
+    #[clap(long, env, default_value = "50")]
+    buy_threshold_bps: u64,
+    /// The guest signals SELL when the predicted price is more than this many basis points below
+    /// the current price, and HOLD when it falls between the two thresholds.
+    #[clap(long, env, default_value = "50")]
+    sell_threshold_bps: u64,
+    /// Where the BUY and SELL thresholds come from: `flags` uses `--buy-threshold-bps` and
+    /// `--sell-threshold-bps`, and `onchain` reads them before each run from `getThresholds()` on
+    /// `--trading-signal-address`, through the first reachable `--rpc-url`.
+    #[clap(long, env, value_enum, default_value_t = ThresholdSource::Flags)]
+    threshold_source: ThresholdSource,
+    /// Have the prediction models signal BUY when the predicted price is above this many USD,
+    /// instead of applying `--buy-threshold-bps`.
+    #[clap(long, env, value_parser = clap::value_parser!(u64).range(1..))]
+    buy_above_usd: Option<u64>,
+    /// Have the prediction models signal SELL when the predicted price is below this many USD,
+    /// instead of applying `--sell-threshold-bps`.
+    #[clap(long, env, value_parser = clap::value_parser!(u64).range(1..))]
+    sell_below_usd: Option<u64>,
+    /// Position constraint applied to the decoded signal before acting on it.
+    #[clap(long, env, value_enum, default_value_t = Strategy::LongShort)]
+    strategy: Strategy,
+    /// Fail the run, without updating the contract, when the guest signals SELL, whatever the
+    /// strategy maps it to.
+    #[clap(long, env)]
+    fail_on_sell: bool,
+    /// Hold, without updating the contract, when the guest's confidence is zero.
+    #[clap(long, env)]
+    require_positive_confidence: bool,
+    /// Hold instead of acting when the predicted move from the current price is below this many
+    /// basis points.
+    #[clap(long, env, default_value = "0")]
+    min_predicted_move_bps: u64,
+    /// Act on a BUY or SELL only when this rule over the guest's outputs holds, e.g.
+    /// `predicted > current * 1.01 && rsi < 60`. It can use predicted, current, confidence, rsi,
+    /// macd, sma_fast, sma_slow, regression, slope and last. The guest proves the values, but the
+    /// rule itself runs in the app and is not proven.
+    #[clap(long, env, requires = "report")]
+    decision_expr: Option<DecisionExpr>,
+    /// Hold and warn when the predicted move from the current price exceeds this many basis
+    /// points, which likely means bad input data.
+    #[clap(long, env)]
+    max_predicted_move_bps: Option<u64>,
+    /// Fail a run that has not completed (submission, fulfillment and contract update) within
+    /// this many seconds. In loop mode the deadline applies to each run.
+    #[clap(long, env)]
+    deadline_secs: Option<u64>,
+    /// Seconds to wait for the setSignal transaction to be confirmed. Raise it on congested
+    /// chains.
+    #[clap(long, env, default_value_t = TX_TIMEOUT.as_secs())]
+    tx_timeout_secs: u64,
+    /// Set the nonce of each setSignal transaction to the pending transaction count of the
+    /// wallet, so that updates sent in quick succession do not fail with "nonce too low".
+    #[clap(long, env)]
+    manage_nonce: bool,
+    /// Simulate setSignal with eth_call and only send the transaction if it would not revert.
+    #[clap(long, env)]
+    contract_read_only_check: bool,
+    /// Read the image ID the TradingSignal contract verifies proofs against before requesting a
+    /// proof, and fail if it is not the image ID of this build of the guest.
+    #[clap(long, env)]
+    check_image_id: bool,
+    /// Execute the guest locally on the same input before requesting a proof, and skip the request
+    /// if its signal would not be published anyway: a SELL rejected by `--fail-on-sell`, or a
+    /// signal the strategy or the predicted move gates turn into HOLD.
+    #[clap(long, env, conflicts_with = "raw_prediction")]
+    dry_run_against_history: bool,
+    /// Run continuously, computing a new signal every this many seconds.
+    #[clap(long, env)]
+    interval_secs: Option<u64>,
+    /// In loop mode, stop after this many consecutive failed runs instead of retrying forever.
+    #[clap(long, env, value_parser = clap::value_parser!(u32).range(1..))]
+    max_consecutive_failures: Option<u32>,
+    /// POST the JSON result of each successful run to this URL.
+    #[clap(long, env)]
+    webhook_url: Option<Url>,
+    /// In loop mode, also POST the JSON result to this URL when the action flips between BUY and
+    /// SELL from one run to the next.
+    #[clap(long, env, requires = "interval_secs")]
+    flip_webhook_url: Option<Url>,
+    /// JSON file of a paper-trading position, opened on BUY and closed on SELL at the current
+    /// price, that tracks the hypothetical PnL of the signals across runs.
+    #[clap(long, env)]
+    ledger: Option<PathBuf>,
+    /// Replay a CSV file of `timestamp,price` ticks through the loop mode's decisions, locally
+    /// and without proving, and report the paper-trading PnL they would have produced. Each tick
+    /// is used as the current price, at most once per `--interval-secs` if set.
+    #[clap(
+        long,
+        conflicts_with_all = ["price_feed", "batch_file", "compare_models", "raw_prediction"]
+    )]
+    replay_from_ledger: Option<PathBuf>,
+    /// Number of recent signals kept in loop mode to report streaks.
+    #[clap(long, env, default_value = "10")]
+    signal_history_len: usize,
+    /// How to report the result of each run.
+    #[clap(long, env, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+    /// File that `--output csv` or `--output json` appends a record to after each run.
+    #[clap(long, env, required_if_eq_any([("output", "csv"), ("output", "json")]))]
+    output_file: Option<PathBuf>,
+    /// Save the journal and seal of each fulfilled request to this file, so that the contract
+    /// update can be retried with the `replay` subcommand.
+    #[clap(long, env)]
+    save_receipt: Option<PathBuf>,
+    /// Archive each proving run in a folder of this directory named after its timestamp: the
+    /// guest input, the journal and seal as hex, the run record as JSON and the setSignal
+    /// transaction hash, if one was sent.
+    #[clap(long, env)]
+    output_dir: Option<PathBuf>,
+    /// Print the ABI-encoded `setSignal` calldata as hex instead of sending the transaction, for
+    /// submission through other tooling such as a multisig.
+    #[clap(long, env)]
+    emit_calldata: bool,
+    /// Print the proven signal as a JSON EIP-712 message signed with `--private-key` instead of
+    /// sending the transaction, for off-chain consumers that verify the agent's signature.
+    #[clap(long, env, conflicts_with = "emit_calldata")]
+    emit_eip712: bool,
+    /// Build the request and print its estimated cost (proving price plus `setSignal` gas)
+    /// without submitting it.
+    #[clap(long)]
+    simulate_fees: bool,
+    /// Execute the guest locally and print the signal without proving or sending transactions.
+    #[clap(long)]
+    dry_run: bool,
+    /// Report the guest's cycle usage in dry-run mode.
+    #[clap(long, requires = "dry_run")]
+    profile: bool,
+    /// Abort a dry-run execution of the guest once it exceeds this many cycles, rather than keep
+    /// executing an input that would be too expensive to prove.
+    #[clap(long, requires = "dry_run")]
+    max_cycles: Option<u64>,
+    /// Print each step of the linear regression in dry-run mode. Recomputed on the host from the
+    /// price history sent to the guest, so it is informational only.
+    #[clap(long, requires = "dry_run")]
+    explain: bool,
+    /// Run each model alone and all of them combined on the same input in dry-run mode, and
+    /// print their outputs side by side.
+    #[clap(long, requires = "dry_run", conflicts_with = "raw_prediction")]
+    compare_models: bool,
+    /// Re-execute the guest locally and check the proven journal matches before using it.
+    #[clap(long, env)]
+    verify_journal: bool,
+    /// URLs of the Ethereum RPC endpoints, comma separated or repeated. The first that responds
+    /// is used.
+    #[clap(short, long, env, value_delimiter = ',')]
+    rpc_url: Vec<Url>,
     /// Private key used to interact with contracts and the Boundless Market.
     #[clap(long, env)]
-    private_key: PrivateKeySigner,
+    private_key: Option<PrivateKeySigner>,
     /// Address of the TradingSignal contract.
     #[clap(long, env)]
-    trading_signal_address: Address,
+    trading_signal_address: Option<Address>,
     /// URL where provers can download the program to be proven.
     #[clap(long, env)]
     program_url: Option<Url>,
+    /// Most the proof request offers to pay the prover, in wei. A request no prover takes at that
+    /// price expires unfulfilled.
+    #[clap(long, env)]
+    max_request_price: Option<U256>,
     /// Submit the request offchain via the provided order stream service url.
     #[clap(short, long, requires = "order_stream_url")]
     offchain: bool,
@@ -66,58 +450,1209 @@ struct Args {
     /// Will be automatically resolved from the connected chain ID if unspecified.
     #[clap(flatten, next_help_heading = "Boundless Market Deployment")]
     deployment: Option<Deployment>,
+    /// JSON file with the deployment of the Boundless contracts and services to use, for networks
+    /// the chain ID resolves wrongly. Takes the same fields as the deployment flags, in snake
+    /// case.
+    #[clap(
+        long,
+        env,
+        next_help_heading = "Boundless Market Deployment",
+        conflicts_with = "boundless_market_address"
+    )]
+    deployment_file: Option<PathBuf>,
+    /// File of one JSON object per line, each the inputs of a run: `current_price`, and
+    /// optionally `history` as `[day, price]` pairs, `models`, `buy_threshold_bps` and
+    /// `sell_threshold_bps`, over the other flags. Every line is run in turn, locally with
+    /// `--dry-run` and proven otherwise, and its result appended to `--output-file`, as JSON
+    /// unless `--output csv`. Proven lines update the `--ledger` and notify `--webhook-url` like
+    /// a single run.
+    #[clap(
+        long,
+        requires = "output_file",
+        conflicts_with_all = ["interval_secs", "price_feed", "append_to_series", "compare_models"]
+    )]
+    batch_file: Option<PathBuf>,
+    /// Append the current price of every run to `--series-file` and fit the series of the runs
+    /// before it instead of the history source, so that the agent builds up its own history.
+    #[clap(long, env, requires = "series_file", conflicts_with = "batch_file")]
+    append_to_series: bool,
+    /// CSV file of `day,price,timestamp` rows that `--append-to-series` grows by a day each run,
+    /// the timestamp being the Unix time of the run.
+    #[clap(long, env)]
+    series_file: Option<PathBuf>,
+    /// Have the guest weigh each `--series-file` point in the regression by its age, halving its
+    /// weight for every this many seconds it is older than the newest, so that a price fetched
+    /// hours ago counts for more than one fetched a week ago.
+    #[clap(
+        long,
+        env,
+        requires = "append_to_series",
+        conflicts_with = "downsample",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    decay_half_life_secs: Option<u64>,
+    /// Price history used in place of the history source, from the current `--batch-file` line
+    /// or the `--series-file`.
+    #[clap(skip)]
+    history_override: Option<Vec<PricePoint>>,
+    /// Unix timestamps of the `history_override` points, sent with `--decay-half-life-secs`.
+    #[clap(skip)]
+    point_timestamps: Vec<u64>,
+}
+
+#[derive(Subcommand, Clone, Debug)]
+enum Command {
+    /// Submit a receipt saved with `--save-receipt` to the TradingSignal contract without
+    /// re-proving.
+    Replay {
+        /// Receipt file written by `--save-receipt`.
+        #[clap(long)]
+        receipt: PathBuf,
+    },
+    /// Walk the `--price-history-csv` series forward one day at a time, predicting each next day
+    /// with the guest from the days before it, and report the directional accuracy and return of
+    /// following the signal.
+    Backtest {
+        /// Number of days used to train the first prediction.
+        #[clap(long, default_value = "10")]
+        min_train_len: usize,
+    },
+    /// Backtest the `--price-history-csv` series with a range of symmetric BUY/SELL thresholds
+    /// and report the best one. Runs locally, without proving.
+    Tune {
+        /// Number of days used to train the first prediction.
+        #[clap(long, default_value = "10")]
+        min_train_len: usize,
+        /// Smallest threshold tried, in basis points.
+        #[clap(long, default_value = "0")]
+        min_threshold_bps: u64,
+        /// Largest threshold tried, in basis points.
+        #[clap(long, default_value = "200")]
+        max_threshold_bps: u64,
+        /// Step between thresholds, in basis points.
+        #[clap(long, default_value = "10", value_parser = clap::value_parser!(u64).range(1..))]
+        step_bps: u64,
+        /// What the best threshold maximizes.
+        #[clap(long, value_enum, default_value_t = TuneObjective::Return)]
+        objective: TuneObjective,
+    },
+    /// Print the signals the TradingSignal contract at `--trading-signal-address` has published,
+    /// read from its `SignalUpdated` events through the first reachable `--rpc-url`.
+    Signals {
+        /// First block to scan, up to the latest one.
+        #[clap(long)]
+        since_block: u64,
+    },
+    /// Poll the signal the TradingSignal contract at `--trading-signal-address` stores, through the
+    /// first reachable `--rpc-url`, and print it whenever it changes, whoever wrote it.
+    Watch {
+        /// Seconds between reads of the contract.
+        #[clap(long, default_value = "30", value_parser = clap::value_parser!(u64).range(1..))]
+        poll_secs: u64,
+    },
+    /// Execute the guest, with the strategy flags, on generated adversarial series (flat, spike,
+    /// crash and sawtooth) and fail if any output breaks an invariant: a confidence above 100%, an
+    /// undecodable signal, or a prediction far enough from the prices to have wrapped around.
+    /// Runs locally, without proving.
+    Stress {
+        /// Number of series generated per scenario.
+        #[clap(long, default_value = "25", value_parser = clap::value_parser!(u64).range(1..))]
+        cases: u64,
+        /// Seed the series are generated from, so that a failing case can be reproduced.
+        #[clap(long, default_value = "0")]
+        scenario_seed: u64,
+    },
+    /// Print the strategy ID that the strategy flags commit with `--commit-strategy-id`, for the
+    /// contract owner to approve with `setStrategyApproved`. The flags are resolved as a run
+    /// resolves them, reading the onchain thresholds and the `--series-file` without appending.
+    StrategyId,
+    /// Print the Boundless deployment used for the chain of the first reachable `--rpc-url`: the
+    /// one given by `--deployment-file` or the deployment flags, or else the one resolved from the
+    /// chain ID.
+    Deployment,
+    /// Write the guest's embedded 30-day series as a `day,price` CSV to edit and pass back with
+    /// `--price-history-csv`.
+    InitHistory {
+        /// File to create; an existing file is left untouched.
+        #[clap(long)]
+        path: PathBuf,
+    },
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    tracing_subscriber::fmt()
-        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
-        .init();
+    telemetry::init();
 
+    // `.env` never overrides variables already set in the environment, so the precedence is:
+    // CLI flag > environment > `.env` > config file > default.
     match dotenvy::dotenv() {
         Ok(path) => tracing::debug!("Loaded environment variables from {:?}", path),
         Err(e) if e.not_found() => tracing::debug!("No .env file found"),
         Err(e) => bail!("failed to load .env file: {}", e),
     }
-    let args = Args::parse();
+    let args = match parse_args(std::env::args_os().collect()) {
+        Ok(args) => args,
+        Err(e) => match e.downcast::<clap::Error>() {
+            // Print usage errors and --help the way clap does
+            Ok(e) => e.exit(),
+            Err(e) => return Err(e),
+        },
+    };
+
+    if let Some(Command::InitHistory { path }) = &args.command {
+        history::write_template(path)?;
+        tracing::info!("Wrote a price history template to {}", path.display());
+        return Ok(());
+    }
+    if let Some(Command::Backtest { min_train_len }) = &args.command {
+        return run_backtest(&args, *min_train_len);
+    }
+    if let Some(Command::Tune {
+        min_train_len,
+        min_threshold_bps,
+        max_threshold_bps,
+        step_bps,
+        objective,
+    }) = &args.command
+    {
+        return run_tune(
+            &args,
+            *min_train_len,
+            (*min_threshold_bps..=*max_threshold_bps).step_by(*step_bps as usize),
+            *objective,
+        );
+    }
+
+    if let Some(Command::Signals { since_block }) = &args.command {
+        return run_signals(&args, *since_block).await;
+    }
+    if let Some(Command::Watch { poll_secs }) = &args.command {
+        return run_watch(&args, Duration::from_secs(*poll_secs)).await;
+    }
+    if let Some(Command::Stress {
+        cases,
+        scenario_seed,
+    }) = &args.command
+    {
+        return run_stress(&args, *cases as usize, *scenario_seed);
+    }
+    if let Some(Command::StrategyId) = &args.command {
+        println!("{}", expected_strategy_id(&resolve_args(&args).await?));
+        return Ok(());
+    }
+    if let Some(Command::Deployment) = &args.command {
+        return run_deployment(&args).await;
+    }
+
+    if let Some(path) = &args.replay_from_ledger {
+        return run_ledger_replay(&args, path).await;
+    }
+
+    if args.dry_run {
+        if args.command.is_some() {
+            bail!("--dry-run cannot be combined with a subcommand");
+        }
+        if let Some(path) = &args.batch_file {
+            return run_batch(&args, path, None).await;
+        }
+        return run_dry_run(&args)
+            .await
+            .and_then(|record| report(&args, &record));
+    }
 
     // Create a Boundless client from the provided parameters.
+    if args.rpc_url.is_empty() {
+        bail!("--rpc-url is required");
+    }
     let client = Client::builder()
-        .with_rpc_url(args.rpc_url.clone())
-        .with_deployment(args.deployment.clone())
+        .with_rpc_url(rpc::first_reachable(&args.rpc_url).await?)
+        .with_deployment(deployment(&args)?)
         .with_storage_provider_config(&args.storage_config)?
-        .with_private_key(args.private_key.clone())
+        .with_private_key(args.private_key.clone().context("--private-key is required")?)
         .build()
         .await
         .context("failed to build boundless client")?;
 
-    run_trading_signal_mode(&args, &client).await?;
+    if let Some(Command::Replay { receipt }) = &args.command {
+        return run_replay(&args, &client, receipt)
+            .await
+            .and_then(|record| report(&args, &record));
+    }
 
-    Ok(())
+    if args.simulate_fees {
+        return run_simulate_fees(&args, &client).await;
+    }
+
+    if let Some(path) = &args.batch_file {
+        return run_batch(&args, path, Some(&client)).await;
+    }
+
+    match args.interval_secs {
+        Some(interval_secs) => run_loop(&args, &client, Duration::from_secs(interval_secs)).await,
+        None => run_once(&args, &client)
+            .await
+            .and_then(|record| report(&args, &record)),
+    }
 }
 
-async fn run_trading_signal_mode(args: &Args, client: &Client) -> Result<()> {
-    let contract_address = args.trading_signal_address;
-    let current_price = args.current_price;
+/// Computes a new signal every `interval`, logging failures and the current signal streak, until
+/// `--max-consecutive-failures` runs fail in a row.
+async fn run_loop(args: &Args, client: &Client, interval: Duration) -> Result<()> {
+    let mut history = SignalHistory::new(args.signal_history_len);
+    let mut previous: Option<Action> = None;
+    breaker::run_loop(
+        interval,
+        CircuitBreaker::new(args.max_consecutive_failures),
+        || run_once(args, client),
+        |record| {
+            history.push(record.action);
+            if let Some((action, count)) = history.streak() {
+                tracing::info!("Signal streak: {} consecutive {}s", count, action);
+            }
+            if let Some(previous) =
+                previous.filter(|previous| signal::reverses(*previous, record.action))
+            {
+                alert_flip(args, previous, &record);
+            }
+            previous = Some(record.action);
+            if let Err(e) = report(args, &record) {
+                tracing::error!("Failed to report run: {:?}", e);
+            }
+        },
+    )
+    .await
+}
+
+/// Logs a prominent alert that the signal changed direction and, if `--flip-webhook-url` is set,
+/// posts the run to it in the background so the loop is not held up.
+fn alert_flip(args: &Args, previous: Action, record: &RunRecord) {
+    tracing::warn!(
+        "*** SIGNAL FLIP: {} -> {} (confidence {}%) ***",
+        previous,
+        record.action,
+        record.confidence
+    );
+    if let Some(url) = args.flip_webhook_url.clone() {
+        let record = record.clone();
+        tokio::spawn(async move {
+            match webhook::post(&url, &record).await {
+                Ok(()) => tracing::info!("Posted signal flip to webhook {}", url),
+                Err(e) => tracing::error!("Failed to notify flip webhook: {:?}", e),
+            }
+        });
+    }
+}
 
-    tracing::info!("Current ETH price: ${} USD", current_price);
-    let input_bytes = U256::from(current_price).abi_encode();
+/// Parses `--current-price`, reading the price from stdin if the value is `-`.
+fn current_price_arg(value: &str) -> Result<u64> {
+    read_current_price(value, std::io::stdin().lock())
+}
 
-    // Build the request based on whether program URL is provided
-    let request = if let Some(program_url) = &args.program_url {
-        // Use the provided URL
-        client
-            .new_request()
-            .with_program_url(program_url.clone())?
-            .with_stdin(input_bytes.clone())
+/// Parses a `--current-price` value, or the first line of `stdin` if the value is `-`.
+fn read_current_price(value: &str, mut stdin: impl BufRead) -> Result<u64> {
+    let mut line = String::new();
+    let value = if value == "-" {
+        let read = stdin
+            .read_line(&mut line)
+            .context("failed to read the current price from stdin")?;
+        if read == 0 {
+            bail!("stdin closed before a current price was piped in");
+        }
+        line.trim()
     } else {
-        client
-            .new_request()
-            .with_program(TRADING_SIGNAL_ELF)
-            .with_stdin(input_bytes)
+        value
+    };
+    value
+        .parse()
+        .map_err(|e| anyhow::anyhow!("invalid current price {:?}: {}", value, e))
+}
+
+/// Parses the command line, filling flags that are not set otherwise from the `--config` file.
+fn parse_args(args: Vec<OsString>) -> Result<Args> {
+    let args = config::args_with_config_file(&Args::command(), args)?;
+    Ok(Args::try_parse_from(args)?)
+}
+
+/// Runs a single proving round, records its action in the `--ledger` and notifies `--webhook-url`
+/// of its result.
+///
+/// A failed ledger update or webhook is logged but does not fail the run, which has already
+/// updated the contract.
+async fn run_once(args: &Args, client: &Client) -> Result<RunRecord> {
+    let args = &resolve_args(args).await?;
+    let record = append_after(args, run_with_deadline(args, client)).await?;
+    if let Some(path) = &args.ledger {
+        match ledger::record(path, record.action, args.current_price) {
+            Ok(ledger) => ledger.log(args.current_price),
+            Err(e) => tracing::error!("Failed to update paper trading ledger: {:?}", e),
+        }
+    }
+    if let Some(url) = &args.webhook_url {
+        match webhook::post(url, &record).await {
+            Ok(()) => tracing::info!("Posted result to webhook {}", url),
+            Err(e) => tracing::error!("Failed to notify webhook: {:?}", e),
+        }
+    }
+    Ok(record)
+}
+
+/// Runs a single proving round, bounded by `--deadline-secs` if set.
+async fn run_with_deadline(args: &Args, client: &Client) -> Result<RunRecord> {
+    deadline::run_with_deadline(
+        args.deadline_secs.map(Duration::from_secs),
+        run_trading_signal_mode(args, client),
+    )
+    .await
+}
+
+/// Returns the deployment from `--deployment-file` or the deployment flags, or None to resolve it
+/// from the chain ID.
+fn deployment(args: &Args) -> Result<Option<Deployment>> {
+    match &args.deployment_file {
+        Some(path) => deployment::load(path).map(Some),
+        None => Ok(args.deployment.clone()),
+    }
+}
+
+/// Writes the run record in the selected output format.
+fn report(args: &Args, record: &RunRecord) -> Result<()> {
+    match args.output {
+        OutputFormat::Text => Ok(()),
+        OutputFormat::Csv | OutputFormat::Json => {
+            let path = args
+                .output_file
+                .as_deref()
+                .context("--output-file is required with --output csv or json")?;
+            match args.output {
+                OutputFormat::Csv => output::append_csv(path, record),
+                _ => output::append_json(path, record),
+            }
+        }
+    }
+}
+
+/// Loads the price history from `--history-source`.
+///
+/// An empty history tells the guest to use its embedded price series.
+async fn load_price_history(args: &Args) -> Result<Vec<PricePoint>> {
+    if let Some(price_history) = &args.history_override {
+        return Ok(price_history.clone());
+    }
+    match args.history_source {
+        HistorySource::Csv => match &args.price_history_csv {
+            Some(path) => history::load_price_history(path),
+            None => Ok(Vec::new()),
+        },
+        HistorySource::Onchain => {
+            let address = args
+                .history_contract
+                .context("--history-contract is required with --history-source onchain")?;
+            let provider = rpc::read_only_provider(&args.rpc_url).await?;
+            let price_history = history::read_onchain_history(provider, address).await?;
+            tracing::info!("Read {} price history points from {}", price_history.len(), address);
+            Ok(price_history)
+        }
+    }
+}
+
+/// Returns the arguments a run sends to the guest: the `--price-feed` price, the onchain thresholds
+/// and the `--series-file` history in place of the flags they override.
+///
+/// The series file is left untouched, see [append_after].
+async fn resolve_args(args: &Args) -> Result<Args> {
+    let args = with_feed_price(args).await?;
+    let args = with_onchain_thresholds(&args).await?;
+    with_series(&args)
+}
+
+/// Awaits the `run` of the resolved `args` and, once it succeeded, appends its price to the
+/// `--series-file` with `--append-to-series`, so that a failed run leaves no point behind for its
+/// retry to duplicate.
+///
+/// A failed append is logged but does not fail the run, which has already updated the contract.
+async fn append_after(
+    args: &Args,
+    run: impl Future<Output = Result<RunRecord>>,
+) -> Result<RunRecord> {
+    // The point is timed when its price is read, not when the proof lands
+    let timestamp = price_feed::unix_now()?;
+    let record = run.await?;
+    if let (true, Some(path)) = (args.append_to_series, &args.series_file) {
+        match history::append_to_series(path, args.current_price, timestamp) {
+            Ok(_) => tracing::info!("Appended ${} USD to {}", args.current_price, path.display()),
+            Err(e) => tracing::error!("Failed to append to series file: {:?}", e),
+        }
+    }
+    Ok(record)
+}
+
+/// Returns the arguments with `--current-price` replaced by the latest `--price-feed` answer, if a
+/// feed is set, after checking it against `--max-price-age-secs`.
+async fn with_feed_price(args: &Args) -> Result<Args> {
+    let Some(address) = args.price_feed else {
+        return Ok(args.clone());
+    };
+    let provider = rpc::read_only_provider(&args.rpc_url).await?;
+    let feed_price = price_feed::read_latest_price(provider, address).await?;
+    price_feed::check_price_age(
+        feed_price.updated_at,
+        price_feed::unix_now()?,
+        args.max_price_age_secs,
+    )?;
+    tracing::info!(
+        "Read ETH price ${} USD from price feed {} (updated at {})",
+        feed_price.price,
+        address,
+        feed_price.updated_at
+    );
+    Ok(Args {
+        current_price: feed_price.price,
+        ..args.clone()
+    })
+}
+
+/// Returns the arguments with the BUY and SELL thresholds read from the contract, with
+/// `--threshold-source onchain`.
+async fn with_onchain_thresholds(args: &Args) -> Result<Args> {
+    if args.threshold_source != ThresholdSource::Onchain {
+        return Ok(args.clone());
+    }
+    let address = args
+        .trading_signal_address
+        .context("--trading-signal-address is required with --threshold-source onchain")?;
+    let provider = rpc::read_only_provider(&args.rpc_url).await?;
+    let (buy_threshold_bps, sell_threshold_bps) =
+        thresholds::read_thresholds(provider, address).await?;
+    tracing::info!(
+        "Read thresholds from {}: BUY above {} bps, SELL below {} bps",
+        address,
+        buy_threshold_bps,
+        sell_threshold_bps
+    );
+    Ok(Args {
+        buy_threshold_bps,
+        sell_threshold_bps,
+        ..args.clone()
+    })
+}
+
+/// Returns the arguments with the `--series-file` as the price history, with `--append-to-series`.
+///
+/// The file is only read: a run appends its price with [append_after] once it succeeds.
+fn with_series(args: &Args) -> Result<Args> {
+    if !args.append_to_series {
+        return Ok(args.clone());
+    }
+    let path = args
+        .series_file
+        .as_deref()
+        .context("--series-file is required with --append-to-series")?;
+    let (series, timestamps) = history::read_series(path)?;
+    if args.decay_half_life_secs.is_some() && timestamps.len() != series.len() {
+        bail!(
+            "--decay-half-life-secs needs a timestamp on every row of {}, which rows appended \
+             before timestamps were recorded lack",
+            path.display()
+        );
+    }
+    Ok(Args {
+        history_override: Some(series),
+        point_timestamps: if args.decay_half_life_secs.is_some() {
+            timestamps
+        } else {
+            Vec::new()
+        },
+        ..args.clone()
+    })
+}
+
+/// Averages the loaded price history over `--downsample` points, as sent to the guest.
+fn downsampled_history(args: &Args, price_history: &[PricePoint]) -> Vec<PricePoint> {
+    history::downsample(price_history, args.downsample as usize)
+}
+
+/// Encodes the guest input from the CLI arguments and the loaded price history.
+///
+/// Returns the input bytes and the hash the guest is expected to commit for the price history.
+fn build_guest_input(args: &Args, price_history: Vec<PricePoint>) -> Result<(Vec<u8>, B256)> {
+    tracing::info!("Current ETH price: ${} USD", args.current_price);
+    let price_history = if args.downsample > 1 {
+        let downsampled = downsampled_history(args, &price_history);
+        tracing::info!(
+            "Downsampled {} price history points to {}",
+            price_history.len(),
+            downsampled.len()
+        );
+        downsampled
+    } else {
+        price_history
+    };
+    history::check_history_len(&price_history, args.max_history_len)?;
+    let candles = match &args.ohlc_csv {
+        Some(path) => history::load_candles(path)?,
+        None => Vec::new(),
+    };
+    if !candles.is_empty() && !price_history.is_empty() {
+        bail!("--ohlc-csv replaces the price history, which was given as well");
+    }
+    history::check_history_len(&candles, args.max_history_len)?;
+    if args.bps_confidence
+        && !args.raw_prediction
+        && (args.legacy_encoding || args.journal_version < trading_journal::JOURNAL_V5)
+    {
+        bail!("--bps-confidence needs --journal-version 5 or later, or --raw-prediction");
+    }
+    if args.predicted_return
+        && (args.legacy_encoding || args.journal_version < trading_journal::JOURNAL_V7)
+    {
+        bail!("--predicted-return needs --journal-version 7 or later");
+    }
+    if let (Some(buy_above), Some(sell_below)) = (args.buy_above_usd, args.sell_below_usd) {
+        if sell_below > buy_above {
+            bail!("--sell-below-usd {sell_below} is above --buy-above-usd {buy_above}");
+        }
+    }
+    if candles.is_empty() {
+        tracing::info!("Using {} price history points", price_history.len());
+    } else {
+        tracing::info!("Using {} OHLC candles", candles.len());
+    }
+    Ok(encode_guest_input(args, args.current_price, price_history, candles))
+}
+
+/// Encodes the guest input for the given price and history, or candles in its place, with the
+/// model settings from the CLI arguments.
+fn encode_guest_input(
+    args: &Args,
+    current_price: u64,
+    price_history: Vec<PricePoint>,
+    candles: Vec<Candle>,
+) -> (Vec<u8>, B256) {
+    let input_hash = if !candles.is_empty() {
+        keccak256(candles.abi_encode())
+    } else if !args.point_timestamps.is_empty() {
+        keccak256((price_history.clone(), args.point_timestamps.clone()).abi_encode())
+    } else {
+        keccak256(price_history.abi_encode())
+    };
+    let input = trading_journal::GuestInput {
+        current_price: U256::from(current_price) * signal::unit_scale(args.decimals),
+        history: price_history,
+        outlier_k: args.outlier_k,
+        models: Model::mask(&args.ensemble),
+        prior_slope: args.prior_slope,
+        prior_weight: args.prior_weight,
+        buy_bps: args.buy_threshold_bps,
+        sell_bps: args.sell_threshold_bps,
+        report: args.report,
+        raw_prediction: args.raw_prediction,
+        decimals: args.decimals as u64,
+        clamp_factor: args.clamp_factor,
+        seed: args.seed,
+        bootstrap_samples: args.bootstrap_samples,
+        require_agreement: args.require_agreement,
+        journal_version: args.journal_version as u64,
+        legacy_encoding: args.legacy_encoding,
+        min_r2: args.min_r2,
+        percentile_window: args.percentile_window,
+        ensemble_confidence: args.ensemble_confidence.code(),
+        bps_confidence: args.bps_confidence,
+        holt_alpha: args.holt_alpha,
+        holt_beta: args.holt_beta,
+        buy_above_usd: args.buy_above_usd.unwrap_or(0),
+        sell_below_usd: args.sell_below_usd.unwrap_or(0),
+        rounding: args.rounding.code(),
+        warmup: args.warmup,
+        predicted_return: args.predicted_return,
+        residual_window: args.residual_window,
+        candles,
+        vote_windows: args.vote_windows,
+        max_confidence: args.max_confidence,
+        residuals: args.commit_residuals,
+        horizons: signal::horizon_mask(&args.horizons),
+        timestamps: args.point_timestamps.clone(),
+        half_life_secs: decay_half_life_secs(args),
+        image_id: if args.commit_strategy_id {
+            image_id::local_image_id()
+        } else {
+            B256::ZERO
+        },
+    };
+    (input.encode(), input_hash)
+}
+
+/// The strategy parameters `encode_guest_input` sends, as the guest hashes them from journal
+/// version 8.
+fn strategy_params(args: &Args) -> trading_journal::StrategyParams {
+    trading_journal::StrategyParams {
+        models: Model::mask(&args.ensemble),
+        outlier_k: args.outlier_k,
+        prior_slope: args.prior_slope,
+        prior_weight: args.prior_weight,
+        buy_bps: args.buy_threshold_bps,
+        sell_bps: args.sell_threshold_bps,
+        clamp_factor: args.clamp_factor,
+        seed: args.seed,
+        bootstrap_samples: args.bootstrap_samples,
+        require_agreement: args.require_agreement,
+        min_r2: args.min_r2,
+        percentile_window: args.percentile_window,
+        ensemble_confidence: args.ensemble_confidence.code(),
+        holt_alpha: args.holt_alpha,
+        holt_beta: args.holt_beta,
+        buy_above_usd: args.buy_above_usd.unwrap_or(0),
+        sell_below_usd: args.sell_below_usd.unwrap_or(0),
+        rounding: args.rounding.code(),
+        warmup: args.warmup,
+        residual_window: args.residual_window,
+        vote_windows: args.vote_windows,
+        max_confidence: args.max_confidence,
+        horizons: signal::horizon_mask(&args.horizons),
+        half_life_secs: decay_half_life_secs(args),
+    }
+}
+
+/// The decay half-life sent to the guest: none until the series has points to weigh, since the
+/// first run fits the embedded history.
+fn decay_half_life_secs(args: &Args) -> u64 {
+    if args.point_timestamps.is_empty() {
+        0
+    } else {
+        args.decay_half_life_secs.unwrap_or(0)
+    }
+}
+
+/// The strategy ID the guest commits with `--commit-strategy-id`, for this build of the guest.
+fn expected_strategy_id(args: &Args) -> B256 {
+    trading_journal::strategy_id(image_id::local_image_id(), strategy_params(args).hash())
+}
+
+/// Decodes the journal and checks that it was computed over the input we sent.
+#[tracing::instrument(name = "decode", skip_all)]
+fn decode_journal(args: &Args, journal: &[u8], input_hash: B256) -> Result<GuestOutput> {
+    let output = if args.lenient_decode {
+        GuestOutput::decode_lenient(journal, args.decimals)?
+    } else {
+        GuestOutput::decode(journal, args.decimals)?
+    };
+    tracing::info!("Clamping adjusted {} price points", output.clamped_points);
+    tracing::info!("Outlier trimming dropped {} price points", output.trimmed_points);
+    if output.version >= trading_journal::JOURNAL_V4 {
+        tracing::info!("Models used {} price points", output.data_points);
+    }
+    tracing::info!("Regression slope: {} USD/day", output.slope);
+    tracing::info!(
+        "Slope {} the last day-over-day change",
+        if output.agreement { "agrees with" } else { "disagrees with" }
+    );
+    if args.report {
+        signal::log_panel(&output.panel);
+    }
+
+    // The committed input hash ties the proof to the exact series we sent
+    tracing::info!("Input hash: {}", output.input_hash);
+    if output.input_hash != input_hash {
+        bail!(
+            "journal input hash {} does not match the submitted price history hash {}",
+            output.input_hash,
+            input_hash
+        );
+    }
+    let journal_version = if args.legacy_encoding {
+        trading_journal::JOURNAL_LEGACY
+    } else {
+        args.journal_version
+    };
+    if output.version != journal_version {
+        bail!(
+            "journal version {} does not match the requested version {}",
+            output.version,
+            journal_version
+        );
+    }
+    // The compact layout does not commit the models
+    let models = Model::mask(&args.ensemble);
+    if output.version != trading_journal::JOURNAL_V1 && output.models != models {
+        bail!(
+            "journal models {:#x} do not match the requested models {:#x}",
+            output.models,
+            models
+        );
+    }
+
+    if output.version >= trading_journal::JOURNAL_V8 {
+        let params_hash = strategy_params(args).hash();
+        if output.params_hash != params_hash {
+            bail!(
+                "journal params hash {} does not match the requested parameters' hash {}",
+                output.params_hash,
+                params_hash
+            );
+        }
+    }
+    if let Some(strategy_id) = output.strategy_id {
+        let expected = expected_strategy_id(args);
+        if strategy_id != expected {
+            bail!(
+                "journal strategy ID {} does not match this guest and parameters' ID {}",
+                strategy_id,
+                expected
+            );
+        }
+        tracing::info!("Strategy ID: {}", strategy_id);
+    }
+    if output.version >= trading_journal::JOURNAL_V9 {
+        tracing::info!("Last observed price: ${} USD", output.last_price);
+    }
+    if output.version >= trading_journal::JOURNAL_V11 {
+        tracing::info!("Regression residuals (USD): {:?}", output.residuals);
+    }
+    for (horizon, signal) in &output.horizons {
+        tracing::info!("Signal {} day(s) ahead: {}", horizon, signal);
+    }
+
+    tracing::info!(
+        "Trading Signal: {} ETH (confidence: {}%, predicted price: ${} USD)",
+        output.signal,
+        signal::format_bps_percent(output.confidence_bps),
+        output.predicted_price
+    );
+    if output.version >= trading_journal::JOURNAL_V3 {
+        tracing::info!(
+            "{} (up {}% / down {}%)",
+            output.signal,
+            output.up_confidence,
+            output.down_confidence
+        );
+    }
+    if args.predicted_return {
+        tracing::info!("Predicted return: {} bps", output.predicted_return_bps);
+    }
+    Ok(output)
+}
+
+/// Decodes the shorter journal committed in raw prediction mode.
+///
+/// It carries no input hash, so unlike [decode_journal] it cannot be tied to the submitted series.
+fn decode_raw_prediction(args: &Args, journal: &[u8]) -> Result<RawPrediction> {
+    let prediction = RawPrediction::decode(journal, args.decimals, args.bps_confidence)?;
+    tracing::info!(
+        "Raw prediction: ${} USD (confidence: {}%)",
+        prediction.predicted_price,
+        signal::format_bps_percent(prediction.confidence_bps)
+    );
+    Ok(prediction)
+}
+
+/// Applies the app-side constraints to the guest's signal.
+fn decide_action(args: &Args, output: &GuestOutput) -> Action {
+    let signal = output.signal;
+    if signal == Action::Hold {
+        tracing::info!(
+            "The guest signals HOLD: {}",
+            signal::describe_reason(output.reason)
+        );
+        return signal;
+    }
+
+    if args.require_positive_confidence && output.confidence_bps == 0 {
+        tracing::info!("The guest has no confidence in its {} signal, holding", signal);
+        return Action::Hold;
+    }
+
+    // A data error is no reason to trade whatever the strategy
+    if signal::suppress_extreme_move(
+        signal,
+        args.current_price,
+        output.predicted_price,
+        args.max_predicted_move_bps,
+    ) == Action::Hold
+    {
+        tracing::warn!(
+            "Predicted move of {} bps exceeds --max-predicted-move-bps {}, holding",
+            signal::predicted_move_bps(args.current_price, output.predicted_price),
+            args.max_predicted_move_bps.unwrap_or_default()
+        );
+        return Action::Hold;
+    }
+
+    if let Some(expr) = &args.decision_expr {
+        if !expr.holds(&decision::variables(args.current_price, output)) {
+            tracing::info!("--decision-expr `{}` does not hold (unproven), holding", expr);
+            return Action::Hold;
+        }
+    }
+
+    let action = args.strategy.apply(signal);
+    if action == Action::Hold {
+        tracing::info!("Strategy {:?} maps the signal to HOLD", args.strategy);
+        return action;
+    }
+
+    let action = signal::suppress_small_move(
+        action,
+        args.current_price,
+        output.predicted_price,
+        args.min_predicted_move_bps,
+    );
+    if action == Action::Hold {
+        tracing::info!(
+            "Predicted move of {} bps is below --min-predicted-move-bps {}, holding",
+            signal::predicted_move_bps(args.current_price, output.predicted_price),
+            args.min_predicted_move_bps
+        );
+    }
+    action
+}
+
+/// Recomputes the linear regression of `--explain` over the series the guest fits: the loaded
+/// `price_history` after `--downsample`, or the embedded series when it is empty.
+fn explain(args: &Args, price_history: &[PricePoint]) -> Explanation {
+    let fitted = if price_history.is_empty() {
+        trading_journal::PRICE_HISTORY.to_vec()
+    } else {
+        downsampled_history(args, price_history)
+    };
+    Explanation::compute(
+        &fitted,
+        args.current_price,
+        args.buy_threshold_bps,
+        args.sell_threshold_bps,
+    )
+}
+
+/// Executes the guest locally and reports the resulting action, without proving or touching the
+/// chain.
+async fn run_dry_run(args: &Args) -> Result<RunRecord> {
+    let args = &resolve_args(args).await?;
+    let price_history = load_price_history(args).await?;
+    if args.compare_models {
+        let rows = compare::compare(|models| {
+            let args = Args {
+                ensemble: models.to_vec(),
+                ..args.clone()
+            };
+            let (input_bytes, _) = build_guest_input(&args, price_history.clone())?;
+            let session_info = executor::execute_with_limit(&input_bytes, args.max_cycles)?;
+            GuestOutput::decode(&session_info.journal.bytes, args.decimals)
+        })?;
+        tracing::info!("Model comparison:\n{}", compare::table(&rows));
+    }
+
+    let explanation = args.explain.then(|| explain(args, &price_history));
+    let (input_bytes, input_hash) = build_guest_input(args, price_history)?;
+    let session_info = executor::execute_with_limit(&input_bytes, args.max_cycles)?;
+    if args.profile {
+        CycleReport::from_session(&session_info).log();
+    }
+    if let Some(explanation) = explanation {
+        explanation.log();
+    }
+
+    if args.raw_prediction {
+        let prediction = decode_raw_prediction(args, &session_info.journal.bytes)?;
+        return Ok(RunRecord::from_prediction(&prediction));
+    }
+
+    let output = decode_journal(args, &session_info.journal.bytes, input_hash)?;
+    if args.fail_on_sell {
+        receipt::reject_sell(&output)?;
+    }
+    let action = decide_action(args, &output);
+    tracing::info!("Dry run complete, action: {}", action);
+    Ok(RunRecord::new(action, &output, None))
+}
+
+/// Runs every line of the `--batch-file` in turn, as a dry run if `client` is None and as a single
+/// proving round otherwise, and appends each result to `--output-file`.
+///
+/// Stops at the first line that fails; the results of the lines before it are already written.
+async fn run_batch(args: &Args, path: &Path, client: Option<&Client>) -> Result<()> {
+    if args.threshold_source == ThresholdSource::Onchain {
+        bail!(
+            "--batch-file lines set their own thresholds, so it cannot be combined with \
+             --threshold-source onchain"
+        );
+    }
+    let entries = batch::load(path)?;
+    let output = match args.output {
+        OutputFormat::Text => OutputFormat::Json,
+        output => output,
+    };
+    for (index, entry) in entries.into_iter().enumerate() {
+        let args = Args {
+            current_price: entry.current_price,
+            ensemble: entry.models.unwrap_or_else(|| args.ensemble.clone()),
+            buy_threshold_bps: entry.buy_threshold_bps.unwrap_or(args.buy_threshold_bps),
+            sell_threshold_bps: entry.sell_threshold_bps.unwrap_or(args.sell_threshold_bps),
+            history_override: entry.history,
+            output,
+            ..args.clone()
+        };
+        tracing::info!("Running batch entry {}", index + 1);
+        let record = match client {
+            Some(client) => run_once(&args, client).await,
+            None => run_dry_run(&args).await,
+        }
+        .with_context(|| format!("batch entry {} failed", index + 1))?;
+        report(&args, &record)?;
+    }
+    Ok(())
+}
+
+/// Replays the `--replay-from-ledger` ticks through the guest and the loop mode's decision gates,
+/// and logs the resulting paper-trading ledger.
+///
+/// A SELL rejected by `--fail-on-sell` fails that run in the loop, so it does not trade here
+/// either.
+async fn run_ledger_replay(args: &Args, path: &Path) -> Result<()> {
+    let ticks = ledger::load_ticks(path)?;
+    let last_price = ticks.last().context("no ticks to replay")?.1;
+    let price_history = load_price_history(args).await?;
+    tracing::info!("Replaying {} ticks", ticks.len());
+    let ledger = ledger::replay(&ticks, args.interval_secs, |price| {
+        let args = Args {
+            current_price: price,
+            ..args.clone()
+        };
+        let (input_bytes, input_hash) = build_guest_input(&args, price_history.clone())?;
+        let session_info = executor::execute(&input_bytes)?;
+        let output = decode_journal(&args, &session_info.journal.bytes, input_hash)?;
+        if args.fail_on_sell {
+            if let Err(e) = receipt::reject_sell(&output) {
+                tracing::warn!("Run at ${} would have failed: {}", price, e);
+                return Ok(Action::Hold);
+            }
+        }
+        Ok(decide_action(&args, &output))
+    })?;
+    ledger.log(last_price);
+    Ok(())
+}
+
+/// Prints the deployment the Boundless client would use on the connected chain.
+async fn run_deployment(args: &Args) -> Result<()> {
+    let provider = rpc::read_only_provider(&args.rpc_url).await?;
+    let chain_id = provider
+        .get_chain_id()
+        .await
+        .context("failed to query chain ID")?;
+    println!(
+        "{}",
+        deployment::describe(&deployment::resolve(deployment(args)?, chain_id)?)
+    );
+    Ok(())
+}
+
+/// Prints the signals the contract published since `since_block`, oldest first.
+async fn run_signals(args: &Args, since_block: u64) -> Result<()> {
+    let address = args
+        .trading_signal_address
+        .context("--trading-signal-address is required")?;
+    let provider = rpc::read_only_provider(&args.rpc_url).await?;
+    let signals = events::scan_signals(provider, address, since_block).await?;
+    tracing::info!("Found {} signals since block {}", signals.len(), since_block);
+    for signal in &signals {
+        println!("{}", signal.line(args.decimals));
+    }
+    Ok(())
+}
+
+/// Prints the signal stored in the contract every time a poll finds it changed. Runs until
+/// interrupted; a failed read is logged and retried at the next poll.
+async fn run_watch(args: &Args, poll_interval: Duration) -> Result<()> {
+    let address = args
+        .trading_signal_address
+        .context("--trading-signal-address is required")?;
+    let provider = rpc::read_only_provider(&args.rpc_url).await?;
+    let mut watcher = watch::SignalWatcher::default();
+    let mut interval = tokio::time::interval(poll_interval);
+    tracing::info!("Watching TradingSignal {} every {:?}", address, poll_interval);
+    loop {
+        interval.tick().await;
+        match watch::read_latest_signal(&provider, address).await {
+            Ok(signal) if watcher.changed(&signal) => {
+                println!("{}", watch::line(&signal, args.decimals)?)
+            }
+            Ok(_) => {}
+            Err(err) => tracing::warn!("{:#}", err),
+        }
+    }
+}
+
+/// Backtests the guest with the current settings over the supplied price history.
+fn run_backtest(args: &Args, min_train_len: usize) -> Result<()> {
+    let price_history = load_backtest_history(args)?;
+    let report = backtest::run(&price_history, min_train_len, |train, current_price| {
+        predict_locally(args, train, current_price)
+    })?;
+    report.log();
+    Ok(())
+}
+
+/// Backtests each symmetric BUY/SELL threshold and logs the best one.
+fn run_tune(
+    args: &Args,
+    min_train_len: usize,
+    thresholds_bps: impl IntoIterator<Item = u64>,
+    objective: TuneObjective,
+) -> Result<()> {
+    let price_history = load_backtest_history(args)?;
+    let (best, report) = backtest::tune(
+        &price_history,
+        min_train_len,
+        thresholds_bps,
+        objective,
+        |threshold_bps, train, current_price| {
+            let args = Args {
+                buy_threshold_bps: threshold_bps,
+                sell_threshold_bps: threshold_bps,
+                ..args.clone()
+            };
+            predict_locally(&args, train, current_price)
+        },
+    )?;
+    tracing::info!("Best threshold: {} bps", best);
+    report.log();
+    Ok(())
+}
+
+/// Loads `--price-history-csv` for a backtest.
+fn load_backtest_history(args: &Args) -> Result<Vec<PricePoint>> {
+    let path = args
+        .price_history_csv
+        .as_deref()
+        .context("backtest requires --price-history-csv")?;
+    if args.raw_prediction {
+        bail!("backtest needs the guest's signal and cannot run with --raw-prediction");
+    }
+    let price_history = history::load_price_history(path)?;
+    history::check_history_len(&price_history, args.max_history_len)?;
+    tracing::info!("Backtesting over {} price history points", price_history.len());
+    Ok(price_history)
+}
+
+/// Executes the guest on `train` and returns the action the strategy takes on its signal.
+fn predict_locally(args: &Args, train: &[PricePoint], current_price: u64) -> Result<Action> {
+    let output = execute_locally(args, train, current_price)?;
+    Ok(args.strategy.apply(output.signal))
+}
+
+/// Executes the guest on `history` with the CLI settings and decodes its journal.
+fn execute_locally(args: &Args, history: &[PricePoint], current_price: u64) -> Result<GuestOutput> {
+    let (input_bytes, _) = encode_guest_input(args, current_price, history.to_vec(), Vec::new());
+    let session_info = executor::execute(&input_bytes)?;
+    GuestOutput::decode(&session_info.journal.bytes, args.decimals)
+}
+
+/// Checks the guest outputs on `cases` generated series of each stress scenario.
+fn run_stress(args: &Args, cases: usize, seed: u64) -> Result<()> {
+    stress::run(cases, seed, |history, current_price| {
+        execute_locally(args, history, current_price)
+    })
+    .check()
+}
+
+/// Checks a locally executed signal against the gates a proven one goes through, and returns the
+/// record of the run if a proof of it would not be published, so that none is paid for.
+fn preflight(args: &Args, output: &GuestOutput) -> Result<Option<RunRecord>> {
+    if args.fail_on_sell {
+        receipt::reject_sell(output)?;
+    }
+    let action = decide_action(args, output);
+    Ok((action != output.signal).then(|| RunRecord::new(action, output, None)))
+}
+
+/// Creates the request parameters for the given guest input.
+///
+/// Uses the provided program URL, or uploads the program and reports where it went so that it
+/// can be passed as --program-url next time.
+async fn new_request(
+    args: &Args,
+    client: &Client,
+    input_bytes: Vec<u8>,
+) -> Result<(RequestParams, Url)> {
+    let program_url = match &args.program_url {
+        Some(program_url) => program_url.clone(),
+        None => {
+            let program_url = client
+                .upload_program(TRADING_SIGNAL_ELF)
+                .await
+                .context("failed to upload program")?;
+            tracing::info!("Uploaded program to {}, reuse it with --program-url", program_url);
+            program_url
+        }
     };
+    let request_input = input::request_input(input_bytes, |encoded| async move {
+        Ok(client.upload_input(&encoded).await?)
+    })
+    .await?;
+    let request = client
+        .new_request()
+        .with_program_url(program_url.clone())?
+        .with_request_input(request_input);
+    Ok((with_max_price(request, args.max_request_price), program_url))
+}
+
+/// Caps the price the request offers the prover, if a cap is set.
+fn with_max_price(request: RequestParams, max_price: Option<U256>) -> RequestParams {
+    match max_price {
+        Some(max_price) => request.with_offer(OfferParams::builder().max_price(max_price)),
+        None => request,
+    }
+}
 
-    let (request_id, expires_at) = client.submit_onchain(request).await?;
+/// Builds the request a run would submit, without submitting it or appending to the
+/// `--series-file`, and logs the estimated cost of the run.
+async fn run_simulate_fees(args: &Args, client: &Client) -> Result<()> {
+    let args = &resolve_args(args).await?;
+    let (input_bytes, _) = build_guest_input(args, load_price_history(args).await?)?;
+    let (params, _) = new_request(args, client, input_bytes).await?;
+    let request = client
+        .build_request(params)
+        .await
+        .context("failed to build request")?;
+    let gas_price = client
+        .provider()
+        .get_gas_price()
+        .await
+        .context("failed to fetch gas price")?;
+
+    FeeEstimate {
+        min_proving_price: request.offer.minPrice,
+        max_proving_price: request.offer.maxPrice,
+        gas_price,
+        set_signal_gas: fees::SET_SIGNAL_GAS_ESTIMATE,
+    }
+    .log();
+    Ok(())
+}
+
+/// Runs a single proving round and returns a record of the action taken.
+async fn run_trading_signal_mode(args: &Args, client: &Client) -> Result<RunRecord> {
+    let contract_address = args
+        .trading_signal_address
+        .context("--trading-signal-address is required")?;
+    if args.check_image_id {
+        let expected = image_id::read_image_id(client.provider(), contract_address).await?;
+        image_id::check_image_id(expected, image_id::local_image_id())?;
+        tracing::info!("TradingSignal {} expects image ID {}", contract_address, expected);
+    }
+    let (input_bytes, input_hash) = build_guest_input(args, load_price_history(args).await?)?;
+    if args.dry_run_against_history {
+        let session_info = executor::execute(&input_bytes)?;
+        let output = decode_journal(args, &session_info.journal.bytes, input_hash)?;
+        if let Some(record) = preflight(args, &output)? {
+            tracing::info!("Local execution would not be published, skipping the proof request");
+            return Ok(record);
+        }
+    }
+    let (request, program_url) = new_request(args, client, input_bytes.clone()).await?;
+
+    let (request_id, expires_at) = client
+        .submit_onchain(request)
+        .instrument(tracing::info_span!("submit"))
+        .await?;
 
     // Wait for the request to be fulfilled
     tracing::info!("Waiting for trading signal request {:x} to be fulfilled", request_id);
@@ -127,116 +1662,858 @@ async fn run_trading_signal_mode(args: &Args, client: &Client) -> Result<()> {
             Duration::from_secs(5), // check every 5 seconds
             expires_at,
         )
-        .await?;
+        .instrument(tracing::info_span!("wait_for_fulfillment"))
+        .await
+        .map_err(|e| match (e, args.max_request_price) {
+            (ClientError::MarketError(MarketError::RequestHasExpired(_)), Some(max_price)) => {
+                anyhow::anyhow!(
+                    "request {:x} expired unfulfilled: no prover took it at up to {} wei \
+                     (--max-request-price)",
+                    request_id,
+                    max_price
+                )
+            }
+            (e, _) => e.into(),
+        })?;
     tracing::info!("Request {:x} fulfilled", request_id);
 
-    // Decode the journal data from the guest program
-    let data = &fulfillment.fulfillmentData;
-    tracing::info!("Raw fulfillment data length: {} bytes", data.len());
-    tracing::info!("Fulfillment data hex: {}", hex::encode(data));
-    
-    // The guest now uses proper ABI encoding for (uint8, uint256, uint256)
-    // Boundless wraps our journal data in a fulfillment structure
-    // Based on the hex data, our 96-byte journal starts at offset 128 (0x80)
-    let output: (U256, U256, U256) = if data.len() >= 224 {
-        // Extract our journal from the Boundless fulfillment data
-        // The structure is: [32-byte offset][32-byte IMAGE_ID][32-byte offset][32-byte offset][96-byte journal]
-        let journal_start = 128; // Offset where our journal data starts
-        let journal_data = &data[journal_start..journal_start + 96];
-        
-        tracing::info!("Extracting journal from offset {} (96 bytes)", journal_start);
-        tracing::debug!("Journal hex: {}", hex::encode(journal_data));
-        
-        if let Ok(decoded_tuple) = <(U256, U256, U256)>::abi_decode(journal_data) {
-            tracing::info!("Successfully decoded journal data as ABI tuple");
-            decoded_tuple
-        } else {
-            tracing::error!("Failed to decode journal data at expected offset");
-            // Return safe fallback values
-            (U256::from(0u8), U256::from(0u64), U256::from(3200u64))
-        }
-    } else if data.len() >= 96 {
-        // Fallback: try to find a 96-byte window that decodes properly
-        let mut found_tuple = None;
-        
-        for start_offset in (0..=data.len().saturating_sub(96)).step_by(32) {
-            let potential_tuple_data = &data[start_offset..start_offset + 96];
-            if let Ok(decoded_tuple) = <(U256, U256, U256)>::abi_decode(potential_tuple_data) {
-                // Validate that this looks like reasonable trading data
-                let signal = decoded_tuple.0.as_limbs()[0] as u8;
-                let confidence = decoded_tuple.1.as_limbs()[0];
-                let price = decoded_tuple.2.as_limbs()[0];
-                
-                if signal <= 1 && confidence <= 100 && price >= 1000 && price <= 50000 { // USD price range $1000-$50000
-                    found_tuple = Some(decoded_tuple);
-                    tracing::info!("Found valid ABI tuple at offset {}: signal={}, confidence={}, price={}", 
-                                  start_offset, signal, confidence, price);
-                    break;
-                }
-            }
+    // Extract the journal committed by the guest from the fulfillment data
+    let journal = fulfillment
+        .data()
+        .context("failed to decode fulfillment data")?
+        .journal()
+        .cloned()
+        .context("fulfillment does not contain a journal")?;
+    tracing::debug!("Journal hex: {}", hex::encode(&journal));
+
+    if args.verify_journal {
+        executor::verify_journal(&input_bytes, &journal)?;
+        tracing::info!("Journal matches local execution");
+    }
+
+    if let Some(path) = &args.save_receipt {
+        SavedReceipt {
+            journal: journal.clone(),
+            seal: fulfillment.seal.clone(),
         }
-        
-        found_tuple.unwrap_or_else(|| {
-            tracing::error!("Failed to decode fulfillment data as valid trading signal tuple");
-            (U256::from(0u8), U256::from(0u64), U256::from(3200u64))
-        })
-    } else {
-        tracing::error!("Fulfillment data too short, expected at least 96 bytes but got {}", data.len());
-        (U256::from(0u8), U256::from(0u64), U256::from(3700000000000000000u64))
+        .save(path)?;
+        tracing::info!("Saved receipt to {}", path.display());
+    }
+
+    let seal = fulfillment.seal;
+    let record = publish(
+        args,
+        client,
+        contract_address,
+        journal.clone(),
+        seal.clone(),
+        input_hash,
+    )
+    .await?
+    .with_program_url(program_url);
+    if let Some(dir) = &args.output_dir {
+        let folder = RunArchive {
+            input: &input_bytes,
+            journal: &journal,
+            seal: &seal,
+            record: &record,
+        }
+        .write(dir)?;
+        tracing::info!("Archived the run in {}", folder.display());
+    }
+    Ok(record)
+}
+
+/// Decodes a proven journal and publishes its signal as the CLI arguments ask, returning the
+/// record of the action taken.
+async fn publish(
+    args: &Args,
+    client: &Client,
+    contract_address: Address,
+    journal: Bytes,
+    seal: Bytes,
+    input_hash: B256,
+) -> Result<RunRecord> {
+    if args.raw_prediction {
+        let prediction = decode_raw_prediction(args, &journal)?;
+        tracing::info!("Raw prediction mode, skipping contract update");
+        return Ok(RunRecord::from_prediction(&prediction));
+    }
+
+    let output = decode_journal(args, &journal, input_hash)?;
+    if args.fail_on_sell {
+        receipt::reject_sell(&output)?;
+    }
+    let action = decide_action(args, &output);
+    // The proven journal carries the guest's signal, so an app-side override cannot be published
+    if action != output.signal {
+        tracing::info!("Holding, skipping contract update");
+        return Ok(RunRecord::new(action, &output, None));
+    }
+    // The guest clamps negative predictions to zero, which the contract rejects
+    if output.predicted_amount.is_zero() {
+        tracing::warn!("Predicted price clamped to zero, skipping contract update");
+        return Ok(RunRecord::new(action, &output, None));
+    }
+
+    if args.emit_calldata {
+        tracing::info!("setSignal calldata for {}:", contract_address);
+        println!("{}", set_signal_calldata(journal, seal));
+        return Ok(RunRecord::new(action, &output, None));
+    }
+
+    if args.emit_eip712 {
+        let signer = args.private_key.as_ref().context("--private-key is required")?;
+        let message = oracle::SignedSignal::new(
+            &output,
+            image_id::local_image_id(),
+            price_feed::unix_now()?,
+        );
+        let signed = oracle::sign(signer, message)?;
+        tracing::info!("Signed EIP-712 signal as {}:", signed.signer);
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&signed).context("failed to serialize signed signal")?
+        );
+        return Ok(RunRecord::new(action, &output, None));
+    }
+
+    let sink = ContractSink {
+        client,
+        address: contract_address,
+        decimals: args.decimals,
+        tx_timeout: Duration::from_secs(args.tx_timeout_secs),
+        manage_nonce: args.manage_nonce,
     };
-    
-    // Debug: Print raw decoded values
-    tracing::info!("Raw decoded values: signal={}, confidence={}, predicted_price={}", 
-                   output.0, output.1, output.2);
-    
-    let signal = output.0.as_limbs()[0] as u8;
-    let confidence = output.1.as_limbs()[0];
-    let predicted_price = output.2.as_limbs()[0];
-    
-    // Debug: Print converted values  
-    tracing::info!("Converted values: signal={}, confidence={}, predicted_price={}", 
-                   signal, confidence, predicted_price);
-
-    let action_str = if signal == 1 { "BUY" } else { "SELL" };
-    tracing::info!(
-        "Trading Signal: {} ETH (confidence: {}%, predicted price: ${} USD)",
-        action_str,
-        confidence,
-        predicted_price
-    );
+    let tx_hash = receipt::submit(&sink, journal, seal, args.contract_read_only_check).await?;
 
-    // Interact with the TradingSignal contract
-    let trading_signal = ITradingSignalInstance::new(contract_address, client.provider().clone());
-    let call_set = trading_signal
-        .setSignal(signal, U256::from(confidence), U256::from(predicted_price), fulfillment.seal)
-        .from(client.caller());
-
-    tracing::info!("Calling TradingSignal setSignal function");
-    let pending_tx = call_set.send().await.context("failed to broadcast tx")?;
-    tracing::info!("Broadcasting tx {}", pending_tx.tx_hash());
-    let tx_hash = pending_tx
-        .with_timeout(Some(TX_TIMEOUT))
-        .watch()
-        .await
-        .context("failed to confirm tx")?;
-    tracing::info!("Tx {:?} confirmed", tx_hash);
+    Ok(RunRecord::new(action, &output, Some(tx_hash)))
+}
+
+/// Re-submits a saved receipt and returns a record of the replayed action.
+async fn run_replay(args: &Args, client: &Client, path: &Path) -> Result<RunRecord> {
+    let sink = ContractSink {
+        client,
+        address: args
+            .trading_signal_address
+            .context("--trading-signal-address is required")?,
+        decimals: args.decimals,
+        tx_timeout: Duration::from_secs(args.tx_timeout_secs),
+        manage_nonce: args.manage_nonce,
+    };
+    tracing::info!("Replaying receipt {}", path.display());
+    let (output, tx_hash) = receipt::replay(
+        path,
+        &sink,
+        args.decimals,
+        args.contract_read_only_check,
+        args.fail_on_sell,
+    )
+    .await?;
+    Ok(RunRecord::new(
+        output.signal,
+        &output,
+        Some(tx_hash),
+    ))
+}
+
+/// Submits signals to the TradingSignal contract.
+struct ContractSink<'a> {
+    client: &'a Client,
+    address: Address,
+    /// Decimals of the stored predicted price, used to display it in whole units.
+    decimals: u8,
+    /// How long to wait for the transaction to be confirmed.
+    tx_timeout: Duration,
+    /// Whether to set the nonce of the transaction to the pending one.
+    manage_nonce: bool,
+}
 
-    // Query the stored signal
-    let latest_signal = trading_signal
-        .getLatestSignal()
+impl SignalSink for ContractSink<'_> {
+    async fn simulate_signal(&self, journal: &Bytes, seal: &Bytes) -> Result<()> {
+        tracing::info!("Simulating TradingSignal setSignal call");
+        CallBuilder::new_raw(
+            self.client.provider().clone(),
+            set_signal_calldata(journal.clone(), seal.clone()),
+        )
+        .to(self.address)
+        .from(self.client.caller())
         .call()
         .await
-        .context("failed to get latest signal from contract")?;
-    
-    let action_display = if latest_signal.action == 1 { "BUY" } else { "SELL" };
-    tracing::info!(
-        "Contract updated - Action: {}, Confidence: {}%, Predicted: {} wei ({:.2} ETH), Timestamp: {}",
-        action_display,
-        latest_signal.confidence,
-        latest_signal.predictedPrice.as_limbs()[0],
-        latest_signal.predictedPrice.as_limbs()[0] as f64 / 1e18,
-        latest_signal.timestamp
-    );
+        .context("setSignal simulation reverted")?;
+        Ok(())
+    }
 
-    Ok(())
+    async fn set_signal(&self, journal: Bytes, seal: Bytes) -> Result<B256> {
+        // Interact with the TradingSignal contract
+        let trading_signal =
+            ITradingSignalInstance::new(self.address, self.client.provider().clone());
+        let calldata = set_signal_calldata(journal, seal);
+        let mut call_set = CallBuilder::new_raw(self.client.provider().clone(), calldata)
+            .to(self.address)
+            .from(self.client.caller());
+        if self.manage_nonce {
+            call_set = with_pending_nonce(call_set, self.client.caller()).await?;
+        }
+
+        tracing::info!("Calling TradingSignal setSignal function");
+        let pending_tx = call_set.send().await.context("failed to broadcast tx")?;
+        tracing::info!("Broadcasting tx {}", pending_tx.tx_hash());
+        let tx_hash = with_tx_timeout(pending_tx, self.tx_timeout)
+            .watch()
+            .await
+            .context("failed to confirm tx")?;
+        tracing::info!("Tx {:?} confirmed", tx_hash);
+
+        // Query the stored signal
+        let latest_signal = trading_signal
+            .getLatestSignal()
+            .call()
+            .await
+            .context("failed to get latest signal from contract")?;
+
+        let action_display = Action::try_from(latest_signal.action)
+            .context("contract stores an invalid action")?;
+        tracing::info!(
+            "Contract updated - Action: {}, Confidence: {}%, Predicted: {} base units with {} decimals ({} USD), Input hash: {}, Timestamp: {}",
+            action_display,
+            latest_signal.confidence,
+            latest_signal.predictedPrice,
+            latest_signal.priceDecimals,
+            trading_journal::format_wei(latest_signal.predictedPrice, self.decimals),
+            latest_signal.inputHash,
+            latest_signal.timestamp
+        );
+
+        Ok(tx_hash)
+    }
+}
+
+/// ABI-encodes a `setSignal(journal, seal)` call on the TradingSignal contract, or a
+/// `setSignalForStrategy(journal, seal, strategyId)` call if the journal commits a strategy ID.
+fn set_signal_calldata(journal: Bytes, seal: Bytes) -> Bytes {
+    match trading_journal::Journal::decode_lenient(&journal) {
+        Ok(trading_journal::Journal::V13(committed)) => ITradingSignal::setSignalForStrategyCall {
+            journal,
+            seal,
+            strategyId: committed.strategy_id,
+        }
+        .abi_encode()
+        .into(),
+        _ => ITradingSignal::setSignalCall { journal, seal }
+            .abi_encode()
+            .into(),
+    }
+}
+
+/// Sets the nonce of a call to the pending transaction count of `from`, so that it queues behind
+/// transactions still in the mempool instead of reusing one of their nonces.
+async fn with_pending_nonce<P: Provider<N>, D: CallDecoder, N: Network>(
+    call: CallBuilder<P, D, N>,
+    from: Address,
+) -> Result<CallBuilder<P, D, N>> {
+    let nonce = call
+        .provider
+        .get_transaction_count(from)
+        .pending()
+        .await
+        .context("failed to fetch the pending nonce")?;
+    tracing::info!("Using pending nonce {}", nonce);
+    Ok(call.nonce(nonce))
+}
+
+/// Bounds how long watching a broadcast transaction waits for its confirmation.
+fn with_tx_timeout<N: Network>(
+    pending_tx: PendingTransactionBuilder<N>,
+    tx_timeout: Duration,
+) -> PendingTransactionBuilder<N> {
+    pending_tx.with_timeout(Some(tx_timeout))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{providers::RootProvider, rpc::client::RpcClient, transports::mock::Asserter};
+    use clap::error::ErrorKind;
+
+    use super::*;
+    use crate::config::ENV_LOCK;
+
+    fn parse(args: &[&str]) -> Result<Args> {
+        parse_args(args.iter().map(OsString::from).collect())
+    }
+
+    fn parse_error(args: &[&str]) -> ErrorKind {
+        parse(args).unwrap_err().downcast::<clap::Error>().unwrap().kind()
+    }
+
+    #[test]
+    fn defaults_need_no_flags() {
+        let _env = ENV_LOCK.lock().unwrap();
+        let args = parse(&["app"]).unwrap();
+
+        assert!(args.command.is_none());
+        assert_eq!(args.current_price, 3200);
+        assert_eq!(args.ensemble, [Model::Linreg]);
+        assert_eq!(
+            (args.buy_threshold_bps, args.sell_threshold_bps),
+            (50, 50)
+        );
+        assert_eq!(args.output, OutputFormat::Text);
+        assert_eq!(args.tx_timeout_secs, TX_TIMEOUT.as_secs());
+        assert_eq!(args.decimals, 0);
+        assert!(args.rpc_url.is_empty());
+        assert!(!args.dry_run && !args.offchain && !args.report);
+    }
+
+    #[test]
+    fn required_combinations_are_enforced() {
+        let _env = ENV_LOCK.lock().unwrap();
+        assert_eq!(
+            parse_error(&["app", "--offchain"]),
+            ErrorKind::MissingRequiredArgument
+        );
+        let args = parse(&[
+            "app",
+            "--offchain",
+            "--order-stream-url",
+            "https://order-stream.example",
+            "--boundless-market-address",
+            "0x0000000000000000000000000000000000000001",
+            "--set-verifier-address",
+            "0x0000000000000000000000000000000000000002",
+        ])
+        .unwrap();
+        assert!(args.offchain);
+
+        assert_eq!(
+            parse_error(&["app", "--output", "csv"]),
+            ErrorKind::MissingRequiredArgument
+        );
+        assert_eq!(
+            parse_error(&["app", "--explain"]),
+            ErrorKind::MissingRequiredArgument
+        );
+        assert_eq!(
+            parse_error(&["app", "--profile"]),
+            ErrorKind::MissingRequiredArgument
+        );
+        assert_eq!(
+            parse_error(&["app", "--history-source", "onchain"]),
+            ErrorKind::MissingRequiredArgument
+        );
+        assert_eq!(
+            parse_error(&["app", "--max-price-age-secs", "60"]),
+            ErrorKind::MissingRequiredArgument
+        );
+        assert_eq!(
+            parse_error(&["app", "--prior-weight", "101"]),
+            ErrorKind::ValueValidation
+        );
+    }
+
+    #[test]
+    fn flags_parse() {
+        let _env = ENV_LOCK.lock().unwrap();
+        let args = parse(&[
+            "app",
+            "--rpc-url",
+            "http://localhost:8545,http://localhost:8546",
+            "--ensemble",
+            "linreg,sma-crossover",
+            "--prior-slope",
+            "-5",
+            "--output",
+            "json",
+            "--output-file",
+            "runs.jsonl",
+            "--max-predicted-move-bps",
+            "2000",
+            "--strategy",
+            "long-only",
+            "tune",
+            "--step-bps",
+            "25",
+        ])
+        .unwrap();
+
+        assert_eq!(args.rpc_url.len(), 2);
+        assert_eq!(args.ensemble, [Model::Linreg, Model::SmaCrossover]);
+        assert_eq!(args.prior_slope, -5);
+        assert_eq!(args.output, OutputFormat::Json);
+        assert_eq!(args.max_predicted_move_bps, Some(2000));
+        assert_eq!(args.strategy, Strategy::LongOnly);
+        assert!(matches!(
+            args.command,
+            Some(Command::Tune {
+                step_bps: 25,
+                max_threshold_bps: 200,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn calldata_decodes_to_set_signal_arguments() {
+        let journal = Bytes::from_static(&[0x01; 416]);
+        let seal = Bytes::from_static(&[0xde, 0xad, 0xbe, 0xef]);
+        let calldata = set_signal_calldata(journal.clone(), seal.clone());
+
+        assert_eq!(
+            calldata[..4],
+            keccak256("setSignal(bytes,bytes)")[..4],
+            "calldata should start with the setSignal selector"
+        );
+        let call = ITradingSignal::setSignalCall::abi_decode(&calldata).unwrap();
+        assert_eq!((call.journal, call.seal), (journal, seal));
+    }
+
+    #[test]
+    fn calldata_carries_the_price_decimals() {
+        let journal = trading_journal::Journal::V10(trading_journal::JournalV10 {
+            decimals: 6,
+            ..Default::default()
+        })
+        .encode();
+        let calldata = set_signal_calldata(journal.into(), Bytes::new());
+
+        // The word the contract reads the decimals from, before the version word
+        let call = ITradingSignal::setSignalCall::abi_decode(&calldata).unwrap();
+        assert_eq!(U256::from_be_slice(&call.journal[704..736]), U256::from(6));
+        assert_eq!(U256::from_be_slice(&call.journal[736..]), U256::from(10));
+    }
+
+    #[test]
+    fn piped_current_price_encodes_like_the_flag() {
+        let _env = ENV_LOCK.lock().unwrap();
+        let args = parse(&["app", "--current-price", "3700"]).unwrap();
+        let piped = read_current_price("-", std::io::Cursor::new(b"3700\n")).unwrap();
+        assert_eq!(
+            encode_guest_input(&args, piped, Vec::new(), Vec::new()),
+            encode_guest_input(&args, args.current_price, Vec::new(), Vec::new())
+        );
+        for stdin in [&b""[..], b"\n", b"3.7k\n"] {
+            let err = read_current_price("-", std::io::Cursor::new(stdin)).unwrap_err();
+            assert!(err.to_string().contains("current price"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn current_price_flag_goes_through_its_parser() {
+        let _env = ENV_LOCK.lock().unwrap();
+        let args = Args::try_parse_from(["app", "--current-price", "3700"]).unwrap();
+        assert_eq!(args.current_price, 3700);
+        let err = Args::try_parse_from(["app", "--current-price", "3.7k"]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ValueValidation);
+        assert!(err.to_string().contains("invalid current price"), "{}", err);
+    }
+
+    #[test]
+    fn strategy_journal_calls_set_signal_for_strategy() {
+        let strategy_id = B256::repeat_byte(0x5e);
+        let journal = trading_journal::Journal::V13(trading_journal::JournalV13 {
+            strategy_id,
+            ..Default::default()
+        })
+        .encode();
+        let calldata = set_signal_calldata(journal.clone().into(), Bytes::new());
+
+        let call = ITradingSignal::setSignalForStrategyCall::abi_decode(&calldata).unwrap();
+        assert_eq!(call.journal, journal);
+        assert_eq!(call.strategyId, strategy_id);
+    }
+
+    #[test]
+    fn tx_timeout_flows_into_watch() {
+        let _env = ENV_LOCK.lock().unwrap();
+        let pending_tx = || {
+            let provider = RootProvider::new(RpcClient::mocked(Asserter::new()));
+            PendingTransactionBuilder::<alloy::network::Ethereum>::new(provider, B256::ZERO)
+        };
+        let watch_timeout = |args: &[&str]| {
+            let args = Args::try_parse_from(args).unwrap();
+            with_tx_timeout(pending_tx(), Duration::from_secs(args.tx_timeout_secs))
+                .inner()
+                .timeout()
+        };
+
+        assert_eq!(watch_timeout(&["app"]), Some(TX_TIMEOUT));
+        assert_eq!(
+            watch_timeout(&["app", "--tx-timeout-secs", "120"]),
+            Some(Duration::from_secs(120))
+        );
+    }
+
+    #[tokio::test]
+    async fn manage_nonce_sets_the_pending_nonce() {
+        let asserter = Asserter::new();
+        asserter.push_success(&U256::from(42));
+        let provider = RootProvider::<alloy::network::Ethereum>::new(RpcClient::mocked(asserter));
+        let trading_signal = ITradingSignalInstance::new(Address::ZERO, provider);
+        let call = trading_signal
+            .setSignal(Bytes::new(), Bytes::new())
+            .from(Address::repeat_byte(0x11));
+
+        let call = with_pending_nonce(call, Address::repeat_byte(0x11))
+            .await
+            .unwrap();
+        assert_eq!(call.into_transaction_request().nonce, Some(42));
+    }
+
+    #[test]
+    fn max_request_price_caps_the_offer() {
+        let _env = ENV_LOCK.lock().unwrap();
+        let max_price = |args: &[&str]| {
+            let args = parse(args).unwrap();
+            with_max_price(RequestParams::new(), args.max_request_price)
+                .offer
+                .max_price
+        };
+        assert_eq!(max_price(&["app"]), None);
+        assert_eq!(
+            max_price(&["app", "--max-request-price", "2000000000000000"]),
+            Some(U256::from(2_000_000_000_000_000u64))
+        );
+    }
+
+    #[test]
+    fn decode_runs_in_decode_span() {
+        let _env = ENV_LOCK.lock().unwrap();
+        let args = parse(&["app"]).unwrap();
+        let (_guard, spans) = telemetry::capture_span_names();
+
+        assert!(decode_journal(&args, &[0u8; 31], B256::ZERO).is_err());
+        assert_eq!(*spans.lock().unwrap(), ["decode"]);
+    }
+
+    #[test]
+    fn preflight_skips_the_proof_of_a_gated_signal() {
+        let _env = ENV_LOCK.lock().unwrap();
+        // A BUY predicting $3216 against $3200, a 50 bps move
+        let output = GuestOutput {
+            signal: Action::Buy,
+            predicted_price: 3216,
+            ..Default::default()
+        };
+
+        let args = parse(&["app", "--min-predicted-move-bps", "100"]).unwrap();
+        let record = preflight(&args, &output).unwrap().expect("the proof should be skipped");
+        assert_eq!(record.action, Action::Hold);
+        assert!(record.tx_hash.is_none());
+
+        let args = parse(&["app", "--min-predicted-move-bps", "40"]).unwrap();
+        assert!(preflight(&args, &output).unwrap().is_none());
+
+        // A long-only account never publishes a SELL, and --fail-on-sell fails before paying for
+        // a proof of one
+        let sell = GuestOutput {
+            signal: Action::Sell,
+            ..output
+        };
+        let args = parse(&["app", "--strategy", "long-only"]).unwrap();
+        assert!(preflight(&args, &sell).unwrap().is_some());
+        assert!(preflight(&parse(&["app", "--fail-on-sell"]).unwrap(), &sell).is_err());
+    }
+
+    #[test]
+    fn zero_confidence_skips_the_contract_update() {
+        let _env = ENV_LOCK.lock().unwrap();
+        let output = GuestOutput {
+            signal: Action::Buy,
+            predicted_price: 3216,
+            confidence: 0,
+            confidence_bps: 0,
+            ..Default::default()
+        };
+
+        assert_eq!(decide_action(&parse(&["app"]).unwrap(), &output), Action::Buy);
+        let args = parse(&["app", "--require-positive-confidence"]).unwrap();
+        assert_eq!(decide_action(&args, &output), Action::Hold);
+        let record = preflight(&args, &output).unwrap().expect("the update should be skipped");
+        assert_eq!(record.action, Action::Hold);
+
+        let confident = GuestOutput {
+            confidence: 1,
+            confidence_bps: 100,
+            ..output
+        };
+        assert_eq!(decide_action(&args, &confident), Action::Buy);
+    }
+
+    #[test]
+    fn deployment_file_overrides_resolution() {
+        let _env = ENV_LOCK.lock().unwrap();
+        assert!(deployment(&parse(&["app"]).unwrap()).unwrap().is_none());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("deployment.json");
+        std::fs::write(
+            &path,
+            r#"{
+                "boundless_market_address": "0x0000000000000000000000000000000000000001",
+                "set_verifier_address": "0x0000000000000000000000000000000000000002"
+            }"#,
+        )
+        .unwrap();
+        let path = path.to_str().unwrap();
+
+        let args = parse(&["app", "--deployment-file", path]).unwrap();
+        let deployment = deployment(&args).unwrap().unwrap();
+        assert_eq!(
+            deployment.boundless_market_address,
+            Address::with_last_byte(1)
+        );
+        assert_eq!(
+            parse_error(&[
+                "app",
+                "--deployment-file",
+                path,
+                "--boundless-market-address",
+                "0x0000000000000000000000000000000000000003",
+                "--set-verifier-address",
+                "0x0000000000000000000000000000000000000004",
+            ]),
+            ErrorKind::ArgumentConflict
+        );
+    }
+
+    #[tokio::test]
+    async fn strategy_id_matches_the_one_a_dry_run_commits() {
+        let dir = tempfile::tempdir().unwrap();
+        let series_file = dir.path().join("series.csv");
+        let rows: String = (1..=10)
+            .map(|day| format!("{},{},{}\n", day, 3000 + 10 * day, 1_700_000_000 + 3600 * day))
+            .collect();
+        std::fs::write(&series_file, &rows).unwrap();
+        let args = {
+            let _env = ENV_LOCK.lock().unwrap();
+            parse(&[
+                "app",
+                "--dry-run",
+                "--series-file",
+                series_file.to_str().unwrap(),
+                "--append-to-series",
+                "--decay-half-life-secs",
+                "7200",
+                "--commit-strategy-id",
+            ])
+            .unwrap()
+        };
+
+        let printed = expected_strategy_id(&resolve_args(&args).await.unwrap());
+        assert_eq!(std::fs::read_to_string(&series_file).unwrap(), rows);
+        assert_ne!(printed, expected_strategy_id(&args));
+
+        let args = &resolve_args(&args).await.unwrap();
+        let (input_bytes, _) =
+            build_guest_input(args, load_price_history(args).await.unwrap()).unwrap();
+        let session_info = executor::execute(&input_bytes).unwrap();
+        let output = GuestOutput::decode(&session_info.journal.bytes, args.decimals).unwrap();
+        assert_eq!(output.strategy_id, Some(printed));
+    }
+
+    fn series_args(series_file: &Path, dry_run: bool) -> Args {
+        let _env = ENV_LOCK.lock().unwrap();
+        let series = ["--series-file", series_file.to_str().unwrap(), "--append-to-series"];
+        let flags: &[&str] = if dry_run { &["app", "--dry-run"] } else { &["app"] };
+        parse(&[flags, &series[..]].concat()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn dry_run_leaves_the_series_file_unchanged() {
+        let dir = tempfile::tempdir().unwrap();
+        let series_file = dir.path().join("series.csv");
+        let rows: String = (1..=10)
+            .map(|day| format!("{},{},{}\n", day, 3000 + 10 * day, 1_700_000_000 + 3600 * day))
+            .collect();
+        std::fs::write(&series_file, &rows).unwrap();
+
+        run_dry_run(&series_args(&series_file, true)).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&series_file).unwrap(), rows);
+    }
+
+    #[tokio::test]
+    async fn explains_the_series_the_guest_fits() {
+        let dir = tempfile::tempdir().unwrap();
+        let series_file = dir.path().join("series.csv");
+        // An odd number of days keeps the integer means exact
+        let rows: String = (1..=11)
+            .map(|day| {
+                format!(
+                    "{},{},{}\n",
+                    day,
+                    3000 + 10 * day,
+                    1_700_000_000 + 3600 * day
+                )
+            })
+            .collect();
+        std::fs::write(&series_file, rows).unwrap();
+        // A falling CSV the series overrides, which the explanation must not fit
+        let csv = dir.path().join("history.csv");
+        let rows: String = (1..=10)
+            .map(|day| format!("{},{}\n", day, 4000 - 25 * day))
+            .collect();
+        std::fs::write(&csv, rows).unwrap();
+
+        let args = {
+            let _env = ENV_LOCK.lock().unwrap();
+            let series = series_file.to_str().unwrap();
+            parse(&[
+                "app",
+                "--dry-run",
+                "--explain",
+                "--append-to-series",
+                "--series-file",
+                series,
+                "--price-history-csv",
+                csv.to_str().unwrap(),
+            ])
+            .unwrap()
+        };
+        let args = &resolve_args(&args).await.unwrap();
+        let price_history = load_price_history(args).await.unwrap();
+        let explanation = explain(args, &price_history);
+        assert_eq!(explanation.slope, 10);
+
+        let (input_bytes, _) = build_guest_input(args, price_history).unwrap();
+        let session_info = executor::execute(&input_bytes).unwrap();
+        let output = GuestOutput::decode(&session_info.journal.bytes, 0).unwrap();
+        assert_eq!(explanation.slope, output.slope);
+    }
+
+    #[test]
+    fn explains_the_downsampled_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let csv = dir.path().join("history.csv");
+        let rows: String = (1..=50)
+            .map(|day| format!("{},{}\n", day, 3000 + 10 * day))
+            .collect();
+        std::fs::write(&csv, rows).unwrap();
+
+        let args = {
+            let _env = ENV_LOCK.lock().unwrap();
+            let csv = csv.to_str().unwrap();
+            parse(&[
+                "app",
+                "--dry-run",
+                "--explain",
+                "--downsample",
+                "5",
+                "--price-history-csv",
+                csv,
+            ])
+            .unwrap()
+        };
+        let price_history = history::load_price_history(&csv).unwrap();
+        let explanation = explain(&args, &price_history);
+
+        // The guest fits 10 points averaged over 5 days, predicting the day after the last mean
+        assert_eq!(explanation.next_day, 49);
+        let (input_bytes, _) = build_guest_input(&args, price_history).unwrap();
+        let session_info = executor::execute(&input_bytes).unwrap();
+        let output = GuestOutput::decode(&session_info.journal.bytes, 0).unwrap();
+        assert_eq!(explanation.slope, output.slope);
+        assert_eq!(explanation.predicted_price, output.predicted_price);
+    }
+
+    #[tokio::test]
+    async fn only_a_successful_run_appends_to_the_series() {
+        let dir = tempfile::tempdir().unwrap();
+        let series_file = dir.path().join("series.csv");
+        let args = &resolve_args(&series_args(&series_file, false)).await.unwrap();
+
+        let failed = async { Err(anyhow::anyhow!("prover timed out")) };
+        append_after(args, failed).await.unwrap_err();
+        assert!(!series_file.exists());
+
+        let record = RunRecord {
+            timestamp: 0,
+            action: Action::Hold,
+            confidence: 0,
+            predicted_price: 3200,
+            tx_hash: None,
+            program_url: None,
+        };
+        append_after(args, async { Ok(record) }).await.unwrap();
+        assert_eq!(
+            history::load_series(&series_file).unwrap().0,
+            [(1, args.current_price)]
+        );
+    }
+
+    #[test]
+    fn stress_scenarios_hold_the_invariants() {
+        let args = parse(&["app", "stress", "--cases", "2"]).unwrap();
+        run_stress(&args, 2, 7).unwrap();
+    }
+
+    #[tokio::test]
+    async fn batch_file_dry_run_writes_a_result_per_line() {
+        let dir = tempfile::tempdir().unwrap();
+        let batch_file = dir.path().join("batch.jsonl");
+        let output_file = dir.path().join("results.jsonl");
+        let series = |price: fn(u64) -> u64| {
+            serde_json::to_string(&(1..=20).map(|day| (day, price(day))).collect::<Vec<_>>())
+                .unwrap()
+        };
+        std::fs::write(
+            &batch_file,
+            format!(
+                "{{\"current_price\": 3100, \"history\": {}}}\n\
+                 {{\"current_price\": 3300, \"history\": {}, \"sell_threshold_bps\": 100}}\n\
+                 {{\"current_price\": 3200, \"history\": {}, \"models\": [\"linreg\"]}}\n",
+                series(|day| 3000 + 10 * day),
+                series(|day| 3300 - 10 * day),
+                series(|_| 3200),
+            ),
+        )
+        .unwrap();
+
+        let args = {
+            let _env = ENV_LOCK.lock().unwrap();
+            parse(&[
+                "app",
+                "--dry-run",
+                "--batch-file",
+                batch_file.to_str().unwrap(),
+                "--output-file",
+                output_file.to_str().unwrap(),
+            ])
+            .unwrap()
+        };
+        run_batch(&args, &batch_file, None).await.unwrap();
+
+        let actions: Vec<String> = std::fs::read_to_string(&output_file)
+            .unwrap()
+            .lines()
+            .map(|line| {
+                let record: serde_json::Value = serde_json::from_str(line).unwrap();
+                record["action"].as_str().unwrap().to_string()
+            })
+            .collect();
+        assert_eq!(actions, ["BUY", "SELL", "HOLD"]);
+    }
+
+    #[tokio::test]
+    async fn batch_file_rejects_flags_that_override_its_lines() {
+        let batch = [
+            "app",
+            "--batch-file",
+            "batch.jsonl",
+            "--output-file",
+            "out.jsonl",
+        ];
+        let args = {
+            let _env = ENV_LOCK.lock().unwrap();
+            let series = ["--series-file", "s.csv", "--append-to-series"];
+            assert_eq!(
+                parse_error(&[&batch[..], &series[..]].concat()),
+                ErrorKind::ArgumentConflict
+            );
+            parse(&[&batch[..], &["--threshold-source", "onchain"]].concat()).unwrap()
+        };
+        let err = run_batch(&args, Path::new("batch.jsonl"), None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("--threshold-source onchain"));
+    }
 }
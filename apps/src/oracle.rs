@@ -0,0 +1,140 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloy::{
+    primitives::{Address, Signature, B256, U256},
+    signers::{local::PrivateKeySigner, SignerSync},
+    sol_types::{eip712_domain, Eip712Domain, SolStruct},
+};
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::signal::GuestOutput;
+
+alloy::sol! {
+    /// A trading signal attested by the agent, signed as EIP-712 typed data.
+    #[derive(Debug, PartialEq, Eq)]
+    struct SignedSignal {
+        uint8 action;
+        uint256 confidence;
+        uint256 predictedPrice;
+        bytes32 inputHash;
+        bytes32 imageId;
+        uint256 timestamp;
+    }
+}
+
+/// EIP-712 domain of the signed signals, which no contract verifies on-chain.
+pub const DOMAIN: Eip712Domain = eip712_domain! {
+    name: "TradingSignalAgent",
+    version: "1",
+};
+
+impl SignedSignal {
+    /// The signal committed by the guest of image `image_id`, attested at `timestamp`.
+    pub fn new(output: &GuestOutput, image_id: B256, timestamp: u64) -> Self {
+        Self {
+            action: output.signal as u8,
+            confidence: U256::from(output.confidence),
+            predictedPrice: output.predicted_amount,
+            inputHash: output.input_hash,
+            imageId: image_id,
+            timestamp: U256::from(timestamp),
+        }
+    }
+}
+
+/// A [SignedSignal] with its signature, as printed by `--emit-eip712`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedMessage {
+    /// Address of the private key that signed the message.
+    pub signer: Address,
+    /// 0 = SELL, 1 = BUY, 2 = HOLD, as committed by the guest.
+    pub action: u8,
+    /// Confidence percentage (0-100) committed by the guest.
+    pub confidence: U256,
+    /// Predicted price in base units, as the contract would store it.
+    pub predicted_price: U256,
+    /// keccak256 of the ABI-encoded price history the guest received.
+    pub input_hash: B256,
+    /// Image ID of the guest that computed the signal.
+    pub image_id: B256,
+    /// Unix time at which the agent signed the message.
+    pub timestamp: U256,
+    /// 65-byte `r || s || v` signature of the EIP-712 hash of the message under [DOMAIN].
+    pub signature: Signature,
+}
+
+/// Signs `message` with `signer` under [DOMAIN].
+pub fn sign(signer: &PrivateKeySigner, message: SignedSignal) -> Result<SignedMessage> {
+    let signature = signer
+        .sign_hash_sync(&message.eip712_signing_hash(&DOMAIN))
+        .context("failed to sign the EIP-712 message")?;
+    Ok(SignedMessage {
+        signer: signer.address(),
+        action: message.action,
+        confidence: message.confidence,
+        predicted_price: message.predictedPrice,
+        input_hash: message.inputHash,
+        image_id: message.imageId,
+        timestamp: message.timestamp,
+        signature,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signal::Action;
+
+    /// Recovers the address that signed the message, as an off-chain consumer would.
+    fn recover_signer(signed: &SignedMessage) -> Address {
+        let message = SignedSignal {
+            action: signed.action,
+            confidence: signed.confidence,
+            predictedPrice: signed.predicted_price,
+            inputHash: signed.input_hash,
+            imageId: signed.image_id,
+            timestamp: signed.timestamp,
+        };
+        signed
+            .signature
+            .recover_address_from_prehash(&message.eip712_signing_hash(&DOMAIN))
+            .unwrap()
+    }
+
+    #[test]
+    fn signed_message_recovers_to_signer() {
+        let signer = PrivateKeySigner::random();
+        let output = GuestOutput {
+            signal: Action::Buy,
+            confidence: 85,
+            predicted_amount: U256::from(3750u64),
+            input_hash: B256::repeat_byte(0x61),
+            ..Default::default()
+        };
+        let message = SignedSignal::new(&output, B256::repeat_byte(0x11), 1_700_000_000);
+
+        let signed = sign(&signer, message).unwrap();
+        assert_eq!(signed.signer, signer.address());
+        assert_eq!(recover_signer(&signed), signer.address());
+
+        let tampered = SignedMessage {
+            action: 0,
+            ..signed
+        };
+        assert_ne!(recover_signer(&tampered), signer.address());
+    }
+}
@@ -0,0 +1,211 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    fs::OpenOptions,
+    io::Write,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use alloy::primitives::B256;
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::Serialize;
+use url::Url;
+
+use crate::signal::{Action, GuestOutput, RawPrediction};
+
+/// Where the result of each run is reported.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Log messages only.
+    #[default]
+    Text,
+    /// Also append a row per run to `--output-file`, for spreadsheet import.
+    Csv,
+    /// Also append a JSON object per line to `--output-file`.
+    Json,
+}
+
+const CSV_HEADER: &str = "timestamp,signal,confidence,predicted_wei,tx_hash";
+
+/// Outcome of a single run.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct RunRecord {
+    /// Unix time at which the run completed.
+    pub timestamp: u64,
+    /// Action taken by the app.
+    pub action: Action,
+    /// Confidence percentage (0-100) committed by the guest.
+    pub confidence: u64,
+    /// Predicted price committed by the guest.
+    pub predicted_price: u64,
+    /// Hash of the setSignal transaction, if one was sent.
+    pub tx_hash: Option<B256>,
+    /// URL the guest program was fetched from by provers, including one uploaded during the run.
+    pub program_url: Option<Url>,
+}
+
+impl RunRecord {
+    /// Records the action taken on the guest's output at the current time.
+    pub fn new(action: Action, output: &GuestOutput, tx_hash: Option<B256>) -> Self {
+        Self {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs()),
+            action,
+            confidence: output.confidence,
+            predicted_price: output.predicted_price,
+            tx_hash,
+            program_url: None,
+        }
+    }
+
+    /// Records a raw prediction, which carries no action and is logged as HOLD.
+    pub fn from_prediction(prediction: &RawPrediction) -> Self {
+        Self {
+            confidence: prediction.confidence,
+            predicted_price: prediction.predicted_price,
+            ..Self::new(Action::Hold, &GuestOutput::default(), None)
+        }
+    }
+
+    /// Sets the program URL used for the request.
+    pub fn with_program_url(self, program_url: Url) -> Self {
+        Self {
+            program_url: Some(program_url),
+            ..self
+        }
+    }
+
+    fn to_csv_row(&self) -> String {
+        format!(
+            "{},{},{},{},{}",
+            self.timestamp,
+            self.action,
+            self.confidence,
+            self.predicted_price,
+            self.tx_hash
+                .map(|hash| hash.to_string())
+                .unwrap_or_default()
+        )
+    }
+}
+
+/// Appends the record to a CSV file, writing the header first if the file is new or empty.
+pub fn append_csv(path: &Path, record: &RunRecord) -> Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("failed to open {}", path.display()))?;
+    let is_empty = file
+        .metadata()
+        .with_context(|| format!("failed to read metadata of {}", path.display()))?
+        .len()
+        == 0;
+    let mut contents = String::new();
+    if is_empty {
+        contents.push_str(CSV_HEADER);
+        contents.push('\n');
+    }
+    contents.push_str(&record.to_csv_row());
+    contents.push('\n');
+    file.write_all(contents.as_bytes())
+        .with_context(|| format!("failed to append to {}", path.display()))
+}
+
+/// Appends the record to a file as a single line of JSON.
+pub fn append_json(path: &Path, record: &RunRecord) -> Result<()> {
+    let mut line = serde_json::to_string(record).context("failed to serialize run record")?;
+    line.push('\n');
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("failed to open {}", path.display()))?
+        .write_all(line.as_bytes())
+        .with_context(|| format!("failed to append to {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_is_written_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("results.csv");
+        let record = RunRecord {
+            timestamp: 1_700_000_000,
+            action: Action::Buy,
+            confidence: 85,
+            predicted_price: 3750,
+            tx_hash: Some(B256::repeat_byte(0xab)),
+            program_url: None,
+        };
+
+        append_csv(&path, &record).unwrap();
+        append_csv(
+            &path,
+            &RunRecord {
+                action: Action::Hold,
+                tx_hash: None,
+                ..record
+            },
+        )
+        .unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], CSV_HEADER);
+        assert_eq!(
+            lines[1],
+            format!("1700000000,BUY,85,3750,0x{}", "ab".repeat(32))
+        );
+        assert_eq!(lines[2], "1700000000,HOLD,85,3750,");
+        assert_eq!(contents.matches(CSV_HEADER).count(), 1);
+    }
+
+    #[test]
+    fn json_includes_uploaded_program_url() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("results.jsonl");
+        let record = RunRecord {
+            timestamp: 1_700_000_000,
+            action: Action::Sell,
+            confidence: 92,
+            predicted_price: 3400,
+            tx_hash: None,
+            program_url: None,
+        }
+        .with_program_url(
+            "https://storage.example.com/trading-signal.elf"
+                .parse()
+                .unwrap(),
+        );
+
+        append_json(&path, &record).unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let value: serde_json::Value = serde_json::from_str(contents.trim_end()).unwrap();
+        assert_eq!(value["action"], "SELL");
+        assert_eq!(
+            value["program_url"],
+            "https://storage.example.com/trading-signal.elf"
+        );
+    }
+}
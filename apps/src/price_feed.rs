@@ -0,0 +1,124 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use alloy::{
+    primitives::{Address, U256},
+    providers::Provider,
+};
+use anyhow::{bail, Context, Result};
+
+use crate::price_feed::aggregator::AggregatorV3Interface;
+
+mod aggregator {
+    alloy::sol! {
+        #[sol(rpc)]
+        interface AggregatorV3Interface {
+            function decimals() external view returns (uint8);
+
+            function latestRoundData()
+                external
+                view
+                returns (
+                    uint80 roundId,
+                    int256 answer,
+                    uint256 startedAt,
+                    uint256 updatedAt,
+                    uint80 answeredInRound
+                );
+        }
+    }
+}
+
+/// Latest answer of a price feed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeedPrice {
+    /// USD price per ETH, in whole USD.
+    pub price: u64,
+    /// Unix timestamp of the round that produced the price.
+    pub updated_at: u64,
+}
+
+/// Reads the latest round of a Chainlink ETH/USD aggregator.
+pub async fn read_latest_price<P: Provider>(provider: P, address: Address) -> Result<FeedPrice> {
+    let feed = AggregatorV3Interface::new(address, provider);
+    let decimals = feed
+        .decimals()
+        .call()
+        .await
+        .with_context(|| format!("failed to read decimals of price feed {}", address))?;
+    let round = feed
+        .latestRoundData()
+        .call()
+        .await
+        .with_context(|| format!("failed to read latest round of price feed {}", address))?;
+    if !round.answer.is_positive() {
+        bail!("price feed {} answered {}", address, round.answer);
+    }
+    let price = round.answer.into_raw() / U256::from(10u64).pow(U256::from(decimals));
+    Ok(FeedPrice {
+        price: price
+            .try_into()
+            .context("price feed answer does not fit in a u64")?,
+        updated_at: round
+            .updatedAt
+            .try_into()
+            .context("price feed timestamp does not fit in a u64")?,
+    })
+}
+
+/// Rejects a price last updated more than `max_age_secs` before `now`, both Unix timestamps.
+pub fn check_price_age(updated_at: u64, now: u64, max_age_secs: Option<u64>) -> Result<()> {
+    let Some(max_age_secs) = max_age_secs else {
+        return Ok(());
+    };
+    let age = now.saturating_sub(updated_at);
+    if age > max_age_secs {
+        bail!(
+            "price was last updated {}s ago, which exceeds --max-price-age-secs {}",
+            age,
+            max_age_secs
+        );
+    }
+    Ok(())
+}
+
+/// Current Unix timestamp.
+pub fn unix_now() -> Result<u64> {
+    Ok(SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .context("system clock is before the Unix epoch")?
+        .as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_stale_prices() {
+        let updated_at = 1_700_000_000;
+        check_price_age(updated_at, updated_at + 60, Some(60)).unwrap();
+        check_price_age(updated_at, updated_at + 61, None).unwrap();
+        // A round timestamped ahead of a lagging local clock is not stale
+        check_price_age(updated_at, updated_at - 5, Some(60)).unwrap();
+
+        let err = check_price_age(updated_at, updated_at + 3_600, Some(60)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "price was last updated 3600s ago, which exceeds --max-price-age-secs 60"
+        );
+    }
+}
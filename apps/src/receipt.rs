@@ -0,0 +1,261 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::Path;
+
+use alloy::primitives::{Bytes, B256};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::signal::{Action, GuestOutput};
+
+/// Journal and seal of a fulfilled request, saved so the contract update can be retried without
+/// re-proving.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedReceipt {
+    pub journal: Bytes,
+    pub seal: Bytes,
+}
+
+impl SavedReceipt {
+    /// Writes the receipt as JSON.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let contents = serde_json::to_string_pretty(self).context("failed to serialize receipt")?;
+        std::fs::write(path, contents)
+            .with_context(|| format!("failed to write receipt to {}", path.display()))
+    }
+
+    /// Reads a receipt written by [SavedReceipt::save].
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read receipt from {}", path.display()))?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("failed to parse receipt {}", path.display()))
+    }
+}
+
+/// Destination of a proven signal.
+pub trait SignalSink {
+    /// Checks that submitting the journal and seal would succeed, without submitting them.
+    async fn simulate_signal(&self, journal: &Bytes, seal: &Bytes) -> Result<()>;
+
+    /// Submits the journal and seal, returning the transaction hash.
+    async fn set_signal(&self, journal: Bytes, seal: Bytes) -> Result<B256>;
+}
+
+/// Submits the journal and seal, first simulating the submission if `simulate` is set so that a
+/// revert aborts before any gas is spent.
+#[tracing::instrument(name = "contract_update", skip_all)]
+pub async fn submit(
+    sink: &impl SignalSink,
+    journal: Bytes,
+    seal: Bytes,
+    simulate: bool,
+) -> Result<B256> {
+    if simulate {
+        sink.simulate_signal(&journal, &seal).await?;
+        tracing::info!("Simulated setSignal succeeded");
+    }
+    sink.set_signal(journal, seal).await
+}
+
+/// Fails on a SELL signal, for accounts that only ever act on BUY and treat SELL as something to
+/// investigate rather than publish.
+pub fn reject_sell(output: &GuestOutput) -> Result<()> {
+    if output.signal == Action::Sell {
+        anyhow::bail!(
+            "the guest signals SELL and --fail-on-sell is set, not updating the contract"
+        );
+    }
+    Ok(())
+}
+
+/// Re-submits a saved receipt after checking that its journal decodes to a signal the contract
+/// accepts, and that it is not SELL if `fail_on_sell` is set.
+pub async fn replay(
+    path: &Path,
+    sink: &impl SignalSink,
+    decimals: u8,
+    simulate: bool,
+    fail_on_sell: bool,
+) -> Result<(GuestOutput, B256)> {
+    let receipt = SavedReceipt::load(path)?;
+    let output = GuestOutput::decode(&receipt.journal, decimals)?;
+    output.validate()?;
+    if fail_on_sell {
+        reject_sell(&output)?;
+    }
+    let tx_hash = submit(sink, receipt.journal, receipt.seal, simulate).await?;
+    Ok((output, tx_hash))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use alloy::primitives::U256;
+    use trading_journal::{Journal, JournalV2};
+
+    use super::*;
+
+    #[derive(Default)]
+    struct MockSink {
+        /// Whether simulated submissions revert.
+        reverts: bool,
+        simulated: Mutex<usize>,
+        submitted: Mutex<Vec<(Bytes, Bytes)>>,
+    }
+
+    impl SignalSink for MockSink {
+        async fn simulate_signal(&self, _journal: &Bytes, _seal: &Bytes) -> Result<()> {
+            *self.simulated.lock().unwrap() += 1;
+            if self.reverts {
+                anyhow::bail!("setSignal simulation reverted: invalid proof");
+            }
+            Ok(())
+        }
+
+        async fn set_signal(&self, journal: Bytes, seal: Bytes) -> Result<B256> {
+            self.submitted.lock().unwrap().push((journal, seal));
+            Ok(B256::repeat_byte(0x11))
+        }
+    }
+
+    fn journal(confidence: u64) -> Bytes {
+        journal_with_signal(Action::Buy, confidence)
+    }
+
+    fn journal_with_signal(signal: Action, confidence: u64) -> Bytes {
+        Journal::V2(JournalV2 {
+            signal,
+            confidence,
+            predicted_price: U256::from(3750u64),
+            input_hash: B256::repeat_byte(0x22),
+            models: 1,
+            slope: 15,
+            agreement: true,
+            ..Default::default()
+        })
+        .encode()
+        .into()
+    }
+
+    #[tokio::test]
+    async fn replay_submits_saved_receipt() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("receipt.json");
+        let receipt = SavedReceipt {
+            journal: journal(85),
+            seal: Bytes::from_static(&[0xde, 0xad, 0xbe, 0xef]),
+        };
+        receipt.save(&path).unwrap();
+
+        let sink = MockSink::default();
+        let (output, tx_hash) = replay(&path, &sink, 0, false, false).await.unwrap();
+
+        assert_eq!(tx_hash, B256::repeat_byte(0x11));
+        assert_eq!(output.confidence, 85);
+        assert_eq!(output.predicted_price, 3750);
+        assert_eq!(
+            *sink.submitted.lock().unwrap(),
+            vec![(receipt.journal, receipt.seal)]
+        );
+    }
+
+    #[tokio::test]
+    async fn replay_rejects_invalid_signal() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("receipt.json");
+        SavedReceipt {
+            journal: journal(101),
+            seal: Bytes::new(),
+        }
+        .save(&path)
+        .unwrap();
+
+        let sink = MockSink::default();
+        let err = replay(&path, &sink, 0, false, false).await.unwrap_err();
+        assert!(
+            err.to_string().contains("confidence"),
+            "unexpected error: {err}"
+        );
+        assert!(sink.submitted.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn fail_on_sell_rejects_sell_before_send() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("receipt.json");
+        SavedReceipt {
+            journal: journal_with_signal(Action::Sell, 85),
+            seal: Bytes::new(),
+        }
+        .save(&path)
+        .unwrap();
+
+        let sink = MockSink::default();
+        let err = replay(&path, &sink, 0, false, true).await.unwrap_err();
+        assert!(err.to_string().contains("SELL"), "unexpected error: {err}");
+        assert!(sink.submitted.lock().unwrap().is_empty());
+
+        // Without the flag a SELL goes out like any other signal
+        replay(&path, &sink, 0, false, false).await.unwrap();
+        assert_eq!(sink.submitted.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn simulated_revert_aborts_before_send() {
+        let sink = MockSink {
+            reverts: true,
+            ..Default::default()
+        };
+        let err = submit(&sink, journal(85), Bytes::new(), true)
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("reverted"),
+            "unexpected error: {err}"
+        );
+        assert_eq!(*sink.simulated.lock().unwrap(), 1);
+        assert!(sink.submitted.lock().unwrap().is_empty());
+
+        let sink = MockSink::default();
+        submit(&sink, journal(85), Bytes::new(), true)
+            .await
+            .unwrap();
+        assert_eq!(*sink.simulated.lock().unwrap(), 1);
+        assert_eq!(sink.submitted.lock().unwrap().len(), 1);
+
+        // Without the check the submission goes straight out
+        let sink = MockSink {
+            reverts: true,
+            ..Default::default()
+        };
+        submit(&sink, journal(85), Bytes::new(), false)
+            .await
+            .unwrap();
+        assert_eq!(*sink.simulated.lock().unwrap(), 0);
+        assert_eq!(sink.submitted.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn submit_runs_in_contract_update_span() {
+        let (_guard, spans) = crate::telemetry::capture_span_names();
+
+        submit(&MockSink::default(), journal(85), Bytes::new(), true)
+            .await
+            .unwrap();
+        assert_eq!(*spans.lock().unwrap(), ["contract_update"]);
+    }
+}
@@ -0,0 +1,117 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use alloy::providers::{Provider, ProviderBuilder};
+use anyhow::{bail, Context, Result};
+use url::Url;
+
+/// Time allowed for each RPC endpoint to answer the reachability probe.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Returns the first of `urls` that answers `eth_chainId`, trying them in order.
+///
+/// The Boundless client is built on a single endpoint, so failover happens once, when the client
+/// is built; an endpoint that fails later is not replaced.
+pub async fn first_reachable(urls: &[Url]) -> Result<Url> {
+    for url in urls {
+        match probe(url).await {
+            Ok(chain_id) => {
+                tracing::info!("Using RPC endpoint {} (chain ID {})", url, chain_id);
+                return Ok(url.clone());
+            }
+            Err(e) => tracing::warn!("RPC endpoint {} is unreachable: {:?}", url, e),
+        }
+    }
+    bail!("none of the {} RPC URLs is reachable", urls.len())
+}
+
+/// Builds a provider without a signer on the first reachable of `urls`, for contract reads.
+pub async fn read_only_provider(urls: &[Url]) -> Result<impl Provider> {
+    if urls.is_empty() {
+        bail!("--rpc-url is required to read from a contract");
+    }
+    Ok(ProviderBuilder::new().connect_http(first_reachable(urls).await?))
+}
+
+async fn probe(url: &Url) -> Result<u64> {
+    let provider = ProviderBuilder::new().connect_http(url.clone());
+    tokio::time::timeout(PROBE_TIMEOUT, provider.get_chain_id())
+        .await
+        .context("timed out")?
+        .context("failed to query chain ID")
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    use super::*;
+
+    /// Answers a single JSON-RPC request with chain ID 1.
+    async fn serve_chain_id(listener: TcpListener) {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        let mut buf = [0u8; 1024];
+        let body = loop {
+            let n = stream.read(&mut buf).await.unwrap();
+            request.extend_from_slice(&buf[..n]);
+            let text = String::from_utf8_lossy(&request);
+            if let Some(header_end) = text.find("\r\n\r\n") {
+                if let Ok(body) =
+                    serde_json::from_slice::<serde_json::Value>(&request[header_end + 4..])
+                {
+                    break body;
+                }
+            }
+        };
+        let response =
+            serde_json::json!({ "jsonrpc": "2.0", "id": body["id"], "result": "0x1" }).to_string();
+        stream
+            .write_all(
+                format!(
+                    "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{}",
+                    response.len(),
+                    response
+                )
+                .as_bytes(),
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn falls_over_to_next_reachable_url() {
+        // Nothing listens on a port freed by dropping its listener
+        let closed = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let unreachable = Url::parse(&format!("http://{}", closed.local_addr().unwrap())).unwrap();
+        drop(closed);
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let reachable = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+        tokio::spawn(serve_chain_id(listener));
+
+        let url = first_reachable(&[unreachable.clone(), reachable.clone()])
+            .await
+            .unwrap();
+        assert_eq!(url, reachable);
+
+        let err = first_reachable(&[unreachable]).await.unwrap_err();
+        assert_eq!(err.to_string(), "none of the 1 RPC URLs is reachable");
+    }
+}
@@ -0,0 +1,754 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::VecDeque;
+
+use alloy::{
+    primitives::{B256, U256},
+    sol_types::SolValue,
+};
+use anyhow::{bail, ensure, Context, Result};
+use clap::ValueEnum;
+use serde::Deserialize;
+use trading_journal::{
+    format_wei, Journal, JournalV10, JournalV11, JournalV12, JournalV2, JournalV3, JournalV4,
+    JournalV5, JournalV6, JournalV7, JournalV8, JournalV9, Reason,
+};
+pub use trading_journal::{Action, IndicatorPanel};
+
+/// Values committed to the journal by the trading signal guest.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GuestOutput {
+    /// Journal layout version the guest committed.
+    pub version: u8,
+    /// Action the guest signals.
+    pub signal: Action,
+    /// Confidence percentage (0-100).
+    pub confidence: u64,
+    /// Confidence in basis points (0-10000); `confidence * 100` before journal version 5 or
+    /// unless the guest was asked for basis points.
+    pub confidence_bps: u64,
+    /// Confidence (0-100) that the price moves up, 0 before journal version 3.
+    pub up_confidence: u64,
+    /// Confidence (0-100) that the price moves down, 0 before journal version 3.
+    pub down_confidence: u64,
+    /// Predicted USD price per ETH.
+    pub predicted_price: u64,
+    /// Predicted price in base units, `predicted_price * 10^decimals`, as stored by the contract.
+    pub predicted_amount: U256,
+    /// keccak256 of the ABI-encoded price history the guest received.
+    pub input_hash: B256,
+    /// Number of price points dropped by outlier trimming.
+    pub trimmed_points: u64,
+    /// Number of prices clamped towards the median before fitting.
+    pub clamped_points: u64,
+    /// Number of price points the models were fitted to, 0 before journal version 4.
+    pub data_points: u64,
+    /// Whether the regression slope points the same way as the last day-over-day change.
+    pub agreement: bool,
+    /// Bitmask of the models the guest ran.
+    pub models: u64,
+    /// Regression slope in USD per day after blending in the prior, 0 if the regression did not
+    /// run.
+    pub slope: i64,
+    /// Indicators committed in report mode, all zero otherwise.
+    pub panel: IndicatorPanel,
+    /// Why the guest committed its signal, [Reason::None] before journal version 6.
+    pub reason: Reason,
+    /// Predicted return over the current price in basis points, 0 before journal version 7 or
+    /// unless the guest was asked for it.
+    pub predicted_return_bps: i64,
+    /// Hash of the strategy parameters the guest received, zero before journal version 8.
+    pub params_hash: B256,
+    /// Last USD price of the input series, the baseline of the prediction, 0 before journal
+    /// version 9.
+    pub last_price: u64,
+    /// Regression residual in USD of each price point, empty before journal version 11.
+    pub residuals: Vec<i64>,
+    /// Each horizon in days with the signal predicted for it, empty before journal version 12.
+    pub horizons: Vec<(u64, Action)>,
+    /// Strategy ID of the image and parameters, committed from journal version 13 on request.
+    pub strategy_id: Option<B256>,
+}
+
+/// Journal committed by the guest in raw prediction mode, abi.encode(uint256, uint256).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RawPrediction {
+    /// Predicted USD price per ETH.
+    pub predicted_price: u64,
+    /// Confidence percentage (0-100).
+    pub confidence: u64,
+    /// Confidence in basis points (0-10000), only finer than `confidence` if the guest was asked
+    /// for basis points.
+    pub confidence_bps: u64,
+}
+
+impl RawPrediction {
+    /// Decodes the two-word raw prediction journal, whose price is in base units with `decimals`
+    /// decimals and whose confidence is in basis points if `bps_confidence` is set.
+    pub fn decode(journal: &[u8], decimals: u8, bps_confidence: bool) -> Result<Self> {
+        let (predicted_amount, confidence) = <(U256, U256)>::abi_decode(journal)
+            .context("failed to decode journal as a raw prediction")?;
+        let confidence = to_u64(confidence, "confidence")?;
+        let confidence_bps = if bps_confidence {
+            confidence
+        } else {
+            confidence.saturating_mul(100)
+        };
+        Ok(Self {
+            predicted_price: to_u64(predicted_amount / unit_scale(decimals), "predicted price")?,
+            confidence: confidence_bps / 100,
+            confidence_bps,
+        })
+    }
+}
+
+/// Logs the indicator panel the guest commits in report mode.
+pub fn log_panel(panel: &IndicatorPanel) {
+    tracing::info!(
+        "Indicators - Regression: ${}, RSI: {}, MACD histogram: {:.6}, SMA fast/slow: ${}/${}",
+        panel.regression_price,
+        panel.rsi,
+        panel.macd_histogram as f64 / 1e6,
+        panel.sma_fast,
+        panel.sma_slow
+    );
+}
+
+impl GuestOutput {
+    /// Decodes a journal in any layout of the `trading_journal` crate, with the predicted price in
+    /// base units with `decimals` decimals. Fields a compact layout leaves out are zero.
+    pub fn decode(journal: &[u8], decimals: u8) -> Result<Self> {
+        Self::decode_with(journal, decimals, false)
+    }
+
+    /// Decodes a journal like [GuestOutput::decode], but saturates words too large for their field
+    /// as [Journal::decode_lenient] does, rather than failing.
+    pub fn decode_lenient(journal: &[u8], decimals: u8) -> Result<Self> {
+        Self::decode_with(journal, decimals, true)
+    }
+
+    fn decode_with(journal: &[u8], decimals: u8, lenient: bool) -> Result<Self> {
+        let journal = if lenient {
+            Journal::decode_lenient(journal)
+        } else {
+            Journal::decode(journal)
+        }
+        .context("failed to decode journal as a trading signal")?;
+        let version = journal.version();
+        let output = match journal {
+            Journal::V1(journal) => Self {
+                signal: journal.signal,
+                confidence: journal.confidence,
+                confidence_bps: journal.confidence.saturating_mul(100),
+                predicted_amount: journal.predicted_price,
+                input_hash: journal.input_hash,
+                ..Default::default()
+            },
+            Journal::Legacy(journal) | Journal::V2(journal) => Self::from_v2(journal),
+            Journal::V3(journal) => Self::from_v3(journal),
+            Journal::V4(journal) => Self::from_v4(journal),
+            Journal::V5(journal) => Self::from_v5(journal),
+            Journal::V6(journal) => Self::from_v6(journal),
+            Journal::V7(journal) => Self::from_v7(journal),
+            Journal::V8(journal) => Self::from_v8(journal),
+            Journal::V9(journal) => Self::from_v9(journal),
+            Journal::V10(journal) => Self::from_v10(journal, decimals)?,
+            Journal::V11(journal) => Self::from_v11(journal, decimals)?,
+            Journal::V12(journal) => Self::from_v12(journal, decimals)?,
+            Journal::V13(journal) => Self {
+                strategy_id: Some(journal.strategy_id),
+                ..Self::from_v12(journal.base, decimals)?
+            },
+        };
+        Ok(Self {
+            version,
+            predicted_price: to_u64(
+                output.predicted_amount / unit_scale(decimals),
+                "predicted price",
+            )?,
+            ..output
+        })
+    }
+
+    fn from_v12(journal: JournalV12, decimals: u8) -> Result<Self> {
+        Ok(Self {
+            horizons: journal.horizons,
+            ..Self::from_v11(journal.base, decimals)?
+        })
+    }
+
+    fn from_v11(journal: JournalV11, decimals: u8) -> Result<Self> {
+        Ok(Self {
+            residuals: journal.residuals,
+            ..Self::from_v10(journal.base, decimals)?
+        })
+    }
+
+    fn from_v10(journal: JournalV10, decimals: u8) -> Result<Self> {
+        ensure!(
+            journal.decimals == decimals as u64,
+            "journal commits the predicted price with {} decimals, not {}",
+            journal.decimals,
+            decimals
+        );
+        Ok(Self::from_v9(journal.base))
+    }
+
+    fn from_v9(journal: JournalV9) -> Self {
+        Self {
+            last_price: journal.last_price,
+            ..Self::from_v8(journal.base)
+        }
+    }
+
+    fn from_v8(journal: JournalV8) -> Self {
+        Self {
+            params_hash: journal.params_hash,
+            ..Self::from_v7(journal.base)
+        }
+    }
+
+    fn from_v7(journal: JournalV7) -> Self {
+        Self {
+            predicted_return_bps: journal.predicted_return_bps,
+            ..Self::from_v6(journal.base)
+        }
+    }
+
+    fn from_v6(journal: JournalV6) -> Self {
+        Self {
+            reason: journal.reason,
+            ..Self::from_v5(journal.base)
+        }
+    }
+
+    fn from_v5(journal: JournalV5) -> Self {
+        Self {
+            confidence_bps: journal.confidence_bps,
+            ..Self::from_v4(journal.base)
+        }
+    }
+
+    fn from_v4(journal: JournalV4) -> Self {
+        Self {
+            data_points: journal.data_points,
+            ..Self::from_v3(journal.base)
+        }
+    }
+
+    fn from_v3(journal: JournalV3) -> Self {
+        Self {
+            up_confidence: journal.up_confidence,
+            down_confidence: journal.down_confidence,
+            ..Self::from_v2(journal.base)
+        }
+    }
+
+    fn from_v2(journal: JournalV2) -> Self {
+        Self {
+            signal: journal.signal,
+            confidence: journal.confidence,
+            confidence_bps: journal.confidence.saturating_mul(100),
+            predicted_amount: journal.predicted_price,
+            input_hash: journal.input_hash,
+            trimmed_points: journal.trimmed_points,
+            clamped_points: journal.clamped_points,
+            agreement: journal.agreement,
+            models: journal.models,
+            slope: journal.slope,
+            panel: journal.panel,
+            ..Default::default()
+        }
+    }
+
+    /// Checks the bounds the TradingSignal contract enforces before a transaction is sent.
+    pub fn validate(&self) -> Result<()> {
+        if self.confidence > 100 {
+            bail!("invalid confidence {}: must be 0-100", self.confidence);
+        }
+        if self.predicted_amount.is_zero() {
+            bail!("invalid predicted price: must be > 0");
+        }
+        Ok(())
+    }
+}
+
+/// Returns true if `latest` is a BUY following a SELL or a SELL following a BUY.
+pub fn reverses(previous: Action, latest: Action) -> bool {
+    matches!(
+        (previous, latest),
+        (Action::Buy, Action::Sell) | (Action::Sell, Action::Buy)
+    )
+}
+
+/// Prediction models the guest can run, combined by ensemble voting when more than one is
+/// selected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Model {
+    /// Linear regression over the price history, extrapolated one day.
+    Linreg,
+    /// Crossover of a 5-point and a 20-point simple moving average.
+    SmaCrossover,
+    /// Rank of the current price among the last `--percentile-window` prices: BUY in the bottom
+    /// fifth of the range, SELL in the top fifth.
+    PercentileRank,
+    /// Holt's linear (double exponential) smoothing, forecast one step ahead, with
+    /// `--holt-alpha` and `--holt-beta`.
+    Holt,
+    /// Theil–Sen regression, the median of the slopes between every pair of points, which a few
+    /// bad points barely move, extrapolated one day.
+    TheilSen,
+}
+
+impl Model {
+    /// Returns the bitmask selecting the given models in the guest input.
+    pub fn mask(models: &[Model]) -> u64 {
+        models.iter().fold(0, |mask, model| {
+            mask | match model {
+                Model::Linreg => 1 << 0,
+                Model::SmaCrossover => 1 << 1,
+                Model::PercentileRank => 1 << 2,
+                Model::Holt => 1 << 3,
+                Model::TheilSen => 1 << 4,
+            }
+        })
+    }
+}
+
+/// How the guest combines the confidences of the models in an ensemble.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ConfidenceAggregation {
+    /// The average of the confidences.
+    #[default]
+    Mean,
+    /// The lowest confidence, for conservative users.
+    Min,
+    /// The product of the confidences, taken as probabilities.
+    Product,
+}
+
+impl ConfidenceAggregation {
+    /// Returns the code selecting the aggregation in the guest input.
+    pub fn code(self) -> u64 {
+        match self {
+            ConfidenceAggregation::Mean => 0,
+            ConfidenceAggregation::Min => 1,
+            ConfidenceAggregation::Product => 2,
+        }
+    }
+}
+
+/// How the guest rounds the predicted price, the mean of its models, to base units.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Rounding {
+    /// Towards zero.
+    #[default]
+    Trunc,
+    /// To the nearest unit, halves up.
+    Nearest,
+    /// Up, for a conservative target.
+    Up,
+}
+
+impl Rounding {
+    /// Returns the code selecting the rounding mode in the guest input.
+    pub fn code(self) -> u64 {
+        match self {
+            Rounding::Trunc => 0,
+            Rounding::Nearest => 1,
+            Rounding::Up => 2,
+        }
+    }
+}
+
+/// Position constraints of the account acting on the signal.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Strategy {
+    /// The account cannot short, so SELL signals become HOLD.
+    LongOnly,
+    /// Both BUY and SELL signals are acted on.
+    #[default]
+    LongShort,
+}
+
+impl Strategy {
+    /// Applies the strategy constraint to a decoded action.
+    pub fn apply(self, action: Action) -> Action {
+        match (self, action) {
+            (Strategy::LongOnly, Action::Sell) => Action::Hold,
+            (_, action) => action,
+        }
+    }
+}
+
+/// Converts a journal word to u64, failing rather than dropping its upper bits when it does not fit.
+fn to_u64(value: U256, name: &str) -> Result<u64> {
+    u64::try_from(value).with_context(|| format!("{name} {value} does not fit in 64 bits"))
+}
+
+/// Explains a HOLD committed for `reason`, in the words of the log.
+pub fn describe_reason(reason: Reason) -> &'static str {
+    match reason {
+        Reason::None => "the models disagree or the move is within the thresholds",
+        Reason::DeadBand => "the predicted move is within the thresholds",
+        Reason::ModelsDisagree => "the models disagree",
+        Reason::SlopeDisagrees => "the regression slope disagrees with the last price change",
+        Reason::LowRSquared => "the regression fits the history too poorly",
+        Reason::WarmingUp => "the history is too short for the models to have warmed up",
+        Reason::HorizonsDisagree => "the prediction differs at a longer horizon",
+        Reason::WindowsOutvoted => "the earlier windows outvoted the whole series",
+    }
+}
+
+/// Formats a basis-point confidence as a percentage, with only the decimals it needs.
+pub fn format_bps_percent(bps: u64) -> String {
+    format_wei(U256::from(bps), 2)
+}
+
+/// Returns the bitmask selecting the given horizons, in days ahead, in the guest input.
+pub fn horizon_mask(horizons: &[u64]) -> u64 {
+    horizons
+        .iter()
+        .fold(0, |mask, horizon| mask | 1 << (horizon - 1))
+}
+
+/// Number of base units in one whole unit of a price with `decimals` decimals.
+pub fn unit_scale(decimals: u8) -> U256 {
+    U256::from(10u64).pow(U256::from(decimals))
+}
+
+/// Absolute predicted move relative to the current price, in basis points.
+pub fn predicted_move_bps(current_price: u64, predicted_price: u64) -> u64 {
+    if current_price == 0 {
+        return u64::MAX;
+    }
+    let diff = current_price.abs_diff(predicted_price) as u128;
+    (diff * 10_000 / current_price as u128)
+        .try_into()
+        .unwrap_or(u64::MAX)
+}
+
+/// Forces HOLD when the predicted move is below `min_move_bps`, i.e. too small to be worth
+/// trading after fees regardless of the guest's signal.
+pub fn suppress_small_move(
+    action: Action,
+    current_price: u64,
+    predicted_price: u64,
+    min_move_bps: u64,
+) -> Action {
+    if predicted_move_bps(current_price, predicted_price) < min_move_bps {
+        Action::Hold
+    } else {
+        action
+    }
+}
+
+/// Forces HOLD when the predicted move exceeds `max_move_bps`, which almost certainly means bad
+/// input data rather than a real opportunity. `None` disables the cap.
+pub fn suppress_extreme_move(
+    action: Action,
+    current_price: u64,
+    predicted_price: u64,
+    max_move_bps: Option<u64>,
+) -> Action {
+    match max_move_bps {
+        Some(max) if predicted_move_bps(current_price, predicted_price) > max => Action::Hold,
+        _ => action,
+    }
+}
+
+/// The most recent actions computed in loop mode, oldest first.
+///
+/// Used to tell a persistent trend from a whipsaw before acting on a single signal.
+#[derive(Clone, Debug)]
+pub struct SignalHistory {
+    actions: VecDeque<Action>,
+    capacity: usize,
+}
+
+impl SignalHistory {
+    /// Creates a buffer keeping at most `capacity` actions.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            actions: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Records a new action, evicting the oldest one when full.
+    pub fn push(&mut self, action: Action) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.actions.len() == self.capacity {
+            self.actions.pop_front();
+        }
+        self.actions.push_back(action);
+    }
+
+    /// Returns the latest action and how many times it occurred consecutively.
+    pub fn streak(&self) -> Option<(Action, usize)> {
+        let latest = *self.actions.back()?;
+        let count = self
+            .actions
+            .iter()
+            .rev()
+            .take_while(|action| **action == latest)
+            .count();
+        Some((latest, count))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn long_only_turns_sell_into_hold() {
+        let action = Action::try_from(0).unwrap();
+        assert_eq!(action, Action::Sell);
+        assert_eq!(Strategy::LongOnly.apply(action), Action::Hold);
+        assert_eq!(Strategy::LongOnly.apply(Action::Buy), Action::Buy);
+    }
+
+    #[test]
+    fn guest_hold_signal_maps_to_hold() {
+        assert_eq!(Action::try_from(1), Ok(Action::Buy));
+        assert_eq!(Action::try_from(2), Ok(Action::Hold));
+    }
+
+    #[test]
+    fn model_mask_ignores_duplicates() {
+        assert_eq!(Model::mask(&[Model::Linreg]), 0b01);
+        assert_eq!(
+            Model::mask(&[Model::SmaCrossover, Model::Linreg, Model::SmaCrossover]),
+            0b11
+        );
+        assert_eq!(Model::mask(&[Model::PercentileRank]), 0b100);
+        assert_eq!(Model::mask(&[Model::TheilSen, Model::Holt]), 0b11000);
+    }
+
+    #[test]
+    fn long_short_keeps_actions() {
+        assert_eq!(Strategy::LongShort.apply(Action::Sell), Action::Sell);
+        assert_eq!(Strategy::LongShort.apply(Action::Buy), Action::Buy);
+    }
+
+    #[test]
+    fn small_predicted_move_is_suppressed() {
+        // $3200 -> $3210 is a ~31 bps move
+        assert_eq!(predicted_move_bps(3200, 3210), 31);
+        assert_eq!(
+            suppress_small_move(Action::Buy, 3200, 3210, 50),
+            Action::Hold
+        );
+        assert_eq!(
+            suppress_small_move(Action::Sell, 3200, 3190, 50),
+            Action::Hold
+        );
+        assert_eq!(
+            suppress_small_move(Action::Buy, 3200, 3300, 50),
+            Action::Buy
+        );
+        // Disabled by default
+        assert_eq!(suppress_small_move(Action::Buy, 3200, 3200, 0), Action::Buy);
+    }
+
+    #[test]
+    fn extreme_predicted_move_is_suppressed() {
+        // $3200 -> $4800 is a 5000 bps move, far beyond a 20% cap
+        assert_eq!(
+            suppress_extreme_move(Action::Buy, 3200, 4800, Some(2000)),
+            Action::Hold
+        );
+        assert_eq!(
+            suppress_extreme_move(Action::Sell, 3200, 0, Some(2000)),
+            Action::Hold
+        );
+        assert_eq!(
+            suppress_extreme_move(Action::Buy, 3200, 3300, Some(2000)),
+            Action::Buy
+        );
+        assert_eq!(
+            suppress_extreme_move(Action::Buy, 3200, 4800, None),
+            Action::Buy
+        );
+    }
+
+    #[test]
+    fn reverses_detects_a_single_flip() {
+        let actions = [
+            Action::Buy,
+            Action::Buy,
+            Action::Hold,
+            Action::Sell,
+            Action::Buy,
+            Action::Buy,
+        ];
+        let flips: Vec<_> = actions
+            .windows(2)
+            .filter(|pair| reverses(pair[0], pair[1]))
+            .map(|pair| (pair[0], pair[1]))
+            .collect();
+        // BUY -> HOLD -> SELL is not a flip, since HOLD was the last iteration before SELL.
+        assert_eq!(flips, vec![(Action::Sell, Action::Buy)]);
+    }
+
+    #[test]
+    fn streak_counts_consecutive_latest_actions() {
+        let mut history = SignalHistory::new(4);
+        assert_eq!(history.streak(), None);
+
+        for action in [
+            Action::Sell,
+            Action::Buy,
+            Action::Sell,
+            Action::Buy,
+            Action::Buy,
+        ] {
+            history.push(action);
+        }
+        assert_eq!(history.streak(), Some((Action::Buy, 2)));
+
+        history.push(Action::Buy);
+        history.push(Action::Buy);
+        // The buffer only holds four actions, so the streak is capped at the capacity
+        assert_eq!(history.streak(), Some((Action::Buy, 4)));
+
+        history.push(Action::Hold);
+        assert_eq!(history.streak(), Some((Action::Hold, 1)));
+    }
+
+    #[test]
+    fn oversized_words_are_rejected_rather_than_truncated() {
+        let above_u64 = U256::from(u64::MAX) + U256::from(1u64);
+        let raw = |predicted_amount: U256, decimals: u8| {
+            RawPrediction::decode(
+                &(predicted_amount, U256::from(85u64)).abi_encode(),
+                decimals,
+                false,
+            )
+        };
+
+        let err = raw(above_u64, 0).unwrap_err();
+        assert!(
+            err.to_string().contains("predicted price"),
+            "unexpected error: {err}"
+        );
+        // Wei amounts exceed u64 long before the whole USD price does
+        let wei = U256::from(3750u64) * unit_scale(18);
+        assert!(wei > U256::from(u64::MAX));
+        assert_eq!(raw(wei, 18).unwrap().predicted_price, 3750);
+
+        let journal = Journal::V1(trading_journal::JournalV1 {
+            signal: Action::Buy,
+            confidence: 85,
+            predicted_price: above_u64,
+            input_hash: B256::ZERO,
+        })
+        .encode();
+        assert!(GuestOutput::decode(&journal, 0).is_err());
+        assert_eq!(
+            GuestOutput::decode(&journal, 1).unwrap().predicted_price,
+            u64::MAX / 10
+        );
+    }
+
+    #[test]
+    fn raw_confidence_is_read_in_the_requested_unit() {
+        let journal = (U256::from(3264u64), U256::from(4318u64)).abi_encode();
+        let prediction = RawPrediction::decode(&journal, 0, true).unwrap();
+        assert_eq!(
+            (prediction.confidence, prediction.confidence_bps),
+            (43, 4318)
+        );
+        assert_eq!(format_bps_percent(prediction.confidence_bps), "43.18");
+
+        let journal = (U256::from(3264u64), U256::from(43u64)).abi_encode();
+        let prediction = RawPrediction::decode(&journal, 0, false).unwrap();
+        assert_eq!(
+            (prediction.confidence, prediction.confidence_bps),
+            (43, 4300)
+        );
+        assert_eq!(format_bps_percent(prediction.confidence_bps), "43");
+    }
+
+    #[test]
+    fn v6_journal_carries_the_hold_reason() {
+        let journal = Journal::V6(trading_journal::JournalV6 {
+            base: JournalV5 {
+                base: JournalV4 {
+                    base: JournalV3 {
+                        base: JournalV2 {
+                            signal: Action::Hold,
+                            confidence: 43,
+                            predicted_price: U256::from(3264u64),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                    data_points: 20,
+                },
+                confidence_bps: 4318,
+            },
+            reason: Reason::LowRSquared,
+        })
+        .encode();
+        let output = GuestOutput::decode(&journal, 0).unwrap();
+        assert_eq!((output.version, output.reason), (6, Reason::LowRSquared));
+        assert_eq!(output.confidence_bps, 4318);
+        assert_eq!(
+            describe_reason(output.reason),
+            "the regression fits the history too poorly"
+        );
+    }
+
+    #[test]
+    fn committed_decimals_must_match_the_requested_ones() {
+        let journal = Journal::V10(trading_journal::JournalV10 {
+            base: JournalV9 {
+                base: JournalV8 {
+                    base: JournalV7 {
+                        base: JournalV6 {
+                            base: JournalV5 {
+                                base: JournalV4 {
+                                    base: JournalV3 {
+                                        base: JournalV2 {
+                                            predicted_price: U256::from(3_264_500_000u64),
+                                            ..Default::default()
+                                        },
+                                        ..Default::default()
+                                    },
+                                    ..Default::default()
+                                },
+                                ..Default::default()
+                            },
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                last_price: 3250,
+            },
+            decimals: 6,
+        })
+        .encode();
+        let output = GuestOutput::decode(&journal, 6).unwrap();
+        assert_eq!((output.predicted_price, output.last_price), (3264, 3250));
+        assert!(GuestOutput::decode(&journal, 0).is_err());
+    }
+}
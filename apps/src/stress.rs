@@ -0,0 +1,241 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+
+use anyhow::{bail, Result};
+
+use crate::{history::PricePoint, signal::GuestOutput};
+
+/// Shortest series generated, long enough for every model to have warmed up.
+const MIN_LEN: u64 = 30;
+/// Longest series generated.
+const MAX_LEN: u64 = 120;
+/// Factor of the highest input price beyond which a prediction is taken to have wrapped around
+/// rather than extrapolated: an overflow lands orders of magnitude further.
+const MAX_PREDICTION_FACTOR: u64 = 10;
+
+/// Shape of a generated price series.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Scenario {
+    /// The same price every day.
+    Flat,
+    /// A steady price with a single day tens of times higher.
+    Spike,
+    /// A steady price that collapses to a fraction of itself and stays there.
+    Crash,
+    /// A price that alternates between two levels every few days.
+    Sawtooth,
+}
+
+impl Scenario {
+    /// Every scenario, in the order they are run.
+    const ALL: [Self; 4] = [Self::Flat, Self::Spike, Self::Crash, Self::Sawtooth];
+}
+
+impl fmt::Display for Scenario {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Flat => "flat",
+            Self::Spike => "spike",
+            Self::Crash => "crash",
+            Self::Sawtooth => "sawtooth",
+        })
+    }
+}
+
+/// SplitMix64, the generator the guest draws from as well, so that a seed reproduces the same
+/// cases everywhere.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // Uniform enough draw from `low..=high` for test inputs
+    fn range(&mut self, low: u64, high: u64) -> u64 {
+        low + self.next() % (high - low + 1)
+    }
+}
+
+/// Draws a series of `scenario`'s shape and the current price to predict it against.
+fn generate(scenario: Scenario, rng: &mut Rng) -> (Vec<PricePoint>, u64) {
+    let len = rng.range(MIN_LEN, MAX_LEN);
+    let base = rng.range(1, 100_000);
+    let history: Vec<PricePoint> = match scenario {
+        Scenario::Flat => (1..=len).map(|day| (day, base)).collect(),
+        Scenario::Spike => {
+            let spike_day = rng.range(1, len);
+            let factor = rng.range(10, 50);
+            (1..=len)
+                .map(|day| {
+                    (
+                        day,
+                        if day == spike_day {
+                            base * factor
+                        } else {
+                            base
+                        },
+                    )
+                })
+                .collect()
+        }
+        Scenario::Crash => {
+            let crash_day = rng.range(2, len);
+            let floor = (base / rng.range(10, 1_000)).max(1);
+            (1..=len)
+                .map(|day| (day, if day < crash_day { base } else { floor }))
+                .collect()
+        }
+        Scenario::Sawtooth => {
+            let period = rng.range(1, 5);
+            let high = base * rng.range(2, 5);
+            (1..=len)
+                .map(|day| (day, if (day / period) % 2 == 0 { base } else { high }))
+                .collect()
+        }
+    };
+    let current_price = history[history.len() - 1].1;
+    (history, current_price)
+}
+
+/// Checks what any guest output must satisfy whatever its input: a confidence within 0-100 (and
+/// 0-10000 in basis points) and a prediction within reach of `max_price`, the highest price of
+/// the input. The signal is checked by decoding, which rejects actions outside BUY, SELL and HOLD.
+fn check_invariants(output: &GuestOutput, max_price: u64) -> Result<()> {
+    if output.confidence > 100 || output.up_confidence > 100 || output.down_confidence > 100 {
+        bail!(
+            "confidence {}% (up {}%, down {}%) exceeds 100%",
+            output.confidence,
+            output.up_confidence,
+            output.down_confidence
+        );
+    }
+    if output.confidence_bps > 10_000 {
+        bail!("confidence {} bps exceeds 10000", output.confidence_bps);
+    }
+    if output.predicted_price > max_price.saturating_mul(MAX_PREDICTION_FACTOR) {
+        bail!(
+            "predicted price ${} is more than {} times the highest price ${}, likely wrapped",
+            output.predicted_price,
+            MAX_PREDICTION_FACTOR,
+            max_price
+        );
+    }
+    Ok(())
+}
+
+/// Outcome of a stress run.
+#[derive(Debug, Default)]
+pub struct StressReport {
+    /// Number of cases executed.
+    pub cases: usize,
+    /// Each case that broke an invariant or failed to execute, with what went wrong.
+    pub failures: Vec<String>,
+}
+
+impl StressReport {
+    /// Logs every failure and fails if there was any.
+    pub fn check(&self) -> Result<()> {
+        for failure in &self.failures {
+            tracing::error!("{}", failure);
+        }
+        if !self.failures.is_empty() {
+            bail!(
+                "{} of {} stress cases broke an invariant",
+                self.failures.len(),
+                self.cases
+            );
+        }
+        tracing::info!("All {} stress cases held the invariants", self.cases);
+        Ok(())
+    }
+}
+
+/// Generates `cases` series of every scenario from `seed` and checks the output `execute`
+/// returns for each, given the series and its current price, against [check_invariants].
+pub fn run(
+    cases: usize,
+    seed: u64,
+    mut execute: impl FnMut(&[PricePoint], u64) -> Result<GuestOutput>,
+) -> StressReport {
+    let mut rng = Rng(seed);
+    let mut report = StressReport::default();
+    for scenario in Scenario::ALL {
+        for case in 0..cases {
+            let (history, current_price) = generate(scenario, &mut rng);
+            let max_price = history.iter().map(|(_, price)| *price).max().unwrap_or(0);
+            report.cases += 1;
+            if let Err(err) = execute(&history, current_price)
+                .and_then(|output| check_invariants(&output, max_price.max(current_price)))
+            {
+                report
+                    .failures
+                    .push(format!("{} case {}: {:#}", scenario, case, err));
+            }
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::U256;
+
+    use super::*;
+
+    #[test]
+    fn generates_reproducible_shapes() {
+        let draw = |scenario, seed| generate(scenario, &mut Rng(seed));
+        assert_eq!(draw(Scenario::Crash, 7), draw(Scenario::Crash, 7));
+        assert_ne!(draw(Scenario::Crash, 7), draw(Scenario::Crash, 8));
+
+        let (flat, current_price) = draw(Scenario::Flat, 1);
+        assert!((MIN_LEN..=MAX_LEN).contains(&(flat.len() as u64)));
+        assert!(flat.iter().all(|(_, price)| *price == current_price));
+
+        let (spike, _) = draw(Scenario::Spike, 1);
+        let base = spike.iter().map(|(_, price)| *price).min().unwrap();
+        assert_eq!(spike.iter().filter(|(_, price)| *price != base).count(), 1);
+
+        let (crash, current_price) = draw(Scenario::Crash, 1);
+        assert!(current_price < crash[0].1);
+    }
+
+    #[test]
+    fn reports_each_case_breaking_an_invariant() {
+        let output = |confidence, predicted_price| GuestOutput {
+            confidence,
+            predicted_price,
+            predicted_amount: U256::from(predicted_price),
+            ..Default::default()
+        };
+        check_invariants(&output(100, 30_000), 3_000).unwrap();
+        assert!(check_invariants(&output(101, 3_000), 3_000).is_err());
+        assert!(check_invariants(&output(80, u64::MAX - 5), 3_000).is_err());
+
+        let report = run(3, 0, |history, _| {
+            Ok(output(if history.len() % 2 == 0 { 101 } else { 50 }, 1))
+        });
+        assert_eq!(report.cases, 12);
+        assert!(!report.failures.is_empty());
+        assert!(report.check().is_err());
+        assert!(run(3, 0, |_, _| Ok(output(50, 1))).check().is_ok());
+    }
+}
@@ -0,0 +1,50 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use tracing_subscriber::{fmt::format::FmtSpan, EnvFilter};
+
+/// Installs the global log subscriber, filtered by `RUST_LOG`.
+///
+/// Each phase of a run (submit, wait_for_fulfillment, decode, contract_update) runs in a span of
+/// that name, which is logged with its busy and idle time when it closes.
+pub fn init() {
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .with_span_events(FmtSpan::CLOSE)
+        .init();
+}
+
+/// Records the names of the spans created on the current thread until the guard is dropped.
+#[cfg(test)]
+pub fn capture_span_names() -> (
+    tracing::subscriber::DefaultGuard,
+    std::sync::Arc<std::sync::Mutex<Vec<&'static str>>>,
+) {
+    use std::sync::{Arc, Mutex};
+
+    use tracing::{span, Subscriber};
+    use tracing_subscriber::{layer::Context, prelude::*, Layer};
+
+    struct SpanNames(Arc<Mutex<Vec<&'static str>>>);
+
+    impl<S: Subscriber> Layer<S> for SpanNames {
+        fn on_new_span(&self, attrs: &span::Attributes<'_>, _: &span::Id, _: Context<'_, S>) {
+            self.0.lock().unwrap().push(attrs.metadata().name());
+        }
+    }
+
+    let names = Arc::new(Mutex::new(Vec::new()));
+    let subscriber = tracing_subscriber::registry().with(SpanNames(names.clone()));
+    (tracing::subscriber::set_default(subscriber), names)
+}
@@ -0,0 +1,74 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloy::{
+    primitives::{Address, U256},
+    providers::Provider,
+};
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+
+use crate::trading_signal::ITradingSignal::ITradingSignalInstance;
+
+/// Where the BUY and SELL thresholds sent to the guest come from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ThresholdSource {
+    /// `--buy-threshold-bps` and `--sell-threshold-bps`.
+    #[default]
+    Flags,
+    /// `getThresholds()` on `--trading-signal-address`, so the owner of the contract sets the
+    /// strategy.
+    Onchain,
+}
+
+/// Reads the (BUY, SELL) thresholds in basis points from the TradingSignal contract at `address`.
+pub async fn read_thresholds<P: Provider>(provider: P, address: Address) -> Result<(u64, u64)> {
+    let thresholds = ITradingSignalInstance::new(address, provider)
+        .getThresholds()
+        .call()
+        .await
+        .with_context(|| format!("failed to read the thresholds of TradingSignal {}", address))?;
+    let to_bps = |value: U256, name: &str| {
+        u64::try_from(value).with_context(|| format!("{name} threshold {value} bps is too large"))
+    };
+    Ok((
+        to_bps(thresholds.buyThresholdBps, "BUY")?,
+        to_bps(thresholds.sellThresholdBps, "SELL")?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        providers::RootProvider, rpc::client::RpcClient, sol_types::SolValue,
+        transports::mock::Asserter,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn reads_thresholds_from_the_contract() {
+        let asserter = Asserter::new();
+        let provider = RootProvider::new(RpcClient::mocked(asserter.clone()));
+
+        asserter.push_success(&(U256::from(120), U256::from(80)).abi_encode());
+        assert_eq!(
+            read_thresholds(&provider, Address::ZERO).await.unwrap(),
+            (120, 80)
+        );
+
+        asserter.push_success(&(U256::MAX, U256::from(80)).abi_encode());
+        assert!(read_thresholds(&provider, Address::ZERO).await.is_err());
+    }
+}
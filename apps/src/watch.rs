@@ -0,0 +1,120 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use alloy::{primitives::Address, providers::Provider};
+use anyhow::{Context, Result};
+use trading_journal::format_wei;
+
+use crate::{
+    signal::Action,
+    trading_signal::ITradingSignal::{ITradingSignalInstance, Signal},
+};
+
+/// Reads the signal the TradingSignal contract at `address` currently stores.
+pub async fn read_latest_signal<P: Provider>(provider: P, address: Address) -> Result<Signal> {
+    ITradingSignalInstance::new(address, provider)
+        .getLatestSignal()
+        .call()
+        .await
+        .with_context(|| {
+            format!(
+                "failed to read the latest signal of TradingSignal {}",
+                address
+            )
+        })
+}
+
+/// Tracks the signal stored in the contract across polls, so that only changes are reported.
+#[derive(Debug, Default)]
+pub struct SignalWatcher {
+    last: Option<Signal>,
+}
+
+impl SignalWatcher {
+    /// Records `signal` as the latest read and returns whether it differs from the previous one.
+    /// The first read always counts as a change.
+    pub fn changed(&mut self, signal: &Signal) -> bool {
+        if self.last.as_ref() == Some(signal) {
+            return false;
+        }
+        self.last = Some(signal.clone());
+        true
+    }
+}
+
+/// Formats a stored signal as one line, with the predicted price in units with `decimals`
+/// decimals.
+pub fn line(signal: &Signal, decimals: u8) -> Result<String> {
+    let action = Action::try_from(signal.action).context("contract stores an invalid action")?;
+    Ok(format!(
+        "at {}: {} with {}% confidence, predicted ${}, input {}",
+        signal.timestamp,
+        action,
+        signal.confidence,
+        format_wei(signal.predictedPrice, decimals),
+        signal.inputHash
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        primitives::{B256, U256},
+        providers::RootProvider,
+        rpc::client::RpcClient,
+        sol_types::SolValue,
+        transports::mock::Asserter,
+    };
+
+    use super::*;
+
+    fn stored(action: u8, timestamp: u64) -> Signal {
+        Signal {
+            action,
+            confidence: U256::from(80),
+            predictedPrice: U256::from(3264),
+            priceDecimals: 0,
+            inputHash: B256::repeat_byte(0x44),
+            timestamp: U256::from(timestamp),
+        }
+    }
+
+    #[tokio::test]
+    async fn only_differing_reads_are_changes() {
+        let asserter = Asserter::new();
+        let provider = RootProvider::new(RpcClient::mocked(asserter.clone()));
+        let mut watcher = SignalWatcher::default();
+
+        let mut changes = Vec::new();
+        for signal in [stored(1, 100), stored(1, 100), stored(0, 160)] {
+            asserter.push_success(&signal.abi_encode());
+            let read = read_latest_signal(&provider, Address::ZERO).await.unwrap();
+            assert_eq!(read, signal);
+            changes.push(watcher.changed(&read));
+        }
+        assert_eq!(changes, [true, false, true]);
+    }
+
+    #[test]
+    fn formats_the_stored_signal() {
+        assert_eq!(
+            line(&stored(0, 160), 0).unwrap(),
+            format!(
+                "at 160: SELL with 80% confidence, predicted $3264, input {}",
+                B256::repeat_byte(0x44)
+            )
+        );
+        assert!(line(&stored(3, 160), 0).is_err());
+    }
+}
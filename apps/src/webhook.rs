@@ -0,0 +1,122 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use url::Url;
+
+use crate::output::RunRecord;
+
+/// Time allowed for each POST before it is abandoned.
+pub const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+/// Number of POSTs attempted before giving up.
+pub const WEBHOOK_ATTEMPTS: u32 = 3;
+/// Delay between failed attempts.
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// POSTs the run record as JSON to `url`, retrying failed or non-2xx attempts.
+pub async fn post(url: &Url, record: &RunRecord) -> Result<()> {
+    let client = reqwest::Client::builder()
+        .timeout(WEBHOOK_TIMEOUT)
+        .build()
+        .context("failed to build webhook client")?;
+    let mut attempt = 1;
+    loop {
+        match try_post(&client, url, record).await {
+            Ok(()) => return Ok(()),
+            Err(e) if attempt < WEBHOOK_ATTEMPTS => {
+                tracing::warn!("Webhook attempt {} failed: {:?}", attempt, e);
+                attempt += 1;
+                tokio::time::sleep(RETRY_DELAY).await;
+            }
+            Err(e) => return Err(e.context(format!("webhook failed after {} attempts", attempt))),
+        }
+    }
+}
+
+async fn try_post(client: &reqwest::Client, url: &Url, record: &RunRecord) -> Result<()> {
+    let response = client
+        .post(url.clone())
+        .json(record)
+        .send()
+        .await
+        .context("failed to send webhook")?;
+    let status = response.status();
+    if !status.is_success() {
+        bail!("webhook returned {}", status);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    use super::*;
+    use crate::signal::{Action, GuestOutput};
+
+    /// Accepts a single request and returns its body after answering 200 OK.
+    async fn receive_one(listener: TcpListener) -> Vec<u8> {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        let mut buf = [0u8; 1024];
+        loop {
+            let n = stream.read(&mut buf).await.unwrap();
+            request.extend_from_slice(&buf[..n]);
+            let text = String::from_utf8_lossy(&request);
+            if let Some(header_end) = text.find("\r\n\r\n") {
+                let content_length = text[..header_end]
+                    .lines()
+                    .find_map(|line| {
+                        let (name, value) = line.split_once(':')?;
+                        name.eq_ignore_ascii_case("content-length")
+                            .then(|| value.trim().parse::<usize>().unwrap())
+                    })
+                    .unwrap_or(0);
+                if request.len() >= header_end + 4 + content_length {
+                    stream
+                        .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+                        .await
+                        .unwrap();
+                    return request[header_end + 4..].to_vec();
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn posts_run_record_as_json() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = Url::parse(&format!("http://{}/hook", listener.local_addr().unwrap())).unwrap();
+        let server = tokio::spawn(receive_one(listener));
+
+        let output = GuestOutput {
+            signal: Action::Buy,
+            confidence: 85,
+            predicted_price: 3250,
+            ..Default::default()
+        };
+        let record = RunRecord::new(Action::Buy, &output, None);
+        post(&url, &record).await.unwrap();
+
+        let body: serde_json::Value = serde_json::from_slice(&server.await.unwrap()).unwrap();
+        assert_eq!(body, serde_json::to_value(&record).unwrap());
+        assert_eq!(body["action"], "BUY");
+        assert_eq!(body["confidence"], 85);
+    }
+}
@@ -0,0 +1,25 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+pragma solidity ^0.8.20;
+
+/// Price history read by the app with `--history-source onchain`.
+interface IPriceHistory {
+    struct PricePoint {
+        uint64 day;   // Day index, increasing
+        uint64 price; // USD price per ETH, in whole USD
+    }
+
+    function getPriceHistory() external view returns (PricePoint[] memory);
+}
@@ -16,9 +16,11 @@ pragma solidity ^0.8.20;
 
 interface ITradingSignal {
     struct Signal {
-        uint8 action;           // 0 = SELL, 1 = BUY
+        uint8 action;           // 0 = SELL, 1 = BUY, 2 = HOLD
         uint256 confidence;     // Confidence score (0-100)
-        uint256 predictedPrice; // Predicted price in wei (18 decimals)
+        uint256 predictedPrice; // Predicted price in base units of 10^-priceDecimals USD
+        uint8 priceDecimals;    // Decimals of predictedPrice, 0 for journals before version 10
+        bytes32 inputHash;      // keccak256 of the price series the signal was computed from
         uint256 timestamp;      // When signal was generated
     }
 
@@ -26,20 +28,38 @@ interface ITradingSignal {
         uint8 indexed action,
         uint256 confidence,
         uint256 predictedPrice,
+        bytes32 inputHash,
         uint256 timestamp
     );
 
     event ImageIdUpdated(bytes32 indexed imageId);
 
-    function setSignal(
-        uint8 action,
-        uint256 confidence, 
-        uint256 predictedPrice,
-        bytes calldata seal
-    ) external;
+    event ThresholdsUpdated(uint256 buyThresholdBps, uint256 sellThresholdBps);
+
+    event StrategyApprovalUpdated(bytes32 indexed strategyId, bool approved);
+
+    event StrategyWhitelistUpdated(bool enabled);
+
+    function setSignal(bytes calldata journal, bytes calldata seal) external;
+
+    function setSignalForStrategy(bytes calldata journal, bytes calldata seal, bytes32 strategyId) external;
+
+    function setStrategyApproved(bytes32 strategyId, bool approved) external;
+
+    function setStrategyWhitelist(bool enabled) external;
+
+    function approvedStrategies(bytes32 strategyId) external view returns (bool);
+
+    function strategyWhitelistEnabled() external view returns (bool);
 
     function setImageId(bytes32 _imageId) external;
 
+    function IMAGE_ID() external view returns (bytes32);
+
+    function setThresholds(uint256 buyThresholdBps, uint256 sellThresholdBps) external;
+
+    function getThresholds() external view returns (uint256 buyThresholdBps, uint256 sellThresholdBps);
+
     function getLatestSignal() external view returns (Signal memory);
     
     function getSignalAction() external view returns (uint8);
@@ -47,4 +67,8 @@ interface ITradingSignal {
     function getConfidence() external view returns (uint256);
     
     function getPredictedPrice() external view returns (uint256);
+
+    function getPriceDecimals() external view returns (uint8);
+
+    function getInputHash() external view returns (bytes32);
 }
\ No newline at end of file
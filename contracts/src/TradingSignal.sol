@@ -34,6 +34,25 @@ contract TradingSignal is ITradingSignal, Ownable {
     /// @notice The latest trading signal generated by the AI.
     Signal public latestSignal;
 
+    /// @notice BUY and SELL thresholds in basis points for agents that read their strategy from
+    ///         the contract. They are not enforced on the signals set here.
+    uint256 public buyThresholdBps = 50;
+    uint256 public sellThresholdBps = 50;
+
+    /// @notice Strategy IDs the owner approved. While the whitelist is enabled, only signals from
+    ///         journals that commit one of them are accepted.
+    mapping(bytes32 => bool) public approvedStrategies;
+    bool public strategyWhitelistEnabled;
+
+    /// @notice Journals from version 10 on commit the decimals of the predicted price in this
+    ///         word, right before the trailing version word of a version 10 journal.
+    uint256 private constant PRICE_DECIMALS_OFFSET = 704;
+    uint256 private constant JOURNAL_V10 = 10;
+    /// @notice Journals from version 8 on commit the hash of the strategy parameters in this word,
+    ///         and version 13 journals the strategy ID in the word before their version.
+    uint256 private constant PARAMS_HASH_OFFSET = 640;
+    uint256 private constant JOURNAL_V13 = 13;
+
     /// @notice Initialize the contract, binding it to a specified RISC Zero verifier.
     constructor(IRiscZeroVerifier _verifier) Ownable(msg.sender) {
         VERIFIER = _verifier;
@@ -43,6 +62,8 @@ contract TradingSignal is ITradingSignal, Ownable {
             action: 0,
             confidence: 0,
             predictedPrice: 0,
+            priceDecimals: 0,
+            inputHash: bytes32(0),
             timestamp: block.timestamp
         });
     }
@@ -55,23 +76,85 @@ contract TradingSignal is ITradingSignal, Ownable {
         emit ImageIdUpdated(_imageId);
     }
 
+    /// @notice Set the BUY and SELL thresholds agents read with getThresholds. Only the owner can call this.
+    /// @param _buyThresholdBps Predicted rise over the current price, in basis points, above which to BUY
+    /// @param _sellThresholdBps Predicted fall under the current price, in basis points, above which to SELL
+    function setThresholds(uint256 _buyThresholdBps, uint256 _sellThresholdBps) external onlyOwner {
+        buyThresholdBps = _buyThresholdBps;
+        sellThresholdBps = _sellThresholdBps;
+        emit ThresholdsUpdated(_buyThresholdBps, _sellThresholdBps);
+    }
+
+    /// @notice Approve or revoke a strategy ID. Only the owner can call this.
+    /// @param strategyId keccak256(abi.encode(imageId, paramsHash)) of the strategy
+    /// @param approved Whether signals of the strategy are accepted while the whitelist is enabled
+    function setStrategyApproved(bytes32 strategyId, bool approved) external onlyOwner {
+        approvedStrategies[strategyId] = approved;
+        emit StrategyApprovalUpdated(strategyId, approved);
+    }
+
+    /// @notice Enable or disable the strategy whitelist. Only the owner can call this.
+    /// @param enabled Whether to only accept signals of approved strategies, set with setSignalForStrategy
+    function setStrategyWhitelist(bool enabled) external onlyOwner {
+        strategyWhitelistEnabled = enabled;
+        emit StrategyWhitelistUpdated(enabled);
+    }
+
+    /// @notice Returns the BUY and SELL thresholds in basis points.
+    function getThresholds() public view returns (uint256, uint256) {
+        return (buyThresholdBps, sellThresholdBps);
+    }
+
     /// @notice Set a new trading signal. Requires a RISC Zero proof that the signal was generated correctly.
-    /// @param action The trading action: 0 for SELL, 1 for BUY
-    /// @param confidence The confidence score of the prediction (0-100)
-    /// @param predictedPrice The predicted ETH price in wei (18 decimals)
+    /// @dev The journal starts with abi.encode(uint8 action, uint256 confidence, uint256 predictedPrice, bytes32 inputHash)
+    ///      as committed by the guest. Verify will fail if the journal was not produced by the guest.
+    ///      Of the fields the guest commits after these, only the decimals of the predicted price,
+    ///      from journal version 10, are stored; the rest are covered by the proof but not stored.
+    ///      Reverts while the strategy whitelist is enabled, which only setSignalForStrategy passes.
+    /// @param journal The journal committed by the trading signal guest
+    /// @param seal The RISC Zero proof seal
+    function setSignal(bytes calldata journal, bytes calldata seal) public {
+        require(!strategyWhitelistEnabled, "Strategy whitelist enabled: use setSignalForStrategy");
+        _setSignal(journal, seal);
+    }
+
+    /// @notice Set a new trading signal from a version 13 journal, which commits the ID of the
+    ///         strategy that produced it. Requires the strategy to be approved while the whitelist
+    ///         is enabled.
+    /// @dev The guest cannot know its own image ID, so it hashes the one it is given with the hash
+    ///      of its parameters; the ID is only accepted if it was computed for IMAGE_ID, the image
+    ///      the proof is verified against.
+    /// @param journal The version 13 journal committed by the trading signal guest
     /// @param seal The RISC Zero proof seal
-    function setSignal(
-        uint8 action,
-        uint256 confidence, 
-        uint256 predictedPrice,
-        bytes calldata seal
-    ) public {
-        require(action <= 1, "Invalid action: must be 0 (SELL) or 1 (BUY)");
+    /// @param strategyId The strategy ID the journal commits
+    function setSignalForStrategy(bytes calldata journal, bytes calldata seal, bytes32 strategyId) public {
+        require(
+            journal.length >= PARAMS_HASH_OFFSET + 96
+                && uint256(bytes32(journal[journal.length - 32:])) == JOURNAL_V13,
+            "Invalid journal: must be version 13"
+        );
+        require(
+            bytes32(journal[journal.length - 64:journal.length - 32]) == strategyId,
+            "Invalid strategy ID: not the one the journal commits"
+        );
+        bytes32 paramsHash = bytes32(journal[PARAMS_HASH_OFFSET:PARAMS_HASH_OFFSET + 32]);
+        require(
+            keccak256(abi.encode(IMAGE_ID, paramsHash)) == strategyId,
+            "Invalid strategy ID: not computed for this image"
+        );
+        require(!strategyWhitelistEnabled || approvedStrategies[strategyId], "Strategy not approved");
+        _setSignal(journal, seal);
+    }
+
+    /// @dev Checks and stores the signal of a journal proven by `seal`.
+    function _setSignal(bytes calldata journal, bytes calldata seal) private {
+        (uint8 action, uint256 confidence, uint256 predictedPrice, bytes32 inputHash) =
+            abi.decode(journal, (uint8, uint256, uint256, bytes32));
+        require(action <= 2, "Invalid action: must be 0 (SELL), 1 (BUY) or 2 (HOLD)");
         require(confidence <= 100, "Invalid confidence: must be 0-100");
         require(predictedPrice > 0, "Invalid predicted price: must be > 0");
+        uint8 priceDecimals = _priceDecimals(journal);
 
-        // Construct the expected journal data. Verify will fail if journal doesn't match.
-        bytes memory journal = abi.encode(action, confidence, predictedPrice);
         VERIFIER.verify(seal, IMAGE_ID, sha256(journal));
         
         // Update the signal
@@ -79,10 +162,12 @@ contract TradingSignal is ITradingSignal, Ownable {
             action: action,
             confidence: confidence,
             predictedPrice: predictedPrice,
+            priceDecimals: priceDecimals,
+            inputHash: inputHash,
             timestamp: block.timestamp
         });
 
-        emit SignalUpdated(action, confidence, predictedPrice, block.timestamp);
+        emit SignalUpdated(action, confidence, predictedPrice, inputHash, block.timestamp);
     }
 
     /// @notice Returns the complete latest trading signal.
@@ -105,10 +190,23 @@ contract TradingSignal is ITradingSignal, Ownable {
         return latestSignal.predictedPrice;
     }
 
+    /// @notice Returns the decimals of the latest predicted price, 0 if its journal predates version 10.
+    function getPriceDecimals() public view returns (uint8) {
+        return latestSignal.priceDecimals;
+    }
+
+    /// @notice Returns the hash of the price series the latest signal was computed from.
+    function getInputHash() public view returns (bytes32) {
+        return latestSignal.inputHash;
+    }
+
     /// @notice Helper function to get human-readable action string.
-    /// @return actionString "BUY" or "SELL"
+    /// @return actionString "BUY", "SELL" or "HOLD"
     function getActionString() public view returns (string memory actionString) {
-        return latestSignal.action == 1 ? "BUY" : "SELL";
+        if (latestSignal.action == 1) {
+            return "BUY";
+        }
+        return latestSignal.action == 2 ? "HOLD" : "SELL";
     }
 
     /// @notice Check if the latest signal recommends buying.
@@ -120,4 +218,18 @@ contract TradingSignal is ITradingSignal, Ownable {
     function shouldSell() public view returns (bool) {
         return latestSignal.action == 0;
     }
+
+    /// @dev Reads the decimals of the predicted price from a journal of version 10 or later, whose
+    ///      last word is its version. Older and legacy journals are shorter or carry a lower
+    ///      version, and leave the decimals at 0.
+    function _priceDecimals(bytes calldata journal) private pure returns (uint8) {
+        if (journal.length < PRICE_DECIMALS_OFFSET + 64) {
+            return 0;
+        }
+        uint256 version = uint256(bytes32(journal[journal.length - 32:]));
+        if (version < JOURNAL_V10) {
+            return 0;
+        }
+        return uint8(uint256(bytes32(journal[PRICE_DECIMALS_OFFSET:PRICE_DECIMALS_OFFSET + 32])));
+    }
 }
\ No newline at end of file
@@ -22,11 +22,14 @@ import {VerificationFailed} from "risc0/IRiscZeroVerifier.sol";
 import {TradingSignal} from "../src/TradingSignal.sol";
 import {ITradingSignal} from "../src/ITradingSignal.sol";
 import {ImageID} from "../src/ImageID.sol";
+import {Ownable} from "openzeppelin-contracts/contracts/access/Ownable.sol";
 
 contract TradingSignalTest is RiscZeroCheats, Test {
     TradingSignal public tradingSignal;
     RiscZeroMockVerifier public verifier;
 
+    bytes32 internal constant INPUT_HASH = keccak256("price-series");
+
     function setUp() public {
         verifier = new RiscZeroMockVerifier(0);
         tradingSignal = new TradingSignal(verifier);
@@ -36,6 +39,7 @@ contract TradingSignalTest is RiscZeroCheats, Test {
         assertEq(initial.action, 0);
         assertEq(initial.confidence, 0);
         assertEq(initial.predictedPrice, 0);
+        assertEq(initial.inputHash, bytes32(0));
     }
 
     function test_SetBuySignal() public {
@@ -43,17 +47,17 @@ contract TradingSignalTest is RiscZeroCheats, Test {
         uint256 confidence = 85;
         uint256 predictedPrice = 3750000000000000000; // 3.75 ETH in wei
 
-        RiscZeroReceipt memory receipt = verifier.mockProve(
-            ImageID.TRADING_SIGNAL_ID, 
-            sha256(abi.encode(action, confidence, predictedPrice))
-        );
+        bytes memory journal = abi.encode(action, confidence, predictedPrice, INPUT_HASH);
+        RiscZeroReceipt memory receipt = verifier.mockProve(ImageID.TRADING_SIGNAL_ID, sha256(journal));
 
-        tradingSignal.setSignal(action, confidence, predictedPrice, receipt.seal);
+        tradingSignal.setSignal(journal, receipt.seal);
         
         ITradingSignal.Signal memory signal = tradingSignal.getLatestSignal();
         assertEq(signal.action, 1);
         assertEq(signal.confidence, 85);
         assertEq(signal.predictedPrice, 3750000000000000000);
+        assertEq(signal.inputHash, INPUT_HASH);
+        assertEq(tradingSignal.getInputHash(), INPUT_HASH);
         assertEq(tradingSignal.shouldBuy(), true);
         assertEq(tradingSignal.shouldSell(), false);
         assertEq(tradingSignal.getActionString(), "BUY");
@@ -64,12 +68,10 @@ contract TradingSignalTest is RiscZeroCheats, Test {
         uint256 confidence = 92;
         uint256 predictedPrice = 3400000000000000000; // 3.4 ETH in wei
 
-        RiscZeroReceipt memory receipt = verifier.mockProve(
-            ImageID.TRADING_SIGNAL_ID, 
-            sha256(abi.encode(action, confidence, predictedPrice))
-        );
+        bytes memory journal = abi.encode(action, confidence, predictedPrice, INPUT_HASH);
+        RiscZeroReceipt memory receipt = verifier.mockProve(ImageID.TRADING_SIGNAL_ID, sha256(journal));
 
-        tradingSignal.setSignal(action, confidence, predictedPrice, receipt.seal);
+        tradingSignal.setSignal(journal, receipt.seal);
         
         ITradingSignal.Signal memory signal = tradingSignal.getLatestSignal();
         assertEq(signal.action, 0);
@@ -80,18 +82,34 @@ contract TradingSignalTest is RiscZeroCheats, Test {
         assertEq(tradingSignal.getActionString(), "SELL");
     }
 
+    function test_SetHoldSignal() public {
+        uint8 action = 2; // HOLD
+        uint256 confidence = 40;
+        uint256 predictedPrice = 3550000000000000000; // 3.55 ETH in wei
+
+        bytes memory journal = abi.encode(action, confidence, predictedPrice, INPUT_HASH);
+        RiscZeroReceipt memory receipt = verifier.mockProve(ImageID.TRADING_SIGNAL_ID, sha256(journal));
+
+        tradingSignal.setSignal(journal, receipt.seal);
+
+        ITradingSignal.Signal memory signal = tradingSignal.getLatestSignal();
+        assertEq(signal.action, 2);
+        assertEq(signal.confidence, 40);
+        assertEq(tradingSignal.shouldBuy(), false);
+        assertEq(tradingSignal.shouldSell(), false);
+        assertEq(tradingSignal.getActionString(), "HOLD");
+    }
+
     function test_RejectInvalidAction() public {
-        uint8 invalidAction = 2; // Invalid
+        uint8 invalidAction = 3; // Invalid
         uint256 confidence = 80;
         uint256 predictedPrice = 3600000000000000000;
 
-        RiscZeroReceipt memory receipt = verifier.mockProve(
-            ImageID.TRADING_SIGNAL_ID, 
-            sha256(abi.encode(invalidAction, confidence, predictedPrice))
-        );
+        bytes memory journal = abi.encode(invalidAction, confidence, predictedPrice, INPUT_HASH);
+        RiscZeroReceipt memory receipt = verifier.mockProve(ImageID.TRADING_SIGNAL_ID, sha256(journal));
 
-        vm.expectRevert("Invalid action: must be 0 (SELL) or 1 (BUY)");
-        tradingSignal.setSignal(invalidAction, confidence, predictedPrice, receipt.seal);
+        vm.expectRevert("Invalid action: must be 0 (SELL), 1 (BUY) or 2 (HOLD)");
+        tradingSignal.setSignal(journal, receipt.seal);
     }
 
     function test_RejectInvalidConfidence() public {
@@ -99,13 +117,11 @@ contract TradingSignalTest is RiscZeroCheats, Test {
         uint256 invalidConfidence = 101; // > 100
         uint256 predictedPrice = 3600000000000000000;
 
-        RiscZeroReceipt memory receipt = verifier.mockProve(
-            ImageID.TRADING_SIGNAL_ID, 
-            sha256(abi.encode(action, invalidConfidence, predictedPrice))
-        );
+        bytes memory journal = abi.encode(action, invalidConfidence, predictedPrice, INPUT_HASH);
+        RiscZeroReceipt memory receipt = verifier.mockProve(ImageID.TRADING_SIGNAL_ID, sha256(journal));
 
         vm.expectRevert("Invalid confidence: must be 0-100");
-        tradingSignal.setSignal(action, invalidConfidence, predictedPrice, receipt.seal);
+        tradingSignal.setSignal(journal, receipt.seal);
     }
 
     function test_RejectZeroPredictedPrice() public {
@@ -113,25 +129,22 @@ contract TradingSignalTest is RiscZeroCheats, Test {
         uint256 confidence = 80;
         uint256 invalidPrice = 0;
 
-        RiscZeroReceipt memory receipt = verifier.mockProve(
-            ImageID.TRADING_SIGNAL_ID, 
-            sha256(abi.encode(action, confidence, invalidPrice))
-        );
+        bytes memory journal = abi.encode(action, confidence, invalidPrice, INPUT_HASH);
+        RiscZeroReceipt memory receipt = verifier.mockProve(ImageID.TRADING_SIGNAL_ID, sha256(journal));
 
         vm.expectRevert("Invalid predicted price: must be > 0");
-        tradingSignal.setSignal(action, confidence, invalidPrice, receipt.seal);
+        tradingSignal.setSignal(journal, receipt.seal);
     }
 
     function test_RejectInvalidProof() public {
         // Create a proof for different data than what we're submitting
-        RiscZeroReceipt memory receipt = verifier.mockProve(
-            ImageID.TRADING_SIGNAL_ID, 
-            sha256(abi.encode(1, 80, 350000))
-        );
+        bytes memory provenJournal = abi.encode(uint8(1), uint256(80), uint256(350000), INPUT_HASH);
+        RiscZeroReceipt memory receipt = verifier.mockProve(ImageID.TRADING_SIGNAL_ID, sha256(provenJournal));
 
         // Try to submit different data with the wrong proof
+        bytes memory journal = abi.encode(uint8(0), uint256(90), uint256(340000), INPUT_HASH);
         vm.expectRevert(VerificationFailed.selector);
-        tradingSignal.setSignal(0, 90, 340000, receipt.seal);
+        tradingSignal.setSignal(journal, receipt.seal);
     }
 
     function test_EventEmission() public {
@@ -139,15 +152,13 @@ contract TradingSignalTest is RiscZeroCheats, Test {
         uint256 confidence = 75;
         uint256 predictedPrice = 3650000000000000000;
 
-        RiscZeroReceipt memory receipt = verifier.mockProve(
-            ImageID.TRADING_SIGNAL_ID, 
-            sha256(abi.encode(action, confidence, predictedPrice))
-        );
+        bytes memory journal = abi.encode(action, confidence, predictedPrice, INPUT_HASH);
+        RiscZeroReceipt memory receipt = verifier.mockProve(ImageID.TRADING_SIGNAL_ID, sha256(journal));
 
         vm.expectEmit(true, false, false, false);
-        emit ITradingSignal.SignalUpdated(action, confidence, predictedPrice, block.timestamp);
+        emit ITradingSignal.SignalUpdated(action, confidence, predictedPrice, INPUT_HASH, block.timestamp);
         
-        tradingSignal.setSignal(action, confidence, predictedPrice, receipt.seal);
+        tradingSignal.setSignal(journal, receipt.seal);
     }
 
     function test_MultiplUpdates() public {
@@ -156,12 +167,10 @@ contract TradingSignalTest is RiscZeroCheats, Test {
         uint256 confidence1 = 80;
         uint256 price1 = 3700000000000000000;
 
-        RiscZeroReceipt memory receipt1 = verifier.mockProve(
-            ImageID.TRADING_SIGNAL_ID, 
-            sha256(abi.encode(action1, confidence1, price1))
-        );
+        bytes memory journal1 = abi.encode(action1, confidence1, price1, INPUT_HASH);
+        RiscZeroReceipt memory receipt1 = verifier.mockProve(ImageID.TRADING_SIGNAL_ID, sha256(journal1));
 
-        tradingSignal.setSignal(action1, confidence1, price1, receipt1.seal);
+        tradingSignal.setSignal(journal1, receipt1.seal);
         assertEq(tradingSignal.getSignalAction(), 1);
 
         // Second signal: SELL
@@ -169,28 +178,47 @@ contract TradingSignalTest is RiscZeroCheats, Test {
         uint256 confidence2 = 95;
         uint256 price2 = 3500000000000000000;
 
-        RiscZeroReceipt memory receipt2 = verifier.mockProve(
-            ImageID.TRADING_SIGNAL_ID, 
-            sha256(abi.encode(action2, confidence2, price2))
-        );
+        bytes memory journal2 = abi.encode(action2, confidence2, price2, INPUT_HASH);
+        RiscZeroReceipt memory receipt2 = verifier.mockProve(ImageID.TRADING_SIGNAL_ID, sha256(journal2));
 
-        tradingSignal.setSignal(action2, confidence2, price2, receipt2.seal);
+        tradingSignal.setSignal(journal2, receipt2.seal);
         assertEq(tradingSignal.getSignalAction(), 0);
         assertEq(tradingSignal.getConfidence(), 95);
         assertEq(tradingSignal.getPredictedPrice(), 3500000000000000000);
     }
 
+    function test_StoresPriceDecimals() public {
+        // A version 10 journal: the four words the contract decodes, the words it skips, then the
+        // decimals of the predicted price and the version
+        bytes memory journal = bytes.concat(
+            abi.encode(uint8(1), uint256(80), uint256(3264500000), INPUT_HASH),
+            new bytes(576),
+            abi.encode(uint256(6), uint256(10))
+        );
+        RiscZeroReceipt memory receipt = verifier.mockProve(ImageID.TRADING_SIGNAL_ID, sha256(journal));
+
+        tradingSignal.setSignal(journal, receipt.seal);
+
+        assertEq(tradingSignal.getPredictedPrice(), 3264500000);
+        assertEq(tradingSignal.getPriceDecimals(), 6);
+        assertEq(tradingSignal.getLatestSignal().priceDecimals, 6);
+
+        // Journals that predate the decimals leave them at 0
+        bytes memory v1 = abi.encode(uint8(1), uint256(80), uint256(3264), INPUT_HASH, uint256(1));
+        receipt = verifier.mockProve(ImageID.TRADING_SIGNAL_ID, sha256(v1));
+        tradingSignal.setSignal(v1, receipt.seal);
+        assertEq(tradingSignal.getPriceDecimals(), 0);
+    }
+
     function test_ViewFunctions() public {
         uint8 action = 1;
         uint256 confidence = 88;
         uint256 predictedPrice = 3800000000000000000;
 
-        RiscZeroReceipt memory receipt = verifier.mockProve(
-            ImageID.TRADING_SIGNAL_ID, 
-            sha256(abi.encode(action, confidence, predictedPrice))
-        );
+        bytes memory journal = abi.encode(action, confidence, predictedPrice, INPUT_HASH);
+        RiscZeroReceipt memory receipt = verifier.mockProve(ImageID.TRADING_SIGNAL_ID, sha256(journal));
 
-        tradingSignal.setSignal(action, confidence, predictedPrice, receipt.seal);
+        tradingSignal.setSignal(journal, receipt.seal);
 
         // Test individual getters
         assertEq(tradingSignal.getSignalAction(), 1);
@@ -204,4 +232,70 @@ contract TradingSignalTest is RiscZeroCheats, Test {
         assertEq(signal.predictedPrice, 3800000000000000000);
         assertTrue(signal.timestamp > 0);
     }
+
+    function test_SetThresholds() public {
+        (uint256 buyThresholdBps, uint256 sellThresholdBps) = tradingSignal.getThresholds();
+        assertEq(buyThresholdBps, 50);
+        assertEq(sellThresholdBps, 50);
+
+        vm.expectEmit(true, true, true, true);
+        emit ITradingSignal.ThresholdsUpdated(120, 80);
+        tradingSignal.setThresholds(120, 80);
+        (buyThresholdBps, sellThresholdBps) = tradingSignal.getThresholds();
+        assertEq(buyThresholdBps, 120);
+        assertEq(sellThresholdBps, 80);
+
+        vm.prank(address(0xBEEF));
+        vm.expectRevert(abi.encodeWithSelector(Ownable.OwnableUnauthorizedAccount.selector, address(0xBEEF)));
+        tradingSignal.setThresholds(0, 0);
+    }
+
+    function _strategyJournal(bytes32 paramsHash, bytes32 strategyId) internal pure returns (bytes memory) {
+        // A version 13 journal without residuals or horizons: the v10 words, with the params hash
+        // at its offset, the two zero counts, then the strategy ID and the version
+        return bytes.concat(
+            abi.encode(uint8(1), uint256(80), uint256(3264), INPUT_HASH),
+            new bytes(512),
+            abi.encode(paramsHash, uint256(3250), uint256(0), uint256(0), uint256(0), strategyId, uint256(13))
+        );
+    }
+
+    function test_StrategyWhitelist() public {
+        bytes32 paramsHash = keccak256("params");
+        bytes32 strategyId = keccak256(abi.encode(ImageID.TRADING_SIGNAL_ID, paramsHash));
+        bytes memory journal = _strategyJournal(paramsHash, strategyId);
+        RiscZeroReceipt memory receipt = verifier.mockProve(ImageID.TRADING_SIGNAL_ID, sha256(journal));
+
+        // Without the whitelist either entry point accepts the journal
+        tradingSignal.setSignalForStrategy(journal, receipt.seal, strategyId);
+        assertEq(tradingSignal.getPredictedPrice(), 3264);
+
+        vm.expectEmit(true, true, true, true);
+        emit ITradingSignal.StrategyWhitelistUpdated(true);
+        tradingSignal.setStrategyWhitelist(true);
+        vm.expectRevert("Strategy whitelist enabled: use setSignalForStrategy");
+        tradingSignal.setSignal(journal, receipt.seal);
+        vm.expectRevert("Strategy not approved");
+        tradingSignal.setSignalForStrategy(journal, receipt.seal, strategyId);
+
+        vm.expectEmit(true, true, true, true);
+        emit ITradingSignal.StrategyApprovalUpdated(strategyId, true);
+        tradingSignal.setStrategyApproved(strategyId, true);
+        tradingSignal.setSignalForStrategy(journal, receipt.seal, strategyId);
+        assertTrue(tradingSignal.approvedStrategies(strategyId));
+
+        // The ID must be the one committed, and computed for the image the proof verifies under
+        vm.expectRevert("Invalid strategy ID: not the one the journal commits");
+        tradingSignal.setSignalForStrategy(journal, receipt.seal, bytes32(uint256(1)));
+        bytes32 otherImageId = keccak256(abi.encode(bytes32(uint256(7)), paramsHash));
+        tradingSignal.setStrategyApproved(otherImageId, true);
+        bytes memory otherImage = _strategyJournal(paramsHash, otherImageId);
+        receipt = verifier.mockProve(ImageID.TRADING_SIGNAL_ID, sha256(otherImage));
+        vm.expectRevert("Invalid strategy ID: not computed for this image");
+        tradingSignal.setSignalForStrategy(otherImage, receipt.seal, otherImageId);
+
+        vm.prank(address(0xBEEF));
+        vm.expectRevert(abi.encodeWithSelector(Ownable.OwnableUnauthorizedAccount.selector, address(0xBEEF)));
+        tradingSignal.setStrategyApproved(strategyId, false);
+    }
 }
\ No newline at end of file
@@ -12,10 +12,27 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use alloy_primitives::U256;
+use alloy_primitives::{keccak256, B256, I256, U256};
 use alloy_sol_types::SolValue;
 use guests::TRADING_SIGNAL_ELF;
-use risc0_zkvm::{default_executor, ExecutorEnv};
+use risc0_zkvm::{default_executor, ExecutorEnv, ExitCode};
+use trading_journal::{
+    format_wei, strategy_id, Action, GuestInput, Journal, Reason, StrategyParams, EXIT_INVALID_INPUT, INPUT_FORMAT_ABI,
+    INPUT_FORMAT_JSON,
+};
+
+#[test]
+fn test_trading_signal_elf_is_present_and_loads() {
+    // Guards against a build that embeds an empty or unloadable guest: the smallest valid input,
+    // a JSON current price with everything else defaulted, must run to completion
+    assert!(!TRADING_SIGNAL_ELF.is_empty(), "The guest ELF should be embedded");
+
+    let input = [vec![INPUT_FORMAT_JSON], br#"{"current_price": "3200"}"#.to_vec()].concat();
+    let env = ExecutorEnv::builder().write_slice(&input).build().unwrap();
+    let session_info = default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap();
+    assert_eq!(session_info.exit_code, ExitCode::Halted(0), "The guest should halt normally");
+    assert!(!session_info.journal.bytes.is_empty(), "The guest should commit a journal");
+}
 
 #[test]
 fn test_trading_signal_upward_trend() {
@@ -24,7 +41,7 @@ fn test_trading_signal_upward_trend() {
     let current_price = U256::from(3600000000000000000u64); // 3.6 ETH in wei
 
     let env = ExecutorEnv::builder()
-        .write_slice(&current_price.abi_encode())
+        .write_slice(&GuestInput { current_price, ..Default::default() }.encode())
         .build()
         .unwrap();
 
@@ -32,16 +49,16 @@ fn test_trading_signal_upward_trend() {
     let session_info = default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap();
 
     let output: (U256, U256, U256) = <(U256, U256, U256)>::abi_decode(&session_info.journal.bytes).unwrap();
-    let signal = output.0.as_limbs()[0] as u8;
-    let confidence = output.1.as_limbs()[0];
-    let predicted_price = output.2.as_limbs()[0];
+    let signal = output.0.to::<u8>();
+    let confidence = output.1.to::<u64>();
+    let predicted_price = output.2.to::<u64>();
 
     println!(
-        "Signal: {}, Confidence: {}%, Predicted: {} wei ({:.2} ETH)", 
+        "Signal: {}, Confidence: {}%, Predicted: {} wei ({} ETH)", 
         if signal == 1 { "BUY" } else { "SELL" },
         confidence,
         predicted_price,
-        predicted_price as f64 / 1e18
+        format_wei(U256::from(predicted_price), 18)
     );
 
     // The historical data shows an upward trend, so with a lower current price,
@@ -58,23 +75,23 @@ fn test_trading_signal_flat_market() {
     let current_price = U256::from(3750000000000000000u64); // 3.75 ETH in wei (close to trend end)
 
     let env = ExecutorEnv::builder()
-        .write_slice(&current_price.abi_encode())
+        .write_slice(&GuestInput { current_price, ..Default::default() }.encode())
         .build()
         .unwrap();
 
     let session_info = default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap();
 
     let output: (U256, U256, U256) = <(U256, U256, U256)>::abi_decode(&session_info.journal.bytes).unwrap();
-    let signal = output.0.as_limbs()[0] as u8;
-    let confidence = output.1.as_limbs()[0];
-    let predicted_price = output.2.as_limbs()[0];
+    let signal = output.0.to::<u8>();
+    let confidence = output.1.to::<u64>();
+    let predicted_price = output.2.to::<u64>();
 
     println!(
-        "Signal: {}, Confidence: {}%, Predicted: {} wei ({:.2} ETH)", 
+        "Signal: {}, Confidence: {}%, Predicted: {} wei ({} ETH)", 
         if signal == 1 { "BUY" } else { "SELL" },
         confidence,
         predicted_price,
-        predicted_price as f64 / 1e18
+        format_wei(U256::from(predicted_price), 18)
     );
 
     // With current price close to predicted, and only small upward movement expected,
@@ -91,33 +108,33 @@ fn test_trading_signal_high_current_price() {
     let current_price = U256::from(5000000000000000000u64); // 5.0 ETH in wei (much higher than 3.7 trend)
 
     let env = ExecutorEnv::builder()
-        .write_slice(&current_price.abi_encode())
+        .write_slice(&GuestInput { current_price, ..Default::default() }.encode())
         .build()
         .unwrap();
 
     let session_info = default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap();
 
     let output: (U256, U256, U256) = <(U256, U256, U256)>::abi_decode(&session_info.journal.bytes).unwrap();
-    let signal = output.0.as_limbs()[0] as u8;
-    let confidence = output.1.as_limbs()[0];
-    let predicted_price = output.2.as_limbs()[0];
+    let signal = output.0.to::<u8>();
+    let confidence = output.1.to::<u64>();
+    let predicted_price = output.2.to::<u64>();
 
     println!(
-        "Signal: {}, Confidence: {}%, Predicted: {} wei ({:.2} ETH)", 
+        "Signal: {}, Confidence: {}%, Predicted: {} wei ({} ETH)", 
         if signal == 1 { "BUY" } else { "SELL" },
         confidence,
         predicted_price,
-        predicted_price as f64 / 1e18
+        format_wei(U256::from(predicted_price), 18)
     );
 
     // The algorithm might still predict higher prices based on the upward trend in data
     // So instead of asserting SELL, let's just verify the logic is consistent
-    println!("Current price: {:.2} ETH, Predicted: {:.2} ETH", 
-             current_price.as_limbs()[0] as f64 / 1e18, 
-             predicted_price as f64 / 1e18);
+    println!("Current price: {} ETH, Predicted: {} ETH", 
+             format_wei(current_price, 18), 
+             format_wei(U256::from(predicted_price), 18));
     
     // The signal logic is: BUY if predicted > current * 1.005, otherwise SELL
-    let current_eth = current_price.as_limbs()[0];
+    let current_eth = current_price.to::<u64>();
     let threshold = current_eth + (current_eth / 200);
     let expected_signal = if predicted_price > threshold { 1 } else { 0 };
     assert_eq!(signal, expected_signal, "Signal should match the algorithm logic");
@@ -131,7 +148,7 @@ fn test_trading_signal_output_format() {
     let current_price = U256::from(3700000000000000000u64); // 3.7 ETH in wei
 
     let env = ExecutorEnv::builder()
-        .write_slice(&current_price.abi_encode())
+        .write_slice(&GuestInput { current_price, ..Default::default() }.encode())
         .build()
         .unwrap();
 
@@ -139,9 +156,9 @@ fn test_trading_signal_output_format() {
 
     // Test that we can decode the output correctly
     let output: (U256, U256, U256) = <(U256, U256, U256)>::abi_decode(&session_info.journal.bytes).unwrap();
-    let signal = output.0.as_limbs()[0] as u8;
-    let confidence = output.1.as_limbs()[0];
-    let predicted_price = output.2.as_limbs()[0];
+    let signal = output.0.to::<u8>();
+    let confidence = output.1.to::<u64>();
+    let predicted_price = output.2.to::<u64>();
 
     // Validate output constraints
     assert!(signal == 0 || signal == 1, "Signal must be 0 or 1");
@@ -153,4 +170,1486 @@ fn test_trading_signal_output_format() {
         "Output validation passed - Signal: {}, Confidence: {}%, Predicted: {} wei", 
         signal, confidence, predicted_price
     );
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_trading_signal_rejects_oversized_history() {
+    // The guest refuses histories beyond its compiled-in cap, whatever the host allows
+    let current_price = U256::from(3200u64);
+    let history: Vec<(u64, u64)> = (1..=1025).map(|day| (day, 3200)).collect();
+
+    let env = ExecutorEnv::builder()
+        .write_slice(&GuestInput { current_price, history, ..Default::default() }.encode())
+        .build()
+        .unwrap();
+
+    let session_info = default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap();
+    assert_eq!(
+        session_info.exit_code,
+        ExitCode::Halted(EXIT_INVALID_INPUT as u32),
+        "Guest should reject a history longer than its hard cap"
+    );
+}
+
+#[test]
+fn test_trading_signal_exits_cleanly_on_invalid_input() {
+    // Undecodable input halts with a dedicated exit code and no journal instead of panicking
+    let garbage_abi = [vec![INPUT_FORMAT_ABI], vec![0xde, 0xad, 0xbe, 0xef, 0x01, 0x02, 0x03]].concat();
+    let garbage_json = [vec![INPUT_FORMAT_JSON], b"{\"current_price\": ".to_vec()].concat();
+    for input in [garbage_abi, garbage_json, vec![7u8, 0, 0], Vec::new()] {
+        let env = ExecutorEnv::builder().write_slice(&input).build().unwrap();
+        let session_info = default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap();
+        assert_eq!(
+            session_info.exit_code,
+            ExitCode::Halted(EXIT_INVALID_INPUT as u32),
+            "Invalid input {:?} should halt with the invalid input exit code",
+            input
+        );
+        assert!(session_info.journal.bytes.is_empty(), "Invalid input should commit nothing");
+    }
+}
+
+#[test]
+fn test_trading_signal_exits_cleanly_on_out_of_range_parameters() {
+    // Decodable input with a parameter beyond its supported range halts the same way as garbage
+    let history: Vec<(u64, u64)> = (1..=20).map(|day| (day, 3000 + 10 * day)).collect();
+    let base = GuestInput { current_price: U256::from(3200u64), history, ..Default::default() };
+    for input in [
+        GuestInput { models: 0xff, ..base.clone() },
+        GuestInput { prior_weight: 101, ..base.clone() },
+        GuestInput { min_r2: 101, ..base.clone() },
+        GuestInput { decimals: 37, ..base.clone() },
+        GuestInput { vote_windows: 20, ..base.clone() },
+    ] {
+        let env = ExecutorEnv::builder().write_slice(&input.encode()).build().unwrap();
+        let session_info = default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap();
+        assert_eq!(
+            session_info.exit_code,
+            ExitCode::Halted(EXIT_INVALID_INPUT as u32),
+            "Out-of-range input should halt with the invalid input exit code"
+        );
+        assert!(session_info.journal.bytes.is_empty(), "Rejected input should commit nothing");
+    }
+}
+
+#[test]
+fn test_trading_signal_commits_input_hash() {
+    // The journal must carry the keccak256 of the exact series that was supplied
+    let current_price = U256::from(3200u64);
+    let history: Vec<(u64, u64)> = vec![(1, 3100), (2, 3150), (3, 3125), (4, 3190), (5, 3240)];
+    let expected_hash = keccak256(history.abi_encode());
+
+    let env = ExecutorEnv::builder()
+        .write_slice(&GuestInput { current_price, history, ..Default::default() }.encode())
+        .build()
+        .unwrap();
+
+    let session_info = default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap();
+
+    let output: (U256, U256, U256, B256) = <(U256, U256, U256, B256)>::abi_decode(&session_info.journal.bytes).unwrap();
+    assert_eq!(session_info.journal.bytes.len(), 768, "Journal should be twenty-four ABI words");
+    assert_eq!(output.3, expected_hash, "Committed input hash should match the supplied series");
+}
+
+#[test]
+fn test_trading_signal_commits_last_price() {
+    // The last point of the supplied series is committed as the baseline of the prediction, even
+    // when it is not the highest
+    let history: Vec<(u64, u64)> = vec![(1, 3100), (2, 3150), (3, 3125), (4, 3190), (5, 3170)];
+    let env = ExecutorEnv::builder()
+        .write_slice(
+            &GuestInput {
+                current_price: U256::from(3200u64),
+                history: history.clone(),
+                ..Default::default()
+            }
+            .encode(),
+        )
+        .build()
+        .unwrap();
+    let session_info = default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap();
+
+    let Journal::V10(journal) = Journal::decode(&session_info.journal.bytes).unwrap() else {
+        panic!("Journal should decode as the latest layout");
+    };
+    assert_eq!(journal.base.last_price, history[history.len() - 1].1, "The last price should be the final element of the series");
+    assert_eq!(U256::from_be_slice(&session_info.journal.bytes[672..704]), U256::from(3170u64));
+}
+
+#[test]
+fn test_trading_signal_commits_requested_journal_version() {
+    // Version 1 keeps only the words the contract reads, followed by the version word
+    let current_price = U256::from(3200u64);
+    let history: Vec<(u64, u64)> = vec![(1, 3100), (2, 3150), (3, 3125), (4, 3190), (5, 3240)];
+
+    let run = |journal_version: u64| {
+        let env = ExecutorEnv::builder()
+            .write_slice(
+                &GuestInput {
+                    current_price,
+                    history: history.clone(),
+                    journal_version,
+                    ..Default::default()
+                }
+                .encode(),
+            )
+            .build()
+            .unwrap();
+        default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap().journal.bytes
+    };
+
+    let v1 = run(1);
+    let latest = run(0);
+    assert_eq!(v1.len(), 160, "Version 1 journal should be five ABI words");
+    assert_eq!(U256::from_be_slice(&v1[128..]), U256::from(1), "Version 1 journal should end with its version");
+    assert_eq!(U256::from_be_slice(&latest[736..]), U256::from(10), "Version 0 should select the latest layout");
+    assert_eq!(v1[..128], latest[..128], "Both layouts should share the words the contract decodes");
+}
+
+#[test]
+fn test_trading_signal_ohlc_typical_price_regression() {
+    // The typical prices (high + low + close) / 3 are 3010, 3020, 3040, 3050 and 3070. By hand,
+    // around x̄ = 3 and ȳ = 3038: Sxy = 150 and Sxx = 10, so the slope is 15 and the intercept
+    // 3038 - 45 = 2993, which predicts 2993 + 6 * 15 = 3083 for day 6. The closes alone would
+    // predict 3091.
+    let candles: Vec<(u64, u64, u64, u64, u64)> = vec![
+        (1, 3000, 3040, 2990, 3000),
+        (2, 3000, 3050, 2980, 3030),
+        (3, 3030, 3060, 3010, 3050),
+        (4, 3050, 3090, 3020, 3040),
+        (5, 3040, 3100, 3030, 3080),
+    ];
+    let expected_hash = keccak256(candles.abi_encode());
+    let env = ExecutorEnv::builder()
+        .write_slice(&GuestInput { current_price: U256::from(3000u64), candles, ..Default::default() }.encode())
+        .build()
+        .unwrap();
+    let session_info = default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap();
+    let (signal, confidence, predicted_price, input_hash) =
+        <(U256, U256, U256, B256)>::abi_decode(&session_info.journal.bytes[..128]).unwrap();
+
+    assert_eq!(predicted_price, U256::from(3083u64), "The regression should fit the typical prices");
+    assert_eq!(confidence, U256::from(98u64), "Confidence should be the R² of the typical prices");
+    assert_eq!(signal, U256::from(1u64), "A prediction above the current price should signal BUY");
+    assert_eq!(input_hash, expected_hash, "The candles should be hashed in place of the history");
+}
+
+#[test]
+fn test_trading_signal_holt_one_step_prediction() {
+    // By hand, with alpha = beta = 50%: the level starts at 3000 and the trend at 100
+    //   3100: forecast 3100, level 3100, trend 100
+    //   3250: forecast 3200, level 3225, trend 112.5
+    //   3300: forecast 3337.5, level 3318.75, trend 103.125
+    // so the next day is forecast at 3421.875. The forecast errors (0, 50, -37.5) against a
+    // variance of 56875 leave 93% confidence.
+    let history: Vec<(u64, u64)> = vec![(1, 3000), (2, 3100), (3, 3250), (4, 3300)];
+    let env = ExecutorEnv::builder()
+        .write_slice(
+            &GuestInput {
+                current_price: U256::from(3300u64),
+                history,
+                models: 0b1000,
+                holt_alpha: 50,
+                holt_beta: 50,
+                ..Default::default()
+            }
+            .encode(),
+        )
+        .build()
+        .unwrap();
+    let session_info = default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap();
+    let (signal, confidence, predicted_price) = <(U256, U256, U256)>::abi_decode(&session_info.journal.bytes[..96]).unwrap();
+
+    assert_eq!(predicted_price, U256::from(3421u64), "Holt should forecast the hand-calculated price");
+    assert_eq!(confidence, U256::from(93u64), "Confidence should follow the in-sample error");
+    assert_eq!(signal, U256::from(1u64), "A forecast above the current price should signal BUY");
+}
+
+#[test]
+fn test_trading_signal_basis_point_confidence() {
+    // The choppy series of the min R² test fits with an R² just above 43%: basis points keep the
+    // fraction a percentage truncates away
+    let current_price = U256::from(3100u64);
+    let history: Vec<(u64, u64)> = (1..=20).map(|day| (day, 3000 + 10 * day + day % 2 * 120)).collect();
+    let run = |raw_prediction: bool, bps_confidence: bool| {
+        let env = ExecutorEnv::builder()
+            .write_slice(
+                &GuestInput {
+                    current_price,
+                    history: history.clone(),
+                    raw_prediction,
+                    bps_confidence,
+                    ..Default::default()
+                }
+                .encode(),
+            )
+            .build()
+            .unwrap();
+        default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap().journal.bytes
+    };
+
+    let percent = <(U256, U256)>::abi_decode(&run(true, false)).unwrap().1.to::<u64>();
+    let bps = <(U256, U256)>::abi_decode(&run(true, true)).unwrap().1.to::<u64>();
+    assert_eq!(percent, 43, "Raw confidence should default to a percentage");
+    assert_eq!(bps / 100, percent, "Basis-point confidence should be the percentage with more digits");
+    assert_ne!(bps, percent * 100, "Basis points should keep the fraction of a percent");
+
+    // The full journal keeps the percentage in the word the contract reads and adds the basis points
+    let journal = run(false, true);
+    assert_eq!(U256::from_be_slice(&journal[32..64]), U256::from(percent), "The contract word should stay a percentage");
+    assert_eq!(U256::from_be_slice(&journal[544..576]), U256::from(bps), "Version 5 should commit the basis points");
+    let journal = run(false, false);
+    assert_eq!(U256::from_be_slice(&journal[544..576]), U256::from(percent * 100), "Without the flag the basis points are whole percent");
+}
+
+#[test]
+fn test_trading_signal_absolute_price_targets() {
+    // A steady rise predicts $3204 for the next day, the mean day truncating to 10; the targets
+    // replace the 50 bps bands
+    let history: Vec<(u64, u64)> = (1..=20).map(|day| (day, 3000 + 10 * day)).collect();
+    let run = |current_price: u64, buy_above_usd: u64, sell_below_usd: u64| {
+        let env = ExecutorEnv::builder()
+            .write_slice(
+                &GuestInput {
+                    current_price: U256::from(current_price),
+                    history: history.clone(),
+                    buy_bps: 50,
+                    sell_bps: 50,
+                    buy_above_usd,
+                    sell_below_usd,
+                    ..Default::default()
+                }
+                .encode(),
+            )
+            .build()
+            .unwrap();
+        let journal = default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap().journal.bytes;
+        let (signal, _, predicted_price) = <(U256, U256, U256)>::abi_decode(&journal[..96]).unwrap();
+        assert_eq!(predicted_price, U256::from(3204u64));
+        signal.to::<u64>()
+    };
+
+    assert_eq!(run(3200, 0, 0), 2, "A 12 bps move should stay within the bands");
+    assert_eq!(run(3200, 3200, 0), 1, "A prediction above the BUY target should BUY");
+    assert_eq!(run(3100, 3250, 0), 2, "A prediction below the BUY target should not BUY, whatever the band");
+    assert_eq!(run(3100, 3300, 3220), 0, "A prediction below the SELL target should SELL");
+}
+
+#[test]
+fn test_trading_signal_rounding_modes() {
+    // Over 22 steady days the regression predicts $3223, the SMA crossover $3275, the median $3115
+    // and Holt $3230; three of them average to a fraction of a dollar
+    let history: Vec<(u64, u64)> = (1..=22).map(|day| (day, 3000 + 10 * day)).collect();
+    let run = |models: u64, rounding: u64| {
+        let env = ExecutorEnv::builder()
+            .write_slice(
+                &GuestInput {
+                    current_price: U256::from(3200u64),
+                    history: history.clone(),
+                    models,
+                    buy_bps: 50,
+                    sell_bps: 50,
+                    rounding,
+                    ..Default::default()
+                }
+                .encode(),
+            )
+            .build()
+            .unwrap();
+        let journal = default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap().journal.bytes;
+        U256::from_be_slice(&journal[64..96]).to::<u64>()
+    };
+
+    // $3204.33: only rounding up moves it
+    assert_eq!([run(0b0111, 0), run(0b0111, 1), run(0b0111, 2)], [3204, 3204, 3205]);
+    // $3242.67: only truncation keeps it
+    assert_eq!([run(0b1011, 0), run(0b1011, 1), run(0b1011, 2)], [3242, 3243, 3243]);
+}
+
+#[test]
+fn test_trading_signal_warmup() {
+    // Ten rising days put the fast average 160 bps above the slow one, but the slow average is
+    // meant to span 20 days
+    let history: Vec<(u64, u64)> = (1..=10).map(|day| (day, 3000 + 20 * day)).collect();
+    let run = |models: u64, warmup: bool| {
+        let env = ExecutorEnv::builder()
+            .write_slice(
+                &GuestInput {
+                    current_price: U256::from(3200u64),
+                    history: history.clone(),
+                    models,
+                    buy_bps: 50,
+                    sell_bps: 50,
+                    warmup,
+                    ..Default::default()
+                }
+                .encode(),
+            )
+            .build()
+            .unwrap();
+        let journal = default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap().journal.bytes;
+        (U256::from_be_slice(&journal[..32]).to::<u64>(), U256::from_be_slice(&journal[576..608]).to::<u8>())
+    };
+
+    assert_eq!(run(0b10, false), (1, Reason::None as u8), "Without warmup the crossover should BUY");
+    assert_eq!(run(0b10, true), (2, Reason::WarmingUp as u8), "The crossover should HOLD while warming up");
+    assert_eq!(run(0b01, true), (1, Reason::None as u8), "Ten points should warm up the regression");
+}
+
+#[test]
+fn test_trading_signal_predicted_return() {
+    // A steady rise predicts $3204 against $3150, in 6-decimal units: a 171.43 bps return
+    let history: Vec<(u64, u64)> = (1..=20).map(|day| (day, 3000 + 10 * day)).collect();
+    let current_price = U256::from(3_150_000_000u64);
+    let run = |predicted_return: bool| {
+        let env = ExecutorEnv::builder()
+            .write_slice(
+                &GuestInput {
+                    current_price,
+                    history: history.clone(),
+                    buy_bps: 50,
+                    sell_bps: 50,
+                    decimals: 6,
+                    predicted_return,
+                    ..Default::default()
+                }
+                .encode(),
+            )
+            .build()
+            .unwrap();
+        default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap().journal.bytes
+    };
+
+    let journal = run(true);
+    let predicted_price = U256::from_be_slice(&journal[64..96]);
+    let return_bps = I256::from_be_bytes::<32>(journal[608..640].try_into().unwrap());
+    assert_eq!(predicted_price, U256::from(3_204_000_000u64));
+    let expected = (I256::from_raw(predicted_price) - I256::from_raw(current_price)) * I256::try_from(10_000).unwrap() / I256::from_raw(current_price);
+    assert_eq!(return_bps, expected, "The return should match the committed prices");
+    assert_eq!(return_bps, I256::try_from(171).unwrap());
+    assert_eq!(I256::from_be_bytes::<32>(run(false)[608..640].try_into().unwrap()), I256::ZERO, "Without the flag the return is zero");
+}
+
+#[test]
+fn test_trading_signal_predicted_return_saturates() {
+    // Two wei against a prediction of $3204 in 18-decimal units is a return far beyond i64
+    let history: Vec<(u64, u64)> = (1..=20).map(|day| (day, 3000 + 10 * day)).collect();
+    let env = ExecutorEnv::builder()
+        .write_slice(
+            &GuestInput { current_price: U256::from(2u64), history, decimals: 18, predicted_return: true, ..Default::default() }
+                .encode(),
+        )
+        .build()
+        .unwrap();
+    let journal = default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap().journal.bytes;
+    let return_bps = I256::from_be_bytes::<32>(journal[608..640].try_into().unwrap());
+    assert_eq!(return_bps, I256::try_from(i64::MAX).unwrap(), "The return should saturate instead of failing the run");
+}
+
+#[test]
+fn test_trading_signal_commits_params_hash() {
+    // The committed hash covers the strategy inputs as they were written to stdin, zeroes included
+    let history: Vec<(u64, u64)> = (1..=20).map(|day| (day, 3000 + 10 * day)).collect();
+    let env = ExecutorEnv::builder()
+        .write_slice(
+            &GuestInput {
+                current_price: U256::from(3150u64),
+                history,
+                outlier_k: 2,
+                models: 0b1001,
+                prior_slope: -5,
+                prior_weight: 20,
+                buy_bps: 120,
+                sell_bps: 80,
+                clamp_factor: 3,
+                seed: 7,
+                require_agreement: true,
+                min_r2: 10,
+                ensemble_confidence: 1,
+                holt_alpha: 40,
+                buy_above_usd: 3300,
+                sell_below_usd: 3000,
+                rounding: 1,
+                warmup: true,
+                ..Default::default()
+            }
+            .encode(),
+        )
+        .build()
+        .unwrap();
+    let journal = default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap().journal.bytes;
+
+    let params = StrategyParams {
+        models: 0b1001,
+        outlier_k: 2,
+        prior_slope: -5,
+        prior_weight: 20,
+        buy_bps: 120,
+        sell_bps: 80,
+        clamp_factor: 3,
+        seed: 7,
+        bootstrap_samples: 0,
+        require_agreement: true,
+        min_r2: 10,
+        percentile_window: 0,
+        ensemble_confidence: 1,
+        holt_alpha: 40,
+        holt_beta: 0,
+        buy_above_usd: 3300,
+        sell_below_usd: 3000,
+        rounding: 1,
+        warmup: true,
+        residual_window: 0,
+        vote_windows: 0,
+        max_confidence: 0,
+        horizons: 0,
+        half_life_secs: 0,
+    };
+    assert_eq!(journal[640..672], params.hash()[..], "The params hash should match the stdin inputs");
+    let Journal::V10(decoded) = Journal::decode(&journal).unwrap() else {
+        panic!("Journal should decode as the latest layout");
+    };
+    assert_eq!(decoded.base.base.params_hash, params.hash());
+    let other = StrategyParams { buy_bps: 121, ..params };
+    assert_ne!(decoded.base.base.params_hash, other.hash(), "Another threshold should hash differently");
+}
+
+#[test]
+fn test_trading_signal_residual_window_confidence() {
+    // A rise whose first ten days swing $160 and whose last ten are a steady $10/day: the line fits
+    // the whole series with an R² of 56% but the last ten points with one of 96%
+    let history: Vec<(u64, u64)> = (1..=20).map(|day| {
+        let offset = match day {
+            11.. => 75,
+            _ if day % 2 == 0 => 150,
+            _ => 0,
+        };
+        (day, 3000 + 10 * day + offset)
+    }).collect();
+    let run = |residual_window: u64| {
+        let env = ExecutorEnv::builder()
+            .write_slice(
+                &GuestInput {
+                    current_price: U256::from(3200u64),
+                    history: history.clone(),
+                    buy_bps: 50,
+                    sell_bps: 50,
+                    residual_window,
+                    ..Default::default()
+                }
+                .encode(),
+            )
+            .build()
+            .unwrap();
+        let journal = default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap().journal.bytes;
+        <(U256, U256)>::abi_decode(&journal[..64]).unwrap()
+    };
+
+    assert_eq!(run(0), (U256::from(1), U256::from(56)), "The whole series should fit poorly");
+    assert_eq!(run(10), (U256::from(1), U256::from(96)), "The calm tail should be trusted");
+    assert_eq!(run(20), run(0), "A window over the whole series should match its R²");
+}
+
+#[test]
+fn test_trading_signal_vote_windows_commit_the_majority() {
+    // A steady $100/day rise that collapses to $2300 on the last day: the whole series fits a
+    // falling line that predicts $2790 and SELLs, while the two windows that end before the
+    // collapse still fit rising lines and BUY. Voting over the three commits BUY with the two of
+    // three windows that agree as its confidence, and keeps the prediction of the whole series.
+    // Two windows tie between SELL and BUY and HOLD, which the vote rather than the models decided.
+    let history: Vec<(u64, u64)> = vec![(1, 3000), (2, 3100), (3, 3200), (4, 3300), (5, 3400), (6, 2300)];
+    let run = |vote_windows: u64| {
+        let env = ExecutorEnv::builder()
+            .write_slice(
+                &GuestInput {
+                    current_price: U256::from(3000u64),
+                    history: history.clone(),
+                    buy_bps: 50,
+                    sell_bps: 50,
+                    vote_windows,
+                    ..Default::default()
+                }
+                .encode(),
+            )
+            .build()
+            .unwrap();
+        let journal = default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap().journal.bytes;
+        let (signal, confidence, predicted_price) = <(U256, U256, U256)>::abi_decode(&journal[..96]).unwrap();
+        (signal, confidence, predicted_price, U256::from_be_slice(&journal[576..608]))
+    };
+
+    let (signal, confidence, predicted_price, reason) = run(0);
+    assert_eq!(signal, U256::from(0u64), "The whole series alone should SELL");
+    assert_eq!(reason, U256::from(Reason::None as u8));
+    assert_eq!(confidence, U256::from(10u64), "Confidence should be the R² of the whole series");
+    assert_eq!(predicted_price, U256::from(2790u64));
+
+    let (signal, confidence, predicted_price, reason) = run(3);
+    assert_eq!(signal, U256::from(1u64), "Two of three windows BUY, so the vote should BUY");
+    assert_eq!(reason, U256::from(Reason::None as u8));
+    assert_eq!(confidence, U256::from(66u64), "Confidence should be the share of agreeing windows");
+    assert_eq!(predicted_price, U256::from(2790u64), "The prediction should stay the whole series'");
+    assert_eq!(run(1), run(0), "A single window should be the whole series alone");
+
+    let (signal, _, _, reason) = run(2);
+    assert_eq!(signal, U256::from(2u64), "A tied vote should HOLD");
+    assert_eq!(reason, U256::from(Reason::WindowsOutvoted as u8), "The HOLD should be put down to the vote");
+}
+
+#[test]
+fn test_trading_signal_max_confidence_caps_a_perfect_fit() {
+    // A perfectly linear rise to $3200 fits with an R² of 100% and predicts $3210, a BUY from $3100;
+    // the cap holds the confidence at 95% without touching the signal
+    let history: Vec<(u64, u64)> = (1..=20).map(|day| (day, 3000 + 10 * day)).collect();
+    let run = |max_confidence: u64| {
+        let env = ExecutorEnv::builder()
+            .write_slice(
+                &GuestInput {
+                    current_price: U256::from(3100u64),
+                    history: history.clone(),
+                    buy_bps: 50,
+                    sell_bps: 50,
+                    max_confidence,
+                    ..Default::default()
+                }
+                .encode(),
+            )
+            .build()
+            .unwrap();
+        let journal = default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap().journal.bytes;
+        <(U256, U256)>::abi_decode(&journal[..64]).unwrap()
+    };
+
+    assert_eq!(run(0), (U256::from(1u64), U256::from(100u64)), "Without a cap a perfect fit should be certain");
+    assert_eq!(run(95), (U256::from(1u64), U256::from(95u64)), "The confidence should be capped at 95%");
+    assert_eq!(run(100), run(0), "A cap of 100% should change nothing");
+}
+
+#[test]
+fn test_trading_signal_horizons_must_agree() {
+    // A price above a rising trend: the line is $30 under it tomorrow but $30 over it a week out,
+    // so the next-day SELL does not hold across horizons
+    let history: Vec<(u64, u64)> = (1..=21).map(|day| (day, 3000 + 10 * day)).collect();
+    let run = |horizons: u64| {
+        let env = ExecutorEnv::builder()
+            .write_slice(
+                &GuestInput {
+                    current_price: U256::from(3250u64),
+                    history: history.clone(),
+                    buy_bps: 50,
+                    sell_bps: 50,
+                    horizons,
+                    ..Default::default()
+                }
+                .encode(),
+            )
+            .build()
+            .unwrap();
+        default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap().journal.bytes
+    };
+
+    let journal = run(0);
+    assert_eq!(journal.len(), 768, "Without horizons the journal should keep the latest layout");
+    assert_eq!(U256::from_be_slice(&journal[..32]), U256::from(0u64), "The next day alone should SELL");
+
+    // Horizons 1, 3 and 7
+    let journal = run(0b100_0101);
+    assert_eq!(U256::from_be_slice(&journal[..32]), U256::from(2u64), "Disagreeing horizons should HOLD");
+    assert_eq!(U256::from_be_slice(&journal[576..608]), U256::from(Reason::HorizonsDisagree as u8));
+    let Journal::V12(decoded) = Journal::decode(&journal).unwrap() else {
+        panic!("Journal should decode as the horizon layout");
+    };
+    assert_eq!(decoded.horizons, [(1, Action::Sell), (3, Action::Hold), (7, Action::Buy)]);
+    assert!(decoded.base.residuals.is_empty(), "No residuals were asked for");
+}
+
+#[test]
+fn test_trading_signal_time_decay_weighs_recent_points() {
+    // The same ten days rise to $3400 and fall back to $3150. Fetched ten minutes apart they all
+    // count alike and the fit still rises; with the rise fetched a week earlier, a 6-hour half-life
+    // leaves only the fall to fit, which it does perfectly even though the plain fit of every
+    // point has an R² of 11%.
+    let history: Vec<(u64, u64)> = (1..=10).map(|day| (day, if day <= 5 { 2900 + 100 * day } else { 3650 - 50 * day })).collect();
+    let now = 1_700_000_000u64;
+    let recent: Vec<u64> = (1..=10).map(|day| now - (10 - day) * 600).collect();
+    let stale: Vec<u64> = recent.iter().enumerate().map(|(i, t)| if i < 5 { t - 7 * 86_400 } else { *t }).collect();
+    let run = |timestamps: Vec<u64>, min_r2: u64| {
+        let env = ExecutorEnv::builder()
+            .write_slice(
+                &GuestInput {
+                    current_price: U256::from(3200u64),
+                    history: history.clone(),
+                    buy_bps: 50,
+                    sell_bps: 50,
+                    min_r2,
+                    timestamps,
+                    half_life_secs: 21_600,
+                    ..Default::default()
+                }
+                .encode(),
+            )
+            .build()
+            .unwrap();
+        let journal = default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap().journal.bytes;
+        let Journal::V10(decoded) = Journal::decode(&journal).unwrap() else {
+            panic!("Journal should decode as the latest layout");
+        };
+        let v2 = decoded.base.base.base.base.base.base.base.base;
+        (v2.slope, v2.signal, v2.input_hash)
+    };
+
+    let (recent_slope, recent_signal, recent_hash) = run(recent.clone(), 0);
+    let (stale_slope, stale_signal, stale_hash) = run(stale.clone(), 0);
+    assert_eq!(recent_slope, 12, "Points fetched minutes apart should weigh about the same");
+    assert_eq!(stale_slope, -50, "Week-old points should barely count");
+    assert_eq!((recent_signal, stale_signal), (Action::Buy, Action::Sell));
+    assert_eq!(recent_hash, keccak256((history.clone(), recent.clone()).abi_encode()), "The timestamps should be hashed with the prices");
+    assert_ne!(recent_hash, stale_hash);
+    assert_eq!(run(stale, 50).1, Action::Sell, "The R² gate should judge the weighted fit");
+    assert_eq!(run(recent, 50).1, Action::Hold, "Evenly weighted, the fit is as poor as the plain one");
+}
+
+#[test]
+fn test_trading_signal_strategy_id_covers_every_param() {
+    let history: Vec<(u64, u64)> = (1..=20).map(|day| (day, 3000 + 10 * day)).collect();
+    let image_id = B256::repeat_byte(0x11);
+    let run = |image_id: B256, params: &str| {
+        let json = format!(
+            r#"{{"current_price": "3190", "history": {:?}, "image_id": "{}"{}}}"#,
+            history.iter().map(|&(day, price)| [day, price]).collect::<Vec<_>>(),
+            image_id,
+            params
+        );
+        let mut input = vec![INPUT_FORMAT_JSON];
+        input.extend(json.as_bytes());
+        let env = ExecutorEnv::builder().write_slice(&input).build().unwrap();
+        let journal = default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap().journal.bytes;
+        let Journal::V13(decoded) = Journal::decode(&journal).unwrap() else {
+            panic!("Journal should decode as the strategy ID layout");
+        };
+        assert_eq!(journal[journal.len() - 64..journal.len() - 32], decoded.strategy_id[..]);
+        decoded.strategy_id
+    };
+
+    let base = run(image_id, "");
+    assert_eq!(base, strategy_id(image_id, StrategyParams::default().hash()));
+    assert_eq!(run(image_id, ""), base, "The same strategy should commit the same ID");
+    assert_ne!(run(B256::repeat_byte(0x12), ""), base, "Another image should commit another ID");
+
+    let mut ids = vec![base];
+    for params in [
+        r#", "models": 3"#,
+        r#", "outlier_k": 2"#,
+        r#", "prior_slope": -5"#,
+        r#", "prior_weight": 20"#,
+        r#", "buy_bps": 120"#,
+        r#", "sell_bps": 80"#,
+        r#", "clamp_factor": 3"#,
+        r#", "seed": 7"#,
+        r#", "min_r2": 10"#,
+        r#", "percentile_window": 10"#,
+        r#", "holt_alpha": 40"#,
+        r#", "rounding": 1"#,
+        r#", "warmup": true"#,
+        r#", "max_confidence": 90"#,
+    ] {
+        let id = run(image_id, params);
+        assert!(!ids.contains(&id), "Changing {} should change the strategy ID", params);
+        ids.push(id);
+    }
+}
+
+#[test]
+fn test_trading_signal_theil_sen_resists_outlier() {
+    // A steady $10/day rise from $3010 with a bad $2500 tick on day 18: least squares flattens to
+    // $2/day and predicts $3093, while the median of the pairwise slopes stays on the clean trend
+    let mut history: Vec<(u64, u64)> = (1..=20).map(|day| (day, 3000 + 10 * day)).collect();
+    history[17].1 = 2500;
+    let run = |models: u64| {
+        let env = ExecutorEnv::builder()
+            .write_slice(
+                &GuestInput {
+                    current_price: U256::from(3150u64),
+                    history: history.clone(),
+                    models,
+                    buy_bps: 50,
+                    sell_bps: 50,
+                    ..Default::default()
+                }
+                .encode(),
+            )
+            .build()
+            .unwrap();
+        let journal = default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap().journal.bytes;
+        <(U256, U256, U256)>::abi_decode(&journal[..96]).unwrap()
+    };
+
+    let clean_prediction = 3000 + 10 * 21;
+    let (ols_signal, _, ols_prediction) = run(0b00001);
+    let (theil_sen_signal, theil_sen_confidence, theil_sen_prediction) = run(0b10000);
+    assert_eq!(ols_prediction, U256::from(3093));
+    assert_eq!(theil_sen_prediction, U256::from(clean_prediction), "Theil–Sen should predict the clean trend");
+    assert!(
+        theil_sen_prediction.abs_diff(U256::from(clean_prediction)) < ols_prediction.abs_diff(U256::from(clean_prediction)),
+        "Theil–Sen should land closer to the clean trend than least squares"
+    );
+    assert_eq!((ols_signal, theil_sen_signal), (U256::from(0), U256::from(1)), "Only least squares should be misled into a SELL");
+    // 17 of the 190 pairwise slopes, from the earlier days to the bad tick, point down
+    assert_eq!(theil_sen_confidence, U256::from(91));
+}
+
+#[test]
+fn test_trading_signal_commits_hold_reason() {
+    // Each way to a HOLD commits its own reason code, and a BUY or SELL commits none
+    let run = |current_price: u64, history: &[(u64, u64)], models: u64, thresholds_bps: u64, require_agreement: bool, min_r2: u64| {
+        let env = ExecutorEnv::builder()
+            .write_slice(
+                &GuestInput {
+                    current_price: U256::from(current_price),
+                    history: history.to_vec(),
+                    models,
+                    buy_bps: thresholds_bps,
+                    sell_bps: thresholds_bps,
+                    require_agreement,
+                    min_r2,
+                    ..Default::default()
+                }
+                .encode(),
+            )
+            .build()
+            .unwrap();
+        let journal = default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap().journal.bytes;
+        (U256::from_be_slice(&journal[..32]).to::<u64>(), U256::from_be_slice(&journal[576..608]).to::<u8>())
+    };
+
+    // A steady rise predicted at $3204, just below the current price, stays inside the thresholds
+    let steady: Vec<(u64, u64)> = (1..=20).map(|day| (day, 3000 + 10 * day)).collect();
+    assert_eq!(run(3210, &steady, 0, 50, false, 0), (2, Reason::DeadBand as u8), "A move inside the thresholds should be the dead band");
+    assert_eq!(run(3100, &steady, 0, 50, false, 0), (1, Reason::None as u8), "A BUY should commit no reason");
+
+    // The ensemble series of the disagreement test: the regression buys while the SMA crossover sells
+    let mut reversal: Vec<(u64, u64)> = (1..=20).map(|day| (day, 3000 + 20 * day)).collect();
+    reversal.extend((21..=25).map(|day| (day, 3100)));
+    assert_eq!(run(3000, &reversal, 0b11, 0, false, 0), (2, Reason::ModelsDisagree as u8), "Disagreeing models should say so");
+
+    // The series of the agreement gate test: the slope is up but the last change is down
+    let mut dipping = steady.clone();
+    dipping[19].1 = dipping[18].1 - 5;
+    assert_eq!(run(3100, &dipping, 0, 50, true, 0), (2, Reason::SlopeDisagrees as u8), "The agreement gate should say so");
+
+    // The choppy series of the min R² test fits with an R² of 43%
+    let choppy: Vec<(u64, u64)> = (1..=20).map(|day| (day, 3000 + 10 * day + day % 2 * 120)).collect();
+    assert_eq!(run(3100, &choppy, 0, 0, false, 60), (2, Reason::LowRSquared as u8), "The R² gate should say so");
+}
+
+#[test]
+fn test_trading_signal_holds_below_min_r2() {
+    // A rising but choppy series fits with an R² of 43%: BUY ungated, HOLD with a 60% minimum
+    let current_price = U256::from(3100u64);
+    let history: Vec<(u64, u64)> = (1..=20).map(|day| (day, 3000 + 10 * day + day % 2 * 120)).collect();
+    let run_models = |models: u64, min_r2: u64| {
+        let env = ExecutorEnv::builder()
+            .write_slice(&GuestInput { current_price, history: history.clone(), models, min_r2, ..Default::default() }.encode())
+            .build()
+            .unwrap();
+        let session_info = default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap();
+        let (signal, confidence) = <(U256, U256)>::abi_decode(&session_info.journal.bytes[..64]).unwrap();
+        (signal.to::<u64>(), confidence.to::<u64>())
+    };
+    let run = |min_r2: u64| run_models(0, min_r2);
+
+    assert_eq!(run(0).0, 1, "Ungated regression should signal BUY");
+    assert_eq!(run(40).0, 1, "An R² above the minimum should not be gated");
+    assert_eq!(run(60), (2, 43), "A poor fit should HOLD with the R² as confidence");
+    // Theil-Sen alone fits no regression line for the gate to judge
+    assert_eq!(run_models(0b10000, 60), run_models(0b10000, 0), "A signal without the regression should not be gated");
+}
+
+#[test]
+fn test_trading_signal_legacy_encoding_matches_abi_encoding() {
+    // The hand-built legacy journal and the versioned abi_encode journal carry the same values
+    let current_price = U256::from(3200u64);
+    let history: Vec<(u64, u64)> = (1..=20).map(|day| (day, 3000 + 10 * day + day % 3 * 15)).collect();
+    let run = |legacy_encoding: bool| {
+        let env = ExecutorEnv::builder()
+            .write_slice(
+                &GuestInput {
+                    current_price,
+                    history: history.clone(),
+                    report: true,
+                    legacy_encoding,
+                    ..Default::default()
+                }
+                .encode(),
+            )
+            .build()
+            .unwrap();
+        default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap().journal.bytes
+    };
+
+    let legacy = run(true);
+    let versioned = run(false);
+    assert_eq!(legacy.len(), 448, "Legacy journal should be fourteen ABI words with no version");
+    let Journal::Legacy(legacy) = Journal::decode(&legacy).unwrap() else {
+        panic!("Legacy journal should decode as the legacy layout");
+    };
+    let Journal::V4(versioned) = Journal::decode(&versioned).unwrap() else {
+        panic!("Versioned journal should decode as the latest layout");
+    };
+    assert_eq!(legacy, versioned.base.base, "Both encodings should decode to the same values");
+}
+
+#[test]
+fn test_trading_signal_splits_confidence_by_direction() {
+    // The predicted direction carries the confidence and the opposite one the rest
+    let run = |current_price: u64, history: Vec<(u64, u64)>| {
+        let env = ExecutorEnv::builder()
+            .write_slice(&GuestInput { current_price: U256::from(current_price), history, ..Default::default() }.encode())
+            .build()
+            .unwrap();
+        let journal = default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap().journal.bytes;
+        let (signal, confidence) = <(U256, U256)>::abi_decode(&journal[..64]).unwrap();
+        let (up, down) = <(U256, U256)>::abi_decode(&journal[448..512]).unwrap();
+        (signal.to::<u64>(), confidence.to::<u64>(), up.to::<u64>(), down.to::<u64>())
+    };
+
+    // Noisy trends so that neither direction is certain
+    let (signal, confidence, up, down) = run(3100, (1..=20).map(|day| (day, 3000 + 10 * day + day % 3 * 15)).collect());
+    assert_eq!(signal, 1, "Uptrend should signal BUY");
+    assert_eq!((up, down), (confidence, 100 - confidence), "BUY should be confident of the upside");
+    assert_eq!(up + down, 100, "Directional confidences should sum to 100");
+
+    let (signal, confidence, up, down) = run(3300, (1..=20).map(|day| (day, 3400 - 10 * day + day % 3 * 15)).collect());
+    assert_eq!(signal, 0, "Downtrend should signal SELL");
+    assert_eq!((up, down), (100 - confidence, confidence), "SELL should be confident of the downside");
+    assert_eq!(up + down, 100, "Directional confidences should sum to 100");
+}
+
+#[test]
+fn test_trading_signal_trims_outlier() {
+    // A single bad tick should be dropped when trimming is enabled, pulling the fit back to the
+    // clean trend. The prediction is the fitted line extrapolated one day, so it tracks the slope.
+    let current_price = U256::from(3200u64);
+    let clean: Vec<(u64, u64)> = (1..=20).map(|day| (day, 3000 + 10 * day)).collect();
+    let mut noisy = clean.clone();
+    noisy[9].1 = 6000; // Day 10 spikes to $6000
+
+    let run = |history: Vec<(u64, u64)>, outlier_k: u64| {
+        let env = ExecutorEnv::builder()
+            .write_slice(&GuestInput { current_price, history, outlier_k, ..Default::default() }.encode())
+            .build()
+            .unwrap();
+        let session_info = default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap();
+        <(U256, U256, U256, B256, U256)>::abi_decode(&session_info.journal.bytes).unwrap()
+    };
+
+    let clean_output = run(clean, 0);
+    let untrimmed_output = run(noisy.clone(), 0);
+    let trimmed_output = run(noisy, 2);
+
+    let clean_price = clean_output.2.to::<i64>();
+    let untrimmed_price = untrimmed_output.2.to::<i64>();
+    let trimmed_price = trimmed_output.2.to::<i64>();
+    println!("Clean: {}, untrimmed: {}, trimmed: {}", clean_price, untrimmed_price, trimmed_price);
+
+    assert_eq!(untrimmed_output.4, U256::ZERO, "Nothing should be trimmed when k = 0");
+    assert_eq!(trimmed_output.4, U256::from(1u64), "Only the spike should be trimmed");
+    assert!(
+        (trimmed_price - clean_price).abs() < (untrimmed_price - clean_price).abs(),
+        "Trimmed fit should be closer to the clean trend"
+    );
+}
+
+#[test]
+fn test_trading_signal_commits_data_points() {
+    // The committed point count is the length of the series the models were fitted to
+    let current_price = U256::from(3200u64);
+    let mut history: Vec<(u64, u64)> = (1..=20).map(|day| (day, 3000 + 10 * day)).collect();
+    history[9].1 = 6000; // Day 10 spikes to $6000
+
+    let run = |outlier_k: u64| {
+        let env = ExecutorEnv::builder()
+            .write_slice(&GuestInput { current_price, history: history.clone(), outlier_k, ..Default::default() }.encode())
+            .build()
+            .unwrap();
+        let journal = default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap().journal.bytes;
+        let trimmed_points = U256::from_be_slice(&journal[128..160]);
+        let data_points = U256::from_be_slice(&journal[512..544]);
+        (trimmed_points, data_points)
+    };
+
+    assert_eq!(run(0), (U256::ZERO, U256::from(20u64)), "Every point should be used without trimming");
+    assert_eq!(run(2), (U256::from(1u64), U256::from(19u64)), "The trimmed spike should not be counted");
+}
+
+#[test]
+fn test_trading_signal_ensemble_holds_on_disagreement() {
+    // A long uptrend followed by a sharp recent drop: the regression over the whole series still
+    // points up (BUY), while the fast moving average has crossed below the slow one (SELL)
+    let current_price = U256::from(3000u64);
+    let mut history: Vec<(u64, u64)> = (1..=20).map(|day| (day, 3000 + 20 * day)).collect();
+    history.extend((21..=25).map(|day| (day, 3100)));
+
+    let run = |models: u64| {
+        let env = ExecutorEnv::builder()
+            .write_slice(&GuestInput { current_price, history: history.clone(), models, ..Default::default() }.encode())
+            .build()
+            .unwrap();
+        let session_info = default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap();
+        <(U256, U256, U256, B256, U256, U256)>::abi_decode(&session_info.journal.bytes).unwrap()
+    };
+
+    let linreg_output = run(0b01);
+    let sma_output = run(0b10);
+    let ensemble_output = run(0b11);
+
+    assert_eq!(linreg_output.0, U256::from(1u64), "Regression alone should signal BUY");
+    assert_eq!(sma_output.0, U256::from(0u64), "SMA crossover alone should signal SELL");
+    assert_eq!(ensemble_output.0, U256::from(2u64), "Disagreeing models should produce HOLD");
+    assert_eq!(
+        ensemble_output.1,
+        (linreg_output.1 + sma_output.1) / U256::from(2u64),
+        "Ensemble confidence should be the average of the models"
+    );
+    assert_eq!(ensemble_output.5, U256::from(0b11u64), "Journal should commit the models that ran");
+}
+
+#[test]
+fn test_trading_signal_ensemble_confidence_aggregation() {
+    // A steady uptrend well above the current price: the regression and the SMA crossover both
+    // signal BUY, with different confidences
+    let current_price = U256::from(3000u64);
+    let history: Vec<(u64, u64)> = (1..=20).map(|day| (day, 3000 + 20 * day)).collect();
+
+    let run = |models: u64, ensemble_confidence: u64| {
+        let env = ExecutorEnv::builder()
+            .write_slice(
+                &GuestInput {
+                    current_price,
+                    history: history.clone(),
+                    models,
+                    ensemble_confidence,
+                    ..Default::default()
+                }
+                .encode(),
+            )
+            .build()
+            .unwrap();
+        let session_info = default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap();
+        let (signal, confidence) = <(U256, U256)>::abi_decode(&session_info.journal.bytes[..64]).unwrap();
+        (signal.to::<u64>(), confidence.to::<u64>())
+    };
+
+    let (linreg_signal, linreg_confidence) = run(0b01, 0);
+    let (sma_signal, sma_confidence) = run(0b10, 0);
+    assert_eq!((linreg_signal, sma_signal), (1, 1), "Both models should signal BUY");
+    assert_ne!(linreg_confidence, sma_confidence, "The models should differ in confidence");
+
+    assert_eq!(run(0b11, 0), (1, (linreg_confidence + sma_confidence) / 2), "mean should average the confidences");
+    assert_eq!(run(0b11, 1), (1, linreg_confidence.min(sma_confidence)), "min should keep the lower confidence");
+    assert_eq!(run(0b11, 2), (1, linreg_confidence * sma_confidence / 100), "product should multiply the confidences");
+}
+
+#[test]
+fn test_trading_signal_percentile_rank_buys_the_dip() {
+    // A steady rise from 3010 to 3200: the regression expects more of it, while the percentile
+    // rank model only looks at where the current price sits in the range
+    let history: Vec<(u64, u64)> = (1..=20).map(|day| (day, 3000 + 10 * day)).collect();
+    let run = |current_price: u64, percentile_window: u64| {
+        let env = ExecutorEnv::builder()
+            .write_slice(
+                &GuestInput {
+                    current_price: U256::from(current_price),
+                    history: history.clone(),
+                    models: 0b100,
+                    percentile_window,
+                    ..Default::default()
+                }
+                .encode(),
+            )
+            .build()
+            .unwrap();
+        let session_info = default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap();
+        <(U256, U256, U256)>::abi_decode(&session_info.journal.bytes[..96]).unwrap()
+    };
+
+    let (signal, confidence, predicted_price) = run(3010, 0);
+    assert_eq!(signal, U256::from(1u64), "A price at the series minimum should signal BUY");
+    assert_eq!(confidence, U256::from(100u64), "The bottom of the range should give full confidence");
+    assert_eq!(predicted_price, U256::from(3105u64), "The prediction should revert to the median");
+
+    assert_eq!(run(3250, 0).0, U256::from(0u64), "A price above the range should signal SELL");
+    assert_eq!(run(3150, 0).0, U256::from(2u64), "A mid-range price should HOLD");
+    assert_eq!(run(3150, 5).0, U256::from(1u64), "The same price is a dip within the last 5 points");
+}
+
+#[test]
+fn test_trading_signal_clamps_negative_prediction() {
+    // $1000 falling $100 a day extrapolates to -$100 on day 11, which must not wrap around
+    let current_price = U256::from(100u64);
+    let history: Vec<(u64, u64)> = (1..=10).map(|day| (day, 1100 - 100 * day)).collect();
+
+    let env = ExecutorEnv::builder()
+        .write_slice(&GuestInput { current_price, history, ..Default::default() }.encode())
+        .build()
+        .unwrap();
+
+    let session_info = default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap();
+
+    let output: (U256, U256, U256) = <(U256, U256, U256)>::abi_decode(&session_info.journal.bytes).unwrap();
+    assert_eq!(output.2, U256::ZERO, "Negative prediction should be clamped to zero");
+    assert_eq!(output.0, U256::from(0u64), "A falling price should signal SELL");
+}
+
+#[test]
+fn test_trading_signal_prior_slope() {
+    // A series rising $10 a day, with the regression slope blended with an external prior
+    let current_price = U256::from(3200u64);
+    let history: Vec<(u64, u64)> = (1..=20).map(|day| (day, 3000 + 10 * day)).collect();
+
+    let run = |prior_slope: i64, prior_weight: u64| {
+        let env = ExecutorEnv::builder()
+            .write_slice(
+                &GuestInput {
+                    current_price,
+                    history: history.clone(),
+                    prior_slope,
+                    prior_weight,
+                    ..Default::default()
+                }
+                .encode(),
+            )
+            .build()
+            .unwrap();
+        let session_info = default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap();
+        <(U256, U256, U256, B256, U256, U256, I256)>::abi_decode(&session_info.journal.bytes).unwrap()
+    };
+
+    let baseline = run(0, 0);
+    let ignored_prior = run(500, 0);
+    let strong_prior = run(100, 80);
+
+    // The guest's integer regression truncates the slope, so compare against what it committed
+    let regression_slope = baseline.6.as_i64();
+    assert!((9..=10).contains(&regression_slope), "Regression slope should be about $10 a day");
+    assert_eq!(ignored_prior, baseline, "A prior with zero weight should leave the regression unchanged");
+    assert_eq!(
+        strong_prior.6.as_i64(),
+        (regression_slope * 20 + 100 * 80) / 100,
+        "Slope should be 20% regression and 80% prior"
+    );
+    assert!(strong_prior.2 > baseline.2, "A strong upward prior should raise the prediction");
+}
+
+#[test]
+fn test_trading_signal_asymmetric_thresholds() {
+    // BUY needs a predicted rise above 1%, SELL any predicted fall beyond 0.2%
+    let history: Vec<(u64, u64)> = (1..=20).map(|day| (day, 3000 + 10 * day)).collect();
+
+    let run = |current_price: u64, buy_bps: u64, sell_bps: u64| {
+        let env = ExecutorEnv::builder()
+            .write_slice(
+                &GuestInput {
+                    current_price: U256::from(current_price),
+                    history: history.clone(),
+                    buy_bps,
+                    sell_bps,
+                    ..Default::default()
+                }
+                .encode(),
+            )
+            .build()
+            .unwrap();
+        let session_info = default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap();
+        <(U256, U256, U256)>::abi_decode(&session_info.journal.bytes).unwrap()
+    };
+
+    // The series extrapolates to about $3204, roughly 0.4% above $3190 and 0.5% below $3220
+    let mid_range = run(3190, 100, 20);
+    let predicted_price = mid_range.2.to::<u64>();
+    assert!((3191..3222).contains(&predicted_price), "Unexpected prediction {}", predicted_price);
+
+    assert_eq!(mid_range.0, U256::from(2u64), "A rise inside the BUY band should HOLD");
+    assert_eq!(run(3190, 0, 0).0, U256::from(1u64), "Without a band the same rise should BUY");
+    assert_eq!(run(3220, 100, 20).0, U256::from(0u64), "A fall beyond the SELL band should SELL");
+}
+
+#[test]
+fn test_trading_signal_report_panel() {
+    // Report mode commits the full indicator panel after the signal fields
+    let current_price = U256::from(3700u64);
+    let history: Vec<(u64, u64)> = (1..=40).map(|day| (day, 3000 + 15 * day + (day % 3) * 20)).collect();
+    let (min_price, max_price) = (3035u64, 3620u64);
+
+    let run = |report: bool| {
+        let env = ExecutorEnv::builder()
+            .write_slice(
+                &GuestInput {
+                    current_price,
+                    history: history.clone(),
+                    buy_bps: 50,
+                    sell_bps: 50,
+                    report,
+                    ..Default::default()
+                }
+                .encode(),
+            )
+            .build()
+            .unwrap();
+        let session_info = default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap();
+        <(U256, U256, U256, B256, U256, U256, I256, U256, U256, I256, U256, U256)>::abi_decode(&session_info.journal.bytes)
+            .unwrap()
+    };
+
+    let output = run(true);
+    let regression_price = output.7.to::<u64>();
+    let rsi = output.8.to::<u64>();
+    let macd_histogram = output.9.as_i64();
+    let sma_fast = output.10.to::<u64>();
+    let sma_slow = output.11.to::<u64>();
+    println!(
+        "Regression: {}, RSI: {}, MACD histogram: {}, SMA fast/slow: {}/{}",
+        regression_price, rsi, macd_histogram, sma_fast, sma_slow
+    );
+
+    assert_eq!(output.7, output.2, "Regression price should match the linreg prediction");
+    assert!(regression_price > max_price, "Rising series should extrapolate above its last price");
+    assert!(rsi > 50 && rsi <= 100, "Rising series should have RSI above 50");
+    assert!(macd_histogram.abs() < 100 * 1_000_000, "MACD histogram should be a few dollars at most");
+    assert!((min_price..=max_price).contains(&sma_fast), "Fast SMA should lie within the series");
+    assert!((min_price..=max_price).contains(&sma_slow), "Slow SMA should lie within the series");
+    assert!(sma_fast > sma_slow, "Fast SMA should lead the slow one in an uptrend");
+
+    let without_report = run(false);
+    assert_eq!(without_report.2, output.2, "Report mode should not change the prediction");
+    assert_eq!(without_report.8, U256::ZERO, "The panel should be zero outside report mode");
+}
+
+#[test]
+fn test_trading_signal_raw_prediction() {
+    // Raw prediction mode commits only (predicted_price, confidence), matching the full journal
+    let current_price = U256::from(3200u64);
+    let history: Vec<(u64, u64)> = vec![(1, 3100), (2, 3150), (3, 3125), (4, 3190), (5, 3240)];
+
+    let run = |raw_prediction: bool| {
+        let env = ExecutorEnv::builder()
+            .write_slice(
+                &GuestInput {
+                    current_price,
+                    history: history.clone(),
+                    buy_bps: 50,
+                    sell_bps: 50,
+                    raw_prediction,
+                    ..Default::default()
+                }
+                .encode(),
+            )
+            .build()
+            .unwrap();
+        default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap().journal.bytes
+    };
+
+    let raw_journal = run(true);
+    let full_journal = run(false);
+
+    assert_eq!(raw_journal.len(), 64, "Raw journal should be exactly two ABI words");
+    let (predicted_price, confidence) = <(U256, U256)>::abi_decode(&raw_journal).unwrap();
+    let full_output = <(U256, U256, U256)>::abi_decode(&full_journal).unwrap();
+    assert_eq!(predicted_price, full_output.2, "Raw prediction should match the full journal");
+    assert_eq!(confidence, full_output.1, "Raw confidence should match the full journal");
+}
+
+#[test]
+fn test_trading_signal_commits_price_decimals() {
+    // The unit of the predicted price is committed next to it, in the word a contract reads it from
+    let history: Vec<(u64, u64)> = (1..=20).map(|day| (day, 3000 + 10 * day)).collect();
+    let env = ExecutorEnv::builder()
+        .write_slice(
+            &GuestInput {
+                current_price: U256::from(3_200_000_000u64),
+                history,
+                buy_bps: 50,
+                sell_bps: 50,
+                decimals: 6,
+                ..Default::default()
+            }
+            .encode(),
+        )
+        .build()
+        .unwrap();
+    let journal = default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap().journal.bytes;
+
+    let Journal::V10(decoded) = Journal::decode(&journal).unwrap() else {
+        panic!("Journal should decode as the latest layout");
+    };
+    assert_eq!(decoded.decimals, 6, "The journal should carry the decimals of the predicted price");
+    assert_eq!(U256::from_be_slice(&journal[704..736]), U256::from(6u64));
+}
+
+#[test]
+fn test_trading_signal_commits_residuals() {
+    // One residual per fitted point; an OLS fit leaves them summing to zero, up to the truncation
+    // of the integer mean
+    let history: Vec<(u64, u64)> = (1..=21).map(|day| (day, 3000 + 10 * day + 7 * (day % 3))).collect();
+    let env = ExecutorEnv::builder()
+        .write_slice(
+            &GuestInput {
+                current_price: U256::from(3200u64),
+                history: history.clone(),
+                buy_bps: 50,
+                sell_bps: 50,
+                residuals: true,
+                ..Default::default()
+            }
+            .encode(),
+        )
+        .build()
+        .unwrap();
+    let journal = default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap().journal.bytes;
+
+    let Journal::V11(decoded) = Journal::decode(&journal).unwrap() else {
+        panic!("Journal should decode as the residual layout");
+    };
+    assert_eq!(decoded.residuals.len(), history.len(), "There should be one residual per point");
+    let sum: i64 = decoded.residuals.iter().sum();
+    assert!(sum.unsigned_abs() < history.len() as u64, "Residuals should sum to about zero, got {sum}");
+}
+
+#[test]
+fn test_trading_signal_decimals() {
+    // A 6-decimal price unit: the current price goes in as $3200 * 10^6 and the prediction comes
+    // out scaled the same way, with the same signal as whole-unit input
+    let history: Vec<(u64, u64)> = (1..=20).map(|day| (day, 3000 + 10 * day)).collect();
+
+    let run = |current_price: U256, decimals: u64| {
+        let env = ExecutorEnv::builder()
+            .write_slice(
+                &GuestInput {
+                    current_price,
+                    history: history.clone(),
+                    buy_bps: 50,
+                    sell_bps: 50,
+                    decimals,
+                    ..Default::default()
+                }
+                .encode(),
+            )
+            .build()
+            .unwrap();
+        let session_info = default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap();
+        <(U256, U256, U256)>::abi_decode(&session_info.journal.bytes).unwrap()
+    };
+
+    let whole = run(U256::from(3200u64), 0);
+    let scaled = run(U256::from(3_200_000_000u64), 6);
+
+    assert_eq!(scaled.0, whole.0, "Scaling should not change the signal");
+    assert_eq!(scaled.2, whole.2 * U256::from(1_000_000u64), "Prediction should be in 6-decimal units");
+    assert_eq!(
+        format_wei(scaled.2, 6),
+        whole.2.to_string(),
+        "Prediction should display as whole USD"
+    );
+
+    // An 18-decimal wei price exceeds 64 bits but its whole-unit price does not
+    let wei = U256::from(3200u64) * U256::from(10u64).pow(U256::from(18u64));
+    assert!(wei > U256::from(u64::MAX));
+    assert_eq!(run(wei, 18).0, whole.0, "A wei price should not be truncated to its low 64 bits");
+    let env = ExecutorEnv::builder()
+        .write_slice(
+            &GuestInput {
+                current_price: wei,
+                history: history.clone(),
+                buy_bps: 50,
+                sell_bps: 50,
+                ..Default::default()
+            }
+            .encode(),
+        )
+        .build()
+        .unwrap();
+    assert_eq!(
+        default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap().exit_code,
+        ExitCode::Halted(EXIT_INVALID_INPUT as u32),
+        "Guest should reject a whole-unit price above 64 bits rather than truncate it"
+    );
+}
+
+#[test]
+fn test_trading_signal_json_input() {
+    // The same parameters as named JSON fields should commit exactly the journal of the ABI tuple
+    let history: Vec<(u64, u64)> = (1..=20).map(|day| (day, 3000 + 10 * day)).collect();
+
+    let run = |input: Vec<u8>| {
+        let env = ExecutorEnv::builder().write_slice(&input).build().unwrap();
+        default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap().journal.bytes
+    };
+
+    let abi_journal = run(
+        GuestInput {
+            current_price: U256::from(3190u64),
+            history: history.clone(),
+            outlier_k: 3,
+            models: 3,
+            prior_slope: 5,
+            prior_weight: 20,
+            buy_bps: 100,
+            sell_bps: 20,
+            report: true,
+            ..Default::default()
+        }
+        .encode(),
+    );
+
+    let json = format!(
+        r#"{{"current_price": "3190", "history": {:?}, "outlier_k": 3, "models": 3, "prior_slope": 5, "prior_weight": 20, "buy_bps": 100, "sell_bps": 20, "report": true}}"#,
+        history.iter().map(|&(day, price)| [day, price]).collect::<Vec<_>>()
+    );
+    let mut json_input = vec![INPUT_FORMAT_JSON];
+    json_input.extend(json.as_bytes());
+    let json_journal = run(json_input);
+
+    assert_eq!(json_journal, abi_journal, "JSON input should commit the same journal as ABI input");
+    // The input hash is over the ABI-encoded history whichever encoding carried it
+    let output = <(U256, U256, U256, B256)>::abi_decode(&json_journal).unwrap();
+    assert_eq!(output.3, keccak256(history.abi_encode()));
+}
+
+#[test]
+fn test_trading_signal_clamps_absurd_price() {
+    // A steady $10/day rise with one corrupt tick of $3,000,000 on day 10
+    let mut history: Vec<(u64, u64)> = (1..=20).map(|day| (day, 3000 + 10 * day)).collect();
+    history[9].1 = 3_000_000;
+
+    let run = |clamp_factor: u64| {
+        let env = ExecutorEnv::builder()
+            .write_slice(
+                &GuestInput {
+                    current_price: U256::from(3200u64),
+                    history: history.clone(),
+                    buy_bps: 50,
+                    sell_bps: 50,
+                    clamp_factor,
+                    ..Default::default()
+                }
+                .encode(),
+            )
+            .build()
+            .unwrap();
+        let session_info = default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap();
+        <(U256, U256, U256, B256, U256, U256, I256, U256, U256, I256, U256, U256, U256)>::abi_decode(&session_info.journal.bytes).unwrap()
+    };
+
+    let unclamped = run(0);
+    assert_eq!(unclamped.12, U256::ZERO, "Nothing should be clamped when disabled");
+    assert!(unclamped.6.is_negative(), "The corrupt tick should wreck the unclamped slope");
+
+    // Within 2x of the $3115 median, the tick is pulled down to $6230
+    let clamped = run(2);
+    assert_eq!(clamped.12, U256::from(1u64), "Exactly the corrupt tick should be clamped");
+    let slope = clamped.6.as_i64();
+    assert!((1..20).contains(&slope), "Clamped slope {} should stay close to $10/day", slope);
+}
+
+#[test]
+fn test_trading_signal_penalizes_gaps() {
+    // The same rise from day 1 to day 20, once daily and once with 17 missing days before the end
+    let contiguous: Vec<(u64, u64)> = (1..=20).map(|day| (day, 3000 + 10 * day)).collect();
+    let gappy: Vec<(u64, u64)> = vec![(1, 3010), (2, 3020), (20, 3200)];
+
+    let run = |history: Vec<(u64, u64)>| {
+        let env = ExecutorEnv::builder()
+            .write_slice(
+                &GuestInput {
+                    current_price: U256::from(3200u64),
+                    history,
+                    buy_bps: 50,
+                    sell_bps: 50,
+                    ..Default::default()
+                }
+                .encode(),
+            )
+            .build()
+            .unwrap();
+        let session_info = default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap();
+        <(U256, U256, U256)>::abi_decode(&session_info.journal.bytes).unwrap()
+    };
+
+    let contiguous_confidence = run(contiguous).1;
+    let gappy_confidence = run(gappy).1;
+    println!("Contiguous confidence: {}%, gappy confidence: {}%", contiguous_confidence, gappy_confidence);
+
+    // Both fit a line equally well, so only the 18-day jump separates them
+    assert_eq!(contiguous_confidence, U256::from(98u64));
+    assert!(gappy_confidence < contiguous_confidence, "A gappy series should lower confidence");
+    assert_eq!(gappy_confidence, U256::from(98u64 * 100 / 270), "17 missing days should divide confidence by 2.7");
+}
+
+#[test]
+fn test_trading_signal_bootstrap_seed() {
+    // A noisy rise whose prediction sits just above the BUY threshold, so resampled fits disagree
+    let history: Vec<(u64, u64)> = (1..=20).map(|day| (day, 3000 + 5 * day + (day * 37 % 11) * 15)).collect();
+
+    let run = |seed: u64| {
+        let env = ExecutorEnv::builder()
+            .write_slice(
+                &GuestInput {
+                    current_price: U256::from(3165u64),
+                    history: history.clone(),
+                    buy_bps: 50,
+                    sell_bps: 50,
+                    seed,
+                    bootstrap_samples: 64,
+                    ..Default::default()
+                }
+                .encode(),
+            )
+            .build()
+            .unwrap();
+        default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap().journal.bytes
+    };
+
+    let first = run(1);
+    assert_eq!(run(1), first, "The same seed should reproduce the journal exactly");
+
+    let confidence = |journal: &[u8]| <(U256, U256, U256)>::abi_decode(journal).unwrap().1;
+    let reseeded = run(2);
+    println!("Bootstrapped confidence: {}% with seed 1, {}% with seed 2", confidence(&first), confidence(&reseeded));
+    assert!(confidence(&first) < U256::from(100u64), "Resampled fits should not all agree");
+    assert_ne!(confidence(&first), confidence(&reseeded), "Another seed should draw other resamples");
+}
+
+#[test]
+fn test_trading_signal_agreement_gate() {
+    // A steady rise whose last day dips: the slope is up but the last change is down
+    let mut history: Vec<(u64, u64)> = (1..=20).map(|day| (day, 3000 + 10 * day)).collect();
+    history[19].1 = history[18].1 - 5;
+
+    let run = |require_agreement: bool| {
+        let env = ExecutorEnv::builder()
+            .write_slice(
+                &GuestInput {
+                    current_price: U256::from(3100u64),
+                    history: history.clone(),
+                    buy_bps: 50,
+                    sell_bps: 50,
+                    require_agreement,
+                    ..Default::default()
+                }
+                .encode(),
+            )
+            .build()
+            .unwrap();
+        let session_info = default_executor().execute(env, TRADING_SIGNAL_ELF).unwrap();
+        <(U256, U256, U256, B256, U256, U256, I256, U256, U256, I256, U256, U256, U256, bool)>::abi_decode(&session_info.journal.bytes).unwrap()
+    };
+
+    let ungated = run(false);
+    assert!(ungated.6.is_positive(), "The regression slope should be up");
+    assert!(!ungated.13, "The slope should disagree with the last delta");
+    assert_eq!(ungated.0, U256::from(1u64), "Without the gate the rising trend should BUY");
+
+    let gated = run(true);
+    assert!(!gated.13);
+    assert_eq!(gated.0, U256::from(2u64), "Disagreement should force HOLD");
+}
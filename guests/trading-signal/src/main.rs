@@ -14,52 +14,35 @@
 
 use std::io::Read;
 
-use alloy_primitives::U256;
+use alloy_primitives::{keccak256, I256, U256};
 use alloy_sol_types::SolValue;
 use risc0_zkvm::guest::env;
+use trading_journal::{
+    strategy_id, Action, GuestInput, IndicatorPanel, Journal, JournalV1, JournalV10, JournalV11, JournalV12, JournalV13,
+    JournalV2, JournalV3, JournalV4, JournalV5, JournalV6, JournalV7, JournalV8, JournalV9, Reason, StrategyParams,
+    EXIT_INVALID_INPUT, INPUT_FORMAT_ABI, INPUT_FORMAT_JSON, JOURNAL_V1, JOURNAL_V10, JOURNAL_V2, JOURNAL_V3, JOURNAL_V4,
+    JOURNAL_V5, JOURNAL_V6, JOURNAL_V7, JOURNAL_V8, JOURNAL_V9, LATEST_JOURNAL_VERSION, PRICE_HISTORY,
+};
 
-// Historical ETH price data (30 days) - USD price per ETH
-// Format: (day_index, usd_price_per_eth)
-// These are actual USD prices, e.g., 3200 means $3200 per ETH
-const PRICE_HISTORY: [(u64, u64); 30] = [
-    (1, 3200),   // Day 1: $3200 per ETH
-    (2, 3215),   // Day 2: $3215 per ETH  
-    (3, 3189),   // Day 3: $3189 per ETH
-    (4, 3221),   // Day 4: $3221 per ETH
-    (5, 3254),   // Day 5: $3254 per ETH
-    (6, 3278),   // Day 6: $3278 per ETH
-    (7, 3242),   // Day 7: $3242 per ETH
-    (8, 3291),   // Day 8: $3291 per ETH
-    (9, 3315),   // Day 9: $3315 per ETH
-    (10, 3287),  // Day 10: $3287 per ETH
-    (11, 3324),  // Day 11: $3324 per ETH
-    (12, 3352),  // Day 12: $3352 per ETH
-    (13, 3389),  // Day 13: $3389 per ETH
-    (14, 3412),  // Day 14: $3412 per ETH
-    (15, 3398),  // Day 15: $3398 per ETH
-    (16, 3436),  // Day 16: $3436 per ETH
-    (17, 3462),  // Day 17: $3462 per ETH
-    (18, 3489),  // Day 18: $3489 per ETH
-    (19, 3453),  // Day 19: $3453 per ETH
-    (20, 3507),  // Day 20: $3507 per ETH
-    (21, 3534),  // Day 21: $3534 per ETH
-    (22, 3561),  // Day 22: $3561 per ETH
-    (23, 3528),  // Day 23: $3528 per ETH
-    (24, 3582),  // Day 24: $3582 per ETH
-    (25, 3615),  // Day 25: $3615 per ETH
-    (26, 3648),  // Day 26: $3648 per ETH
-    (27, 3621),  // Day 27: $3621 per ETH
-    (28, 3674),  // Day 28: $3674 per ETH
-    (29, 3702),  // Day 29: $3702 per ETH
-    (30, 3735),  // Day 30: $3735 per ETH
-];
-
-fn linear_regression() -> (i64, i64, u64) {
-    let n = PRICE_HISTORY.len() as i64;
+// Hard cap on the number of price points the guest will process. The host enforces its own,
+// usually tighter, limit; this one bounds the cycle count of any request regardless of the host.
+const MAX_HISTORY_LEN: usize = 1024;
+
+// Largest supported number of price decimals (ETH's 18, plus headroom); keeps 10^decimals and the
+// scaled prediction well inside a U256
+const MAX_DECIMALS: u64 = 36;
+
+// Units of confidence: models work in basis points, and combine and commit whole percentages unless
+// the input asks for basis points
+const PERCENT: u64 = 100;
+const BPS: u64 = 10_000;
+
+fn linear_regression(history: &[(u64, u64)]) -> (i64, i64, u64) {
+    let n = history.len() as i64;
     
     // Calculate means
-    let sum_x: i64 = PRICE_HISTORY.iter().map(|(x, _)| *x as i64).sum();
-    let sum_y: i64 = PRICE_HISTORY.iter().map(|(_, y)| *y as i64).sum();
+    let sum_x: i64 = history.iter().map(|(x, _)| *x as i64).sum();
+    let sum_y: i64 = history.iter().map(|(_, y)| *y as i64).sum();
     let mean_x = sum_x / n;
     let mean_y = sum_y / n;
     
@@ -69,7 +52,7 @@ fn linear_regression() -> (i64, i64, u64) {
     let mut sum_squared_errors = 0i64;
     let mut sum_squared_total = 0i64;
     
-    for (x, y) in PRICE_HISTORY.iter() {
+    for (x, y) in history.iter() {
         let x_diff = *x as i64 - mean_x;
         let y_diff = *y as i64 - mean_y;
         
@@ -81,60 +64,1190 @@ fn linear_regression() -> (i64, i64, u64) {
     let slope = if denominator != 0 { numerator / denominator } else { 0 };
     let intercept = mean_y - slope * mean_x;
     
-    // Calculate R² for confidence (coefficient of determination)
-    for (x, y) in PRICE_HISTORY.iter() {
+    // Calculate R² for confidence (coefficient of determination), in basis points
+    for (x, y) in history.iter() {
         let predicted = slope * (*x as i64) + intercept;
         let error = *y as i64 - predicted;
         sum_squared_errors += error * error;
     }
     
     let r_squared = if sum_squared_total > 0 {
-        let ratio = (sum_squared_total - sum_squared_errors) * 100 / sum_squared_total;
+        let ratio = (sum_squared_total - sum_squared_errors) as i128 * BPS as i128 / sum_squared_total as i128;
         if ratio > 0 { ratio as u64 } else { 0 }
     } else {
         0
     };
     
-    (slope, intercept, r_squared.min(100))
+    (slope, intercept, r_squared.min(BPS))
+}
+
+// Fixed-point scale of the time-decay weight of a point as recent as the newest one
+const DECAY_SCALE: u64 = 1_000_000;
+
+// Time-decay weight of each point, out of DECAY_SCALE: halved for every `half_life` seconds the
+// point is older than the newest, and linearly in between
+fn decay_weights(timestamps: &[u64], half_life: u64) -> Vec<u64> {
+    let newest = timestamps.iter().copied().max().unwrap_or(0);
+    timestamps
+        .iter()
+        .map(|timestamp| {
+            let age = newest - timestamp;
+            let halvings = age / half_life;
+            if halvings >= u64::BITS as u64 {
+                return 0;
+            }
+            let rest = (age % half_life) as u128;
+            let half_life = half_life as u128;
+            ((DECAY_SCALE >> halvings) as u128 * (2 * half_life - rest) / (2 * half_life)) as u64
+        })
+        .collect()
+}
+
+// Weighted least squares fit of the points, each counting for its weight, with the weighted R² in
+// basis points. With every weight equal it is the plain fit, without the truncated means.
+fn weighted_regression(history: &[(u64, u64)], weights: &[u64]) -> (i64, i64, u64) {
+    let (mut sum_w, mut sum_wx, mut sum_wy, mut sum_wxx, mut sum_wxy) = (0i128, 0i128, 0i128, 0i128, 0i128);
+    for (&(x, y), &w) in history.iter().zip(weights) {
+        let (x, y, w) = (x as i128, y as i128, w as i128);
+        sum_w += w;
+        sum_wx += w * x;
+        sum_wy += w * y;
+        sum_wxx += w * x * x;
+        sum_wxy += w * x * y;
+    }
+    let denominator = sum_w * sum_wxx - sum_wx * sum_wx;
+    let slope = if denominator != 0 { (sum_w * sum_wxy - sum_wx * sum_wy) / denominator } else { 0 };
+    let intercept = (sum_wy - slope * sum_wx) / sum_w.max(1);
+    let mean_y = sum_wy / sum_w.max(1);
+    let (sum_squared_errors, sum_squared_total) =
+        history.iter().zip(weights).fold((0i128, 0i128), |(sse, sst), (&(x, y), &w)| {
+            let error = y as i128 - (slope * x as i128 + intercept);
+            let deviation = y as i128 - mean_y;
+            (sse + w as i128 * error * error, sst + w as i128 * deviation * deviation)
+        });
+    let r_squared = if sum_squared_total > 0 {
+        ((sum_squared_total - sum_squared_errors) * BPS as i128 / sum_squared_total).clamp(0, BPS as i128) as u64
+    } else {
+        0
+    };
+    (slope as i64, intercept as i64, r_squared)
+}
+
+// Typical price (high + low + close) / 3 of each (day, open, high, low, close) candle, the price
+// every model fits when the input is OHLC
+fn typical_prices(candles: &[(u64, u64, u64, u64, u64)]) -> Vec<(u64, u64)> {
+    candles
+        .iter()
+        .map(|&(day, _, high, low, close)| (day, ((high as u128 + low as u128 + close as u128) / 3) as u64))
+        .collect()
+}
+
+// Median of the prices, averaging the middle two of an even-length series
+fn median_price(history: &[(u64, u64)]) -> u64 {
+    let mut prices: Vec<u64> = history.iter().map(|(_, price)| *price).collect();
+    prices.sort_unstable();
+    let mid = prices.len() / 2;
+    if prices.len() % 2 == 0 {
+        ((prices[mid - 1] as u128 + prices[mid] as u128) / 2) as u64
+    } else {
+        prices[mid]
+    }
+}
+
+// Winsorizes the prices into [median / factor, median * factor], returning the clamped series and
+// how many prices were changed
+fn clamp_to_median(history: &[(u64, u64)], factor: u64) -> (Vec<(u64, u64)>, u64) {
+    let median = median_price(history);
+    let low = median / factor;
+    let high = median.saturating_mul(factor);
+    let mut clamped_points = 0;
+    let clamped = history
+        .iter()
+        .map(|&(day, price)| {
+            let bounded = price.clamp(low, high);
+            if bounded != price {
+                clamped_points += 1;
+            }
+            (day, bounded)
+        })
+        .collect();
+    (clamped, clamped_points)
+}
+
+// Drops the points whose residual from the fitted line exceeds k standard deviations.
+// A single pass is made; the caller refits on the returned points.
+fn trim_outliers(history: &[(u64, u64)], slope: i64, intercept: i64, k: u64) -> Vec<(u64, u64)> {
+    let residuals: Vec<i128> = history
+        .iter()
+        .map(|(x, y)| (*y as i64 - (slope * *x as i64 + intercept)) as i128)
+        .collect();
+    let n = residuals.len() as i128;
+    let sum_squared_residuals: i128 = residuals.iter().map(|e| e * e).sum();
+    
+    // |e| > k * sigma  <=>  e² * n > k² * Σe², which avoids a square root
+    let k_squared = (k as i128) * (k as i128);
+    let kept: Vec<(u64, u64)> = history
+        .iter()
+        .zip(residuals.iter())
+        .filter(|(_, e)| *e * *e * n <= k_squared * sum_squared_residuals)
+        .map(|(point, _)| *point)
+        .collect();
+    
+    // A line needs at least two points; keep the original series otherwise
+    if kept.len() < 2 { history.to_vec() } else { kept }
+}
+
+// Models selectable through the input bitmask
+const MODEL_LINREG: u64 = 1 << 0;
+const MODEL_SMA_CROSSOVER: u64 = 1 << 1;
+const MODEL_PERCENTILE_RANK: u64 = 1 << 2;
+const MODEL_HOLT: u64 = 1 << 3;
+const MODEL_THEIL_SEN: u64 = 1 << 4;
+const ALL_MODELS: u64 = MODEL_LINREG | MODEL_SMA_CROSSOVER | MODEL_PERCENTILE_RANK | MODEL_HOLT | MODEL_THEIL_SEN;
+
+// Ways of combining the confidences of the models in an ensemble
+const CONFIDENCE_MEAN: u64 = 0;
+const CONFIDENCE_MIN: u64 = 1;
+const CONFIDENCE_PRODUCT: u64 = 2;
+
+// Window lengths, in points, of the SMA crossover model
+const SMA_FAST_WINDOW: usize = 5;
+const SMA_SLOW_WINDOW: usize = 20;
+
+// Percentile ranks at or below which the percentile rank model signals BUY, and at or above which
+// it signals SELL
+const PERCENTILE_BUY_MAX: u64 = 20;
+const PERCENTILE_SELL_MIN: u64 = 80;
+
+// Smoothing factors (0-100) of the Holt model's level and trend when the input leaves them at 0
+const HOLT_DEFAULT_ALPHA: u64 = 50;
+const HOLT_DEFAULT_BETA: u64 = 20;
+// The Holt model smooths prices in millionths of a USD so that fractional trends survive
+const HOLT_SCALE: i128 = 1_000_000;
+
+// Confidence lost per missing day in the largest gap of the series, in percent of the remaining
+// confidence: a 10-day jump divides it by 1 + 9 * 10%
+const GAP_PENALTY_PCT_PER_DAY: u64 = 10;
+
+// Cap on the bootstrap resamples of the regression, which each cost a full fit
+const MAX_BOOTSTRAP_SAMPLES: u64 = 256;
+
+// Cap on the points whose residuals are committed, a word each
+const MAX_RESIDUAL_POINTS: usize = 256;
+
+// Furthest horizon, in days after the last point, the signal can be required to hold at: one bit
+// of the horizon mask per day
+const MAX_HORIZON: u64 = 64;
+
+// Bands, in basis points around a reference price, outside of which a model signals BUY or SELL,
+// and absolute targets in whole USD (0 if unset) that replace the band on their side when a
+// prediction is classified
+struct Thresholds {
+    buy_bps: u64,
+    sell_bps: u64,
+    buy_above: u64,
+    sell_below: u64,
+}
+
+impl Thresholds {
+    // BUY (1) if `price` is more than buy_bps above `reference`
+    // SELL (0) if `price` is more than sell_bps below `reference`
+    // HOLD (2) in between
+    fn classify(&self, reference: u64, price: u64) -> Action {
+        // Widen before scaling; wei-denominated prices overflow u64 when multiplied by bps
+        let reference = reference as u128;
+        let price = price as u128;
+        if price * 10_000 > reference * (10_000 + self.buy_bps as u128) {
+            Action::Buy
+        } else if price * 10_000 < reference * 10_000u128.saturating_sub(self.sell_bps as u128) {
+            Action::Sell
+        } else {
+            Action::Hold
+        }
+    }
+
+    // Classifies a predicted price against the current one, or against the absolute targets that
+    // are set: BUY if it is above buy_above, SELL if it is below sell_below
+    fn classify_prediction(&self, current_price: u64, predicted_price: u64) -> Action {
+        let band = self.classify(current_price, predicted_price);
+        let buy = if self.buy_above == 0 { band == Action::Buy } else { predicted_price > self.buy_above };
+        let sell = if self.sell_below == 0 { band == Action::Sell } else { predicted_price < self.sell_below };
+        match (buy, sell) {
+            (true, _) => Action::Buy,
+            (_, true) => Action::Sell,
+            _ => Action::Hold,
+        }
+    }
+}
+
+// Output shared by every model so that their results can be combined
+struct ModelOutput {
+    signal: Action,
+    // In basis points out of a model, converted to the unit of the ensemble before combining
+    confidence: u64,
+    predicted_price: u64,
+}
+
+// Fits a line, weighting the points by their time-decay `weights` unless there are none, and
+// blends its slope with an external prior, weighted by the confidence in the prior (0-100). The
+// line keeps passing through the (weighted) mean of the series.
+fn blended_regression(history: &[(u64, u64)], weights: &[u64], prior_slope: i64, prior_weight: u64) -> (i64, i64, u64) {
+    let (slope, intercept, confidence) =
+        if weights.is_empty() { linear_regression(history) } else { weighted_regression(history, weights) };
+    let prior_weight = prior_weight as i64;
+    let blended_slope = (slope * (100 - prior_weight) + prior_slope * prior_weight) / 100;
+    let mean_x = if weights.is_empty() {
+        history.iter().map(|(x, _)| *x as i64).sum::<i64>() / history.len() as i64
+    } else {
+        let weighted_x: u128 = history.iter().zip(weights).map(|(&(x, _), &w)| x as u128 * w as u128).sum();
+        (weighted_x / weights.iter().map(|&w| w as u128).sum::<u128>().max(1)) as i64
+    };
+    (blended_slope, intercept + (slope - blended_slope) * mean_x, confidence)
+}
+
+// Extrapolates the fitted line (slope, intercept, confidence) to `next_day` and classifies the
+// predicted USD price against the current one
+fn linreg_model(line: (i64, i64, u64), next_day: i64, current_price: u64, thresholds: &Thresholds) -> ModelOutput {
+    let (slope, intercept, confidence) = line;
+    // A steep decline can extrapolate below zero; clamp it rather than wrap the cast to u64
+    let predicted_price = (slope * next_day + intercept).max(0) as u64;
+    let signal = thresholds.classify_prediction(current_price, predicted_price);
+    ModelOutput { signal, confidence, predicted_price }
+}
+
+// Mean of the last `window` prices, or of the whole series if it is shorter
+fn simple_moving_average(history: &[(u64, u64)], window: usize) -> u64 {
+    let recent = &history[history.len() - window.min(history.len())..];
+    recent.iter().map(|(_, y)| *y).sum::<u64>() / recent.len() as u64
+}
+
+// Compares a fast and a slow moving average, classifying the fast one against the slow one
+// The prediction projects the gap between the averages forward from the fast one, and confidence
+// grows with the gap, reaching 100% at 10%
+fn sma_crossover_model(history: &[(u64, u64)], thresholds: &Thresholds) -> ModelOutput {
+    let fast = simple_moving_average(history, SMA_FAST_WINDOW);
+    let slow = simple_moving_average(history, SMA_SLOW_WINDOW);
+    let signal = thresholds.classify(slow, fast);
+    let gap_bps = fast.abs_diff(slow) * 10_000 / slow.max(1);
+    ModelOutput {
+        signal,
+        confidence: (gap_bps * 10).min(BPS),
+        predicted_price: (2 * fast).saturating_sub(slow),
+    }
+}
+
+// Percentage (0-100) of the series priced strictly below `current`
+fn percentile_rank(current: u64, series: &[u64]) -> u64 {
+    let below = series.iter().filter(|price| **price < current).count() as u64;
+    below * 100 / series.len().max(1) as u64
+}
+
+// Buys the dip and sells the rally: ranks the current price within the last `window` prices (the
+// whole series if 0) instead of extrapolating a trend
+// The prediction is a reversion to the median of the window, and confidence grows with the
+// distance of the rank from the median, reaching 100% at either end of the range
+fn percentile_rank_model(history: &[(u64, u64)], window: u64, current_price: u64) -> ModelOutput {
+    let window = if window == 0 { history.len() } else { (window as usize).min(history.len()) };
+    let recent = &history[history.len() - window..];
+    let prices: Vec<u64> = recent.iter().map(|(_, price)| *price).collect();
+    let rank = percentile_rank(current_price, &prices);
+    let signal = if rank <= PERCENTILE_BUY_MAX {
+        Action::Buy
+    } else if rank >= PERCENTILE_SELL_MIN {
+        Action::Sell
+    } else {
+        Action::Hold
+    };
+    ModelOutput { signal, confidence: rank.abs_diff(50) * 2 * BPS / PERCENT, predicted_price: median_price(recent) }
+}
+
+// Holt's linear (double exponential) smoothing of the prices, with the level and trend smoothing
+// factors `alpha` and `beta` in percent. Starts from the first price and the first change, and
+// returns the final level and trend in USD, the one-step prediction in USD and the confidence in
+// basis points: 1 - SSE / SST of the in-sample one-step forecasts, 0 if that is negative
+fn holt(history: &[(u64, u64)], alpha: u64, beta: u64) -> (i64, i64, u64, u64) {
+    let prices: Vec<i128> = history.iter().map(|(_, price)| *price as i128 * HOLT_SCALE).collect();
+    let (alpha, beta) = (alpha as i128, beta as i128);
+    let mut level = prices[0];
+    let mut trend = if prices.len() > 1 { prices[1] - prices[0] } else { 0 };
+    let mut sum_squared_errors = 0i128;
+    for price in &prices[1..] {
+        let forecast = level + trend;
+        sum_squared_errors += (price - forecast) * (price - forecast);
+        let previous_level = level;
+        level = (alpha * price + (100 - alpha) * forecast) / 100;
+        trend = (beta * (level - previous_level) + (100 - beta) * trend) / 100;
+    }
+
+    let mean = prices.iter().sum::<i128>() / prices.len() as i128;
+    let sum_squared_total: i128 = prices.iter().map(|price| (price - mean) * (price - mean)).sum();
+    let confidence = if sum_squared_total > 0 {
+        ((sum_squared_total - sum_squared_errors) * BPS as i128 / sum_squared_total).clamp(0, BPS as i128) as u64
+    } else {
+        0
+    };
+    let prediction = ((level + trend).max(0) / HOLT_SCALE) as u64;
+    ((level / HOLT_SCALE) as i64, (trend / HOLT_SCALE) as i64, prediction, confidence)
+}
+
+// Classifies Holt's one-step prediction against the current price
+fn holt_model(history: &[(u64, u64)], alpha: u64, beta: u64, current_price: u64, thresholds: &Thresholds) -> ModelOutput {
+    let (_, _, predicted_price, confidence) = holt(history, alpha, beta);
+    ModelOutput { signal: thresholds.classify_prediction(current_price, predicted_price), confidence, predicted_price }
+}
+
+// Pairwise slopes are taken in millionths of a USD per day so their median does not truncate
+const THEIL_SEN_SCALE: i128 = 1_000_000;
+
+// Median of a non-empty list, averaging the middle two of an even-length one
+fn median_i128(mut values: Vec<i128>) -> i128 {
+    values.sort_unstable();
+    let mid = values.len() / 2;
+    if values.len() % 2 == 0 { (values[mid - 1] + values[mid]) / 2 } else { values[mid] }
+}
+
+// Slopes between every pair of points on different days, in THEIL_SEN_SCALE units. The pairs grow
+// with the square of the series, which MAX_HISTORY_LEN bounds
+fn pairwise_slopes(history: &[(u64, u64)]) -> Vec<i128> {
+    let mut slopes = Vec::with_capacity(history.len() * history.len().saturating_sub(1) / 2);
+    for (i, (x1, y1)) in history.iter().enumerate() {
+        for (x2, y2) in &history[i + 1..] {
+            if x1 != x2 {
+                slopes.push((*y2 as i128 - *y1 as i128) * THEIL_SEN_SCALE / (*x2 as i128 - *x1 as i128));
+            }
+        }
+    }
+    slopes
+}
+
+// Theil–Sen estimator (slope, intercept) in whole USD: the median of the pairwise slopes, and the
+// median of y - slope * x, so that a few bad points barely move the line
+fn theil_sen(history: &[(u64, u64)]) -> (i64, i64) {
+    let slopes = pairwise_slopes(history);
+    if slopes.is_empty() {
+        return (0, median_price(history) as i64);
+    }
+    let slope = median_i128(slopes) / THEIL_SEN_SCALE;
+    let intercept = median_i128(history.iter().map(|(x, y)| *y as i128 - slope * *x as i128).collect());
+    (slope as i64, intercept as i64)
+}
+
+// Extrapolates the Theil–Sen line to `next_day`
+// Confidence is the share of pairwise slopes pointing the same way as the line, which, unlike R²,
+// an ignored outlier only costs the pairs it is part of
+fn theil_sen_model(history: &[(u64, u64)], next_day: i64, current_price: u64, thresholds: &Thresholds) -> ModelOutput {
+    let (slope, intercept) = theil_sen(history);
+    let predicted_price = (slope * next_day + intercept).max(0) as u64;
+    let slopes = pairwise_slopes(history);
+    let agreeing = slopes.iter().filter(|pair_slope| pair_slope.signum() == slope.signum() as i128).count() as u64;
+    ModelOutput {
+        signal: thresholds.classify_prediction(current_price, predicted_price),
+        confidence: agreeing * BPS / (slopes.len() as u64).max(1),
+        predicted_price,
+    }
+}
+
+// Whether the regression slope points the same way as the last day-over-day change; a flat
+// slope or change only agrees with another flat one
+fn slope_agrees_with_last_delta(slope: i64, history: &[(u64, u64)]) -> bool {
+    let last_delta = match history {
+        [.., (_, previous), (_, last)] => *last as i128 - *previous as i128,
+        _ => 0,
+    };
+    slope.signum() as i128 == last_delta.signum()
+}
+
+// Scales confidence down by the largest gap between consecutive day indices, so that stale data
+// followed by a jump is trusted less than a contiguous series; contiguous series are unaffected
+fn penalize_gaps(confidence: u64, history: &[(u64, u64)]) -> u64 {
+    let max_gap = history
+        .windows(2)
+        .map(|pair| pair[1].0.saturating_sub(pair[0].0))
+        .max()
+        .unwrap_or(1);
+    let missing_days = max_gap.saturating_sub(1);
+    confidence * 100 / (100 + GAP_PENALTY_PCT_PER_DAY.saturating_mul(missing_days))
+}
+
+// Splits the confidence into (up, down) by the direction of the predicted move: the predicted
+// direction gets the confidence and the opposite one the rest, so the two always sum to 100
+fn directional_confidence(confidence: u64, predicted_price: u64, current_price: u64) -> (u64, u64) {
+    let opposing = 100u64.saturating_sub(confidence);
+    if predicted_price >= current_price {
+        (confidence, opposing)
+    } else {
+        (opposing, confidence)
+    }
+}
+
+// SplitMix64 generator. Every stochastic step draws from one seeded from the input, so a run is
+// reproducible and its proof verifiable
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // Uniform enough index below `len` for resampling; the modulo bias is negligible for short series
+    fn next_index(&mut self, len: usize) -> usize {
+        (self.next_u64() % len as u64) as usize
+    }
+}
+
+// Residual in whole USD of each point against the line (slope, intercept)
+fn residuals(history: &[(u64, u64)], slope: i64, intercept: i64) -> Vec<i64> {
+    history.iter().map(|(x, y)| *y as i64 - (slope * *x as i64 + intercept)).collect()
+}
+
+// R² of the fitted line over the last `window` points only, in basis points: their squared
+// residuals against the squared deviations from their own mean, so that a calm recent stretch is
+// trusted however noisy the older points were
+fn recent_r_squared(history: &[(u64, u64)], slope: i64, intercept: i64, window: u64) -> u64 {
+    let recent = &history[history.len() - (window as usize).min(history.len())..];
+    let mean_y = recent.iter().map(|(_, y)| *y as i64).sum::<i64>() / recent.len() as i64;
+    let (sum_squared_errors, sum_squared_total) = recent.iter().fold((0i128, 0i128), |(sse, sst), (x, y)| {
+        let error = (*y as i64 - (slope * *x as i64 + intercept)) as i128;
+        let deviation = (*y as i64 - mean_y) as i128;
+        (sse + error * error, sst + deviation * deviation)
+    });
+    if sum_squared_total == 0 {
+        return 0;
+    }
+    ((sum_squared_total - sum_squared_errors) * BPS as i128 / sum_squared_total).clamp(0, BPS as i128) as u64
+}
+
+// Bootstrapped confidence of the regression: the share, in basis points, of fits on resampled
+// (with replacement) series whose signal matches `signal`
+#[allow(clippy::too_many_arguments)]
+fn bootstrap_confidence(
+    history: &[(u64, u64)],
+    signal: Action,
+    samples: u64,
+    rng: &mut SplitMix64,
+    prior: (i64, u64),
+    next_day: i64,
+    current_price: u64,
+    thresholds: &Thresholds,
+) -> u64 {
+    let mut agreeing = 0;
+    for _ in 0..samples {
+        let resampled: Vec<(u64, u64)> = (0..history.len()).map(|_| history[rng.next_index(history.len())]).collect();
+        let line = blended_regression(&resampled, &[], prior.0, prior.1);
+        if linreg_model(line, next_day, current_price, thresholds).signal == signal {
+            agreeing += 1;
+        }
+    }
+    agreeing * BPS / samples
+}
+
+// Outputs of the models selected in `models`, in bitmask order, on `history`, the regression
+// extrapolating the `line` already fitted to it by `blended_regression`
+// The regression's confidence is its R², before any residual window or bootstrap replaces it
+#[allow(clippy::too_many_arguments)]
+fn model_outputs(
+    history: &[(u64, u64)],
+    line: (i64, i64, u64),
+    models: u64,
+    percentile_window: u64,
+    holt: (u64, u64),
+    next_day: i64,
+    current_price: u64,
+    thresholds: &Thresholds,
+) -> Vec<ModelOutput> {
+    let mut outputs = Vec::new();
+    if models & MODEL_LINREG != 0 {
+        outputs.push(linreg_model(line, next_day, current_price, thresholds));
+    }
+    if models & MODEL_SMA_CROSSOVER != 0 {
+        outputs.push(sma_crossover_model(history, thresholds));
+    }
+    if models & MODEL_PERCENTILE_RANK != 0 {
+        outputs.push(percentile_rank_model(history, percentile_window, current_price));
+    }
+    if models & MODEL_HOLT != 0 {
+        outputs.push(holt_model(history, holt.0, holt.1, current_price, thresholds));
+    }
+    if models & MODEL_THEIL_SEN != 0 {
+        outputs.push(theil_sen_model(history, next_day, current_price, thresholds));
+    }
+    outputs
+}
+
+// The most common of `votes`, HOLD on a tie, with the share of the votes it got out of `scale`
+fn majority_vote(votes: &[Action], scale: u64) -> (Action, u64) {
+    let count = |action: Action| votes.iter().filter(|&&vote| vote == action).count() as u64;
+    let (buys, sells) = (count(Action::Buy), count(Action::Sell));
+    let holds = votes.len() as u64 - buys - sells;
+    let winner = if buys > sells && buys > holds {
+        Action::Buy
+    } else if sells > buys && sells > holds {
+        Action::Sell
+    } else {
+        Action::Hold
+    };
+    (winner, count(winner) * scale / votes.len() as u64)
+}
+
+// Emits BUY or SELL only when every model agrees and HOLD (2) otherwise
+// The predicted price is averaged across the models, and confidence is their mean, their minimum or
+// the product of the confidences as probabilities, depending on `aggregation`. Confidences are out
+// of `scale`.
+fn ensemble(outputs: &[ModelOutput], aggregation: u64, scale: u64) -> ModelOutput {
+    let n = outputs.len() as u64;
+    let confidences = outputs.iter().map(|output| output.confidence);
+    let first_signal = outputs[0].signal;
+    let signal = if outputs.iter().all(|output| output.signal == first_signal) {
+        first_signal
+    } else {
+        Action::Hold
+    };
+    ModelOutput {
+        signal,
+        confidence: match aggregation {
+            CONFIDENCE_MEAN => confidences.sum::<u64>() / n,
+            CONFIDENCE_MIN => confidences.min().unwrap_or(0),
+            CONFIDENCE_PRODUCT => confidences.fold(scale, |product, confidence| product * confidence / scale),
+            _ => unreachable!("unknown ensemble confidence aggregation {}", aggregation),
+        },
+        predicted_price: outputs.iter().map(|output| output.predicted_price).sum::<u64>() / n,
+    }
+}
+
+// How the committed prediction is rounded to base units: towards zero, half up or up
+const ROUNDING_TRUNC: u64 = 0;
+const ROUNDING_NEAREST: u64 = 1;
+const ROUNDING_UP: u64 = 2;
+
+// Divides `numerator` by a nonzero `denominator`, rounding the quotient as `rounding` says
+fn round_div(numerator: U256, denominator: U256, rounding: u64) -> U256 {
+    let quotient = numerator / denominator;
+    let remainder = numerator % denominator;
+    let round_up = match rounding {
+        ROUNDING_TRUNC => false,
+        ROUNDING_NEAREST => remainder * U256::from(2u64) >= denominator,
+        ROUNDING_UP => !remainder.is_zero(),
+        _ => unreachable!("unknown rounding mode {}", rounding),
+    };
+    if round_up { quotient + U256::from(1u64) } else { quotient }
+}
+
+// Fewest price points the linear regression and Holt models need to act on a series: a line
+// through two points fits perfectly, so its R² says nothing
+const LINREG_WARMUP: usize = 3;
+const HOLT_WARMUP: usize = 3;
+const THEIL_SEN_WARMUP: usize = 3;
+
+// Fewest price points the selected models need before their signal is acted on: the slow
+// moving average's window for the SMA crossover and the ranking window for the percentile rank
+fn warmup_len(models: u64, percentile_window: u64) -> usize {
+    [
+        (MODEL_LINREG, LINREG_WARMUP),
+        (MODEL_SMA_CROSSOVER, SMA_SLOW_WINDOW),
+        (MODEL_PERCENTILE_RANK, percentile_window as usize),
+        (MODEL_HOLT, HOLT_WARMUP),
+        (MODEL_THEIL_SEN, THEIL_SEN_WARMUP),
+    ]
+    .into_iter()
+    .filter(|(model, _)| models & model != 0)
+    .map(|(_, len)| len)
+    .max()
+    .unwrap_or(0)
+}
+
+// Lookback periods of the report indicators
+const RSI_PERIOD: usize = 14;
+const MACD_FAST_PERIOD: i128 = 12;
+const MACD_SLOW_PERIOD: i128 = 26;
+const MACD_SIGNAL_PERIOD: i128 = 9;
+// MACD is computed and committed in millionths of a USD so small histograms don't truncate to 0
+const MACD_SCALE: i128 = 1_000_000;
+
+// Relative strength index (0-100) over the last RSI_PERIOD price changes, 50 for a flat series
+fn relative_strength_index(history: &[(u64, u64)]) -> u64 {
+    let recent = &history[history.len().saturating_sub(RSI_PERIOD + 1)..];
+    let (gains, losses) = recent.windows(2).fold((0u64, 0u64), |(gains, losses), pair| {
+        let (previous, current) = (pair[0].1, pair[1].1);
+        if current > previous {
+            (gains + (current - previous), losses)
+        } else {
+            (gains, losses + (previous - current))
+        }
+    });
+    // 100 - 100 / (1 + gains / losses) simplifies to 100 * gains / (gains + losses)
+    if gains + losses == 0 { 50 } else { gains * 100 / (gains + losses) }
+}
+
+// MACD histogram: the MACD line (fast EMA - slow EMA) minus its signal EMA, in millionths of a USD
+// Each EMA is seeded with the first value and uses the usual 2 / (period + 1) smoothing factor
+fn macd_histogram(history: &[(u64, u64)]) -> i64 {
+    let first_price = history[0].1 as i128 * MACD_SCALE;
+    let (mut fast, mut slow, mut signal) = (first_price, first_price, 0i128);
+    for (x, (_, y)) in history.iter().enumerate() {
+        let price = *y as i128 * MACD_SCALE;
+        fast += (price - fast) * 2 / (MACD_FAST_PERIOD + 1);
+        slow += (price - slow) * 2 / (MACD_SLOW_PERIOD + 1);
+        let macd = fast - slow;
+        signal = if x == 0 { macd } else { signal + (macd - signal) * 2 / (MACD_SIGNAL_PERIOD + 1) };
+    }
+    (fast - slow - signal) as i64
+}
+
+// Decodes the input after its leading format byte
+fn decode_input(input_bytes: &[u8]) -> Result<GuestInput, String> {
+    let (&format, payload) = input_bytes.split_first().ok_or("empty input")?;
+    match format {
+        INPUT_FORMAT_ABI => GuestInput::abi_decode(payload).map_err(|err| format!("malformed ABI input: {}", err)),
+        INPUT_FORMAT_JSON => serde_json::from_slice(payload).map_err(|err| format!("malformed JSON input: {}", err)),
+        _ => Err(format!("unknown input format {}", format)),
+    }
+}
+
+// Halts with a code the host can recognize as invalid input, rather than panic with an opaque trap
+fn reject(err: String) -> ! {
+    eprintln!("invalid guest input: {}", err);
+    env::exit(EXIT_INVALID_INPUT)
+}
+
+// Fails with the reason of the first check the decoded input does not pass
+macro_rules! ensure {
+    ($condition:expr, $($reason:tt)+) => {
+        if !$condition {
+            return Err(format!($($reason)+));
+        }
+    };
+}
+
+// Checks every parameter against the range the guest supports, before any of them is used
+fn validate(input: &GuestInput) -> Result<(), String> {
+    let GuestInput {
+        current_price,
+        history,
+        outlier_k,
+        models,
+        prior_weight,
+        raw_prediction,
+        decimals,
+        bootstrap_samples,
+        journal_version,
+        legacy_encoding,
+        min_r2,
+        ensemble_confidence,
+        bps_confidence,
+        holt_alpha,
+        holt_beta,
+        buy_above_usd,
+        sell_below_usd,
+        rounding,
+        predicted_return,
+        residual_window,
+        candles,
+        max_confidence,
+        residuals,
+        horizons,
+        timestamps,
+        half_life_secs,
+        image_id,
+        ..
+    } = input;
+    ensure!(*decimals <= MAX_DECIMALS, "{} decimals exceeds the limit of {}", decimals, MAX_DECIMALS);
+    let unit_scale = U256::from(10u64).pow(U256::from(*decimals));
+    ensure!(
+        *current_price / unit_scale <= U256::from(u64::MAX),
+        "current price {} exceeds 64 bits in whole units",
+        current_price
+    );
+    ensure!(
+        history.len() <= MAX_HISTORY_LEN,
+        "price history has {} points, exceeding the guest limit of {}",
+        history.len(),
+        MAX_HISTORY_LEN
+    );
+    ensure!(candles.len() <= MAX_HISTORY_LEN, "{} candles exceed the guest limit of {}", candles.len(), MAX_HISTORY_LEN);
+    ensure!(candles.is_empty() || history.is_empty(), "supply either a price history or candles, not both");
+    for &(day, open, high, low, close) in candles {
+        ensure!(
+            low <= open.min(close) && high >= open.max(close),
+            "candle of day {} has open {} and close {} outside its low {} and high {}",
+            day,
+            open,
+            close,
+            low,
+            high
+        );
+    }
+    ensure!(models & !ALL_MODELS == 0, "unknown models in bitmask {:#x}", models);
+    ensure!(*prior_weight <= 100, "prior weight {} exceeds 100", prior_weight);
+    ensure!(*min_r2 <= 100, "minimum R² {} exceeds 100", min_r2);
+    ensure!(*max_confidence <= 100, "maximum confidence {} exceeds 100", max_confidence);
+    ensure!(*holt_alpha <= 100 && *holt_beta <= 100, "Holt smoothing factors {}/{} exceed 100", holt_alpha, holt_beta);
+    ensure!(*rounding <= ROUNDING_UP, "unknown rounding mode {}", rounding);
+    ensure!(
+        *residual_window == 0 || *bootstrap_samples == 0,
+        "the regression's confidence comes from either a residual window or bootstrapping"
+    );
+    ensure!(*ensemble_confidence <= CONFIDENCE_PRODUCT, "unknown ensemble confidence aggregation {}", ensemble_confidence);
+    ensure!(
+        *bootstrap_samples <= MAX_BOOTSTRAP_SAMPLES,
+        "{} bootstrap samples exceed the limit of {}",
+        bootstrap_samples,
+        MAX_BOOTSTRAP_SAMPLES
+    );
+    ensure!(*journal_version <= LATEST_JOURNAL_VERSION as u64, "unknown journal version {}", journal_version);
+    let journal_version = if *journal_version == 0 { LATEST_JOURNAL_VERSION } else { *journal_version as u8 };
+    // Journal version 10 or later, in its current layout
+    let extended = journal_version >= JOURNAL_V10 && !legacy_encoding && !raw_prediction;
+    ensure!(
+        !bps_confidence || *raw_prediction || (journal_version >= JOURNAL_V5 && !legacy_encoding),
+        "basis-point confidence is only committed from journal version {}",
+        JOURNAL_V5
+    );
+    ensure!(
+        !predicted_return || (journal_version >= JOURNAL_V7 && !legacy_encoding && !raw_prediction),
+        "the predicted return is only committed from journal version {}",
+        JOURNAL_V7
+    );
+    ensure!(!predicted_return || !current_price.is_zero(), "a zero current price has no return");
+    ensure!(!residuals || extended, "residuals are only committed on top of journal version {}", JOURNAL_V10);
+    ensure!(*horizons == 0 || extended, "horizon signals are only committed on top of journal version {}", JOURNAL_V10);
+    ensure!(image_id.is_zero() || extended, "the strategy ID is only committed on top of journal version {}", JOURNAL_V10);
+    ensure!(timestamps.is_empty() == (*half_life_secs == 0), "point timestamps and a decay half-life go together");
+    ensure!(
+        timestamps.is_empty() || timestamps.len() == history.len(),
+        "{} timestamps for {} price history points",
+        timestamps.len(),
+        history.len()
+    );
+    // Both reshape the series, which would leave the weights on the wrong points
+    ensure!(
+        *half_life_secs == 0 || (*outlier_k == 0 && *bootstrap_samples == 0),
+        "time decay cannot be combined with outlier trimming or bootstrapping"
+    );
+    ensure!(
+        *buy_above_usd == 0 || sell_below_usd <= buy_above_usd,
+        "SELL target ${} is above the BUY target ${}",
+        sell_below_usd,
+        buy_above_usd
+    );
+    Ok(())
 }
 
 fn main() {
-    // Read the input data - this is the current USD price per ETH
-    // For example: 3200 means $3200 per ETH
+    // Read the input data - a format byte followed by a GuestInput, see its fields in the journal
+    // crate. The current price is given in base units (price * 10^decimals); the history is in
+    // whole USD. For example: 3200 means $3200 per ETH with 0 decimals, 3200000000 with 6
     let mut input_bytes = Vec::<u8>::new();
     env::stdin().read_to_end(&mut input_bytes).unwrap();
-    let current_usd_price = <U256>::abi_decode(&input_bytes).unwrap();
-    let current_usd_price_per_eth = current_usd_price.as_limbs()[0];
-    
-    // Perform linear regression on USD prices
-    let (slope, intercept, confidence) = linear_regression();
-    
-    // Predict next day USD price (day 31)
-    let next_day = 31i64;
-    let predicted_usd_price_per_eth = (slope * next_day + intercept) as u64;
-    
-    // Generate trading signal
-    // BUY (1) if predicted USD price is > 0.5% higher than current USD price
-    // SELL (0) otherwise
-    let price_threshold = current_usd_price_per_eth + (current_usd_price_per_eth / 200); // 0.5% increase
-    let signal = if predicted_usd_price_per_eth > price_threshold { 1u8 } else { 0u8 };
-    
-    // Create the exact same journal format as the contract expects: abi.encode(uint8, uint256, uint256)  
-    // Output format: (signal, confidence_percentage, predicted_usd_price)
-    let confidence_u256 = U256::from(confidence);
-    let price_u256 = U256::from(predicted_usd_price_per_eth);
-    
-    // Use manual encoding that exactly matches Solidity's abi.encode for (uint8, uint256, uint256)
-    let mut journal_data = Vec::new();
-    
-    // For Solidity abi.encode, uint8 is right-aligned in 32 bytes (big-endian padding)
-    let mut action_bytes = [0u8; 32];
-    action_bytes[31] = signal; // Right-aligned (value in least significant byte)
-    journal_data.extend_from_slice(&action_bytes);
-    
-    // U256 values are encoded as 32-byte big-endian
-    journal_data.extend_from_slice(&confidence_u256.to_be_bytes::<32>());
-    journal_data.extend_from_slice(&price_u256.to_be_bytes::<32>());
-    
-    env::commit_slice(&journal_data);
-}
\ No newline at end of file
+    let input = match decode_input(&input_bytes) {
+        Ok(input) => input,
+        Err(err) => reject(err),
+    };
+    if let Err(err) = validate(&input) {
+        reject(err);
+    }
+    let GuestInput {
+        current_price: current_usd_price,
+        history: input_history,
+        outlier_k,
+        models,
+        prior_slope,
+        prior_weight,
+        buy_bps,
+        sell_bps,
+        report,
+        raw_prediction,
+        decimals,
+        clamp_factor,
+        seed,
+        bootstrap_samples,
+        require_agreement,
+        journal_version,
+        legacy_encoding,
+        min_r2,
+        percentile_window,
+        ensemble_confidence,
+        bps_confidence,
+        holt_alpha,
+        holt_beta,
+        buy_above_usd,
+        sell_below_usd,
+        rounding,
+        warmup,
+        predicted_return,
+        residual_window,
+        candles,
+        vote_windows,
+        max_confidence,
+        residuals: commit_residuals,
+        horizons,
+        timestamps,
+        half_life_secs,
+        image_id,
+    } = input;
+    // Hashed as supplied, before zeroes are replaced by their defaults, so a verifier can hash the
+    // request's own parameters
+    let params_hash = StrategyParams {
+        models,
+        outlier_k,
+        prior_slope,
+        prior_weight,
+        buy_bps,
+        sell_bps,
+        clamp_factor,
+        seed,
+        bootstrap_samples,
+        require_agreement,
+        min_r2,
+        percentile_window,
+        ensemble_confidence,
+        holt_alpha,
+        holt_beta,
+        buy_above_usd,
+        sell_below_usd,
+        rounding,
+        warmup,
+        residual_window,
+        vote_windows,
+        max_confidence,
+        horizons,
+        half_life_secs,
+    }
+    .hash();
+    let unit_scale = U256::from(10u64).pow(U256::from(decimals));
+    let current_usd_price_per_eth = (current_usd_price / unit_scale).to::<u64>();
+    let models = if models == 0 { MODEL_LINREG } else { models };
+    let max_confidence = if max_confidence == 0 { PERCENT } else { max_confidence };
+    let holt_alpha = if holt_alpha == 0 { HOLT_DEFAULT_ALPHA } else { holt_alpha };
+    let holt_beta = if holt_beta == 0 { HOLT_DEFAULT_BETA } else { holt_beta };
+    let journal_version = if journal_version == 0 { LATEST_JOURNAL_VERSION } else { journal_version as u8 };
+    let scale = if bps_confidence { BPS } else { PERCENT };
+    let mut rng = SplitMix64(seed);
+    let thresholds = Thresholds { buy_bps, sell_bps, buy_above: buy_above_usd, sell_below: sell_below_usd };
+
+    // Hash the series exactly as it was supplied so the journal binds the signal to its input data.
+    // An empty series (embedded history) is already pinned by the image ID. Candles are hashed in
+    // place of the history they replace, and the timestamps of the points along with them.
+    let input_hash = if !candles.is_empty() {
+        keccak256(candles.abi_encode())
+    } else if !timestamps.is_empty() {
+        keccak256((input_history.clone(), timestamps.clone()).abi_encode())
+    } else {
+        keccak256(input_history.abi_encode())
+    };
+    let input_history = if candles.is_empty() { input_history } else { typical_prices(&candles) };
+
+    // Fall back to the embedded series when no history is supplied
+    let history: &[(u64, u64)] = if input_history.is_empty() {
+        &PRICE_HISTORY
+    } else {
+        &input_history
+    };
+
+    // Predict the day after the last supplied point, even if that point is trimmed below, and commit
+    // its price as the baseline of the prediction
+    let next_day = history[history.len() - 1].0 as i64 + 1;
+    let last_price = history[history.len() - 1].1;
+
+    // Optionally pull absurd ticks back towards the median before anything is fitted to them
+    let (history, clamped_points) = if clamp_factor > 0 {
+        clamp_to_median(history, clamp_factor)
+    } else {
+        (history.to_vec(), 0)
+    };
+
+    // Optionally drop points far from the fitted line; every model runs on the remaining points
+    let trimmed_history = if outlier_k > 0 {
+        let (slope, intercept, _) = linear_regression(&history);
+        trim_outliers(&history, slope, intercept, outlier_k)
+    } else {
+        history.clone()
+    };
+    let trimmed_points = history.len() - trimmed_history.len();
+    // Without trimming the points keep their order, so each keeps the weight of its timestamp
+    let weights = if half_life_secs > 0 { decay_weights(&timestamps, half_life_secs) } else { Vec::new() };
+    // Both depend on the series the input selects and trims, so they are checked once it is known
+    if commit_residuals && history.len() > MAX_RESIDUAL_POINTS {
+        reject(format!("residuals of {} points exceed the limit of {}", history.len(), MAX_RESIDUAL_POINTS));
+    }
+    if vote_windows >= trimmed_history.len() as u64 {
+        reject(format!("{} vote windows leave fewer than 2 points in the shortest", vote_windows));
+    }
+
+    // Run the selected models and combine their outputs
+    // The regression slope is committed so the effect of the prior is visible; 0 if it did not run
+    let prior = (prior_slope, prior_weight);
+    let holt = (holt_alpha, holt_beta);
+    // Fitted once: the line every model output extrapolates, whose R², slope and residuals the
+    // gates, the report panel and the journal describe
+    let line = blended_regression(&trimmed_history, &weights, prior_slope, prior_weight);
+    let mut outputs = model_outputs(
+        &trimmed_history,
+        line,
+        models,
+        percentile_window,
+        holt,
+        next_day,
+        current_usd_price_per_eth,
+        &thresholds,
+    );
+    let mut slope = 0i64;
+    if models & MODEL_LINREG != 0 {
+        slope = line.0;
+        // The regression always comes first
+        let output = &mut outputs[0];
+        if residual_window > 0 {
+            output.confidence = recent_r_squared(&trimmed_history, line.0, line.1, residual_window);
+        }
+        if bootstrap_samples > 0 {
+            output.confidence = bootstrap_confidence(
+                &trimmed_history,
+                output.signal,
+                bootstrap_samples,
+                &mut rng,
+                prior,
+                next_day,
+                current_usd_price_per_eth,
+                &thresholds,
+            );
+        }
+    }
+    for output in &mut outputs {
+        output.confidence = output.confidence * scale / BPS;
+    }
+    let ModelOutput { signal, confidence, predicted_price } = ensemble(&outputs, ensemble_confidence, scale);
+    // A HOLD out of the ensemble is either every model holding or the models disagreeing
+    let ensemble_reason = if signal != Action::Hold {
+        Reason::None
+    } else if outputs.iter().all(|output| output.signal == Action::Hold) {
+        Reason::DeadBand
+    } else {
+        Reason::ModelsDisagree
+    };
+    let ensemble_signal = signal;
+    // Optionally smooth out single-window noise: every window ends one point before the next and
+    // predicts the same day, and the majority of their ensemble signals is committed with the share
+    // of windows that agree as its confidence. The prediction stays that of the whole series.
+    let (signal, confidence) = if vote_windows > 1 {
+        let votes: Vec<Action> = (0..vote_windows as usize)
+            .map(|dropped| {
+                if dropped == 0 {
+                    return signal;
+                }
+                let window = &trimmed_history[..trimmed_history.len() - dropped];
+                let window_weights = &weights[..weights.len().min(window.len())];
+                let outputs = model_outputs(
+                    window,
+                    blended_regression(window, window_weights, prior_slope, prior_weight),
+                    models,
+                    percentile_window,
+                    holt,
+                    next_day,
+                    current_usd_price_per_eth,
+                    &thresholds,
+                );
+                ensemble(&outputs, ensemble_confidence, scale).signal
+            })
+            .collect();
+        majority_vote(&votes, scale)
+    } else {
+        (signal, confidence)
+    };
+    // The ensemble's reason only stands if the vote kept its signal
+    let mut reason = if signal == ensemble_signal {
+        ensemble_reason
+    } else if signal == Action::Hold {
+        Reason::WindowsOutvoted
+    } else {
+        Reason::None
+    };
+    // A cheap noise filter: the trend and the latest move should point the same way
+    let agreement = slope_agrees_with_last_delta(line.0, &trimmed_history);
+    if require_agreement && !agreement && signal != Action::Hold {
+        reason = Reason::SlopeDisagrees;
+    }
+    let signal = if require_agreement && !agreement { Action::Hold } else { signal };
+    // Optionally require the signal to hold further out: the ensemble predicts the day each
+    // requested horizon lands on, and BUY or SELL stands only if every horizon signals the same
+    let horizon_signals: Vec<(u64, Action)> = (1..=MAX_HORIZON)
+        .filter(|horizon| (horizons >> (horizon - 1)) & 1 == 1)
+        .map(|horizon| {
+            let outputs = model_outputs(
+                &trimmed_history,
+                line,
+                models,
+                percentile_window,
+                holt,
+                next_day - 1 + horizon as i64,
+                current_usd_price_per_eth,
+                &thresholds,
+            );
+            (horizon, ensemble(&outputs, ensemble_confidence, scale).signal)
+        })
+        .collect();
+    let horizons_agree = horizon_signals.iter().all(|(_, horizon_signal)| *horizon_signal == signal);
+    if !horizons_agree && signal != Action::Hold {
+        reason = Reason::HorizonsDisagree;
+    }
+    let signal = if horizons_agree { signal } else { Action::Hold };
+    // Gaps are measured on the supplied series, before trimming opens new ones
+    let confidence = penalize_gaps(confidence, &history);
+    // Refuse to act on a poor fit: below the minimum R² the signal is HOLD, with the R² as its
+    // confidence. Only a signal the regression model took part in rests on the fit
+    let r_squared_bps = line.2;
+    let (signal, confidence) = if models & MODEL_LINREG != 0 && r_squared_bps * PERCENT / BPS < min_r2 {
+        if signal != Action::Hold {
+            reason = Reason::LowRSquared;
+        }
+        (Action::Hold, r_squared_bps * scale / BPS)
+    } else {
+        (signal, confidence)
+    };
+    // A series too short for a model to have warmed up is no basis for acting, whatever the
+    // other gates decided
+    let warming_up = warmup && trimmed_history.len() < warmup_len(models, percentile_window);
+    if warming_up {
+        reason = Reason::WarmingUp;
+    }
+    let signal = if warming_up { Action::Hold } else { signal };
+    // No fit to a market warrants certainty: hold the confidence under the requested ceiling
+    let confidence = confidence.min(max_confidence * scale / PERCENT);
+    // The contract takes a whole percentage, so that is what leads the journal
+    let confidence_bps = confidence * (BPS / scale);
+    let confidence = confidence_bps / (BPS / PERCENT);
+    let (up_confidence, down_confidence) =
+        directional_confidence(confidence, predicted_price, current_usd_price_per_eth);
+    // Models work in whole USD; the committed prediction is their mean in base units like the
+    // current price, rounded as asked rather than truncated to whole USD first
+    let predicted_sum: u64 = outputs.iter().map(|output| output.predicted_price).sum();
+    let predicted_price = round_div(U256::from(predicted_sum) * unit_scale, U256::from(outputs.len()), rounding);
+
+    // Raw prediction mode leaves the decision to the consumer: commit abi.encode(uint256, uint256)
+    // as (predicted_usd_price, confidence) and nothing else, with the confidence in basis points if
+    // asked for and as a percentage otherwise
+    if raw_prediction {
+        let raw_confidence = if bps_confidence { confidence_bps } else { confidence };
+        let mut journal_data = Vec::new();
+        journal_data.extend_from_slice(&predicted_price.to_be_bytes::<32>());
+        journal_data.extend_from_slice(&U256::from(raw_confidence).to_be_bytes::<32>());
+        env::commit_slice(&journal_data);
+        return;
+    }
+
+    // The predicted return is taken between the prices in base units, so that a fraction of a
+    // dollar still counts. A current price of a few base units can put it beyond i64, where it
+    // saturates rather than failing the run
+    let predicted_return_bps = if predicted_return {
+        let current = I256::from_raw(current_usd_price);
+        let return_bps = (I256::from_raw(predicted_price) - current) * I256::try_from(BPS).unwrap() / current;
+        i64::try_from(return_bps).unwrap_or(if return_bps.is_negative() { i64::MIN } else { i64::MAX })
+    } else {
+        0
+    };
+
+    // Compute the full indicator panel in report mode, whichever models drove the signal
+    let panel = if report {
+        IndicatorPanel {
+            regression_price: linreg_model(line, next_day, current_usd_price_per_eth, &thresholds).predicted_price,
+            rsi: relative_strength_index(&trimmed_history),
+            macd_histogram: macd_histogram(&trimmed_history),
+            sma_fast: simple_moving_average(&trimmed_history, SMA_FAST_WINDOW),
+            sma_slow: simple_moving_average(&trimmed_history, SMA_SLOW_WINDOW),
+        }
+    } else {
+        IndicatorPanel::default()
+    };
+
+    // The contract only decodes the leading (signal, confidence, predicted_price, input_hash); the
+    // rest of the layout is selected by the version committed in the last word, except for the
+    // legacy layout, which overrides the version
+    let journal = match journal_version {
+        JOURNAL_V1 if !legacy_encoding => Journal::V1(JournalV1 { signal, confidence, predicted_price, input_hash }),
+        _ => {
+            let v2 = JournalV2 {
+                signal,
+                confidence,
+                predicted_price,
+                input_hash,
+                trimmed_points: trimmed_points as u64,
+                models,
+                slope,
+                panel,
+                clamped_points,
+                agreement,
+            };
+            let v3 = JournalV3 { base: v2.clone(), up_confidence, down_confidence };
+            // The models were fitted to the clamped and trimmed series
+            let v4 = JournalV4 { base: v3.clone(), data_points: trimmed_history.len() as u64 };
+            match journal_version {
+                _ if legacy_encoding => Journal::Legacy(v2),
+                JOURNAL_V2 => Journal::V2(v2),
+                JOURNAL_V3 => Journal::V3(v3),
+                JOURNAL_V4 => Journal::V4(v4),
+                JOURNAL_V5 => Journal::V5(JournalV5 { base: v4, confidence_bps }),
+                JOURNAL_V6 => Journal::V6(JournalV6 { base: JournalV5 { base: v4, confidence_bps }, reason }),
+                JOURNAL_V7 => Journal::V7(JournalV7 {
+                    base: JournalV6 { base: JournalV5 { base: v4, confidence_bps }, reason },
+                    predicted_return_bps,
+                }),
+                JOURNAL_V8 => Journal::V8(JournalV8 {
+                    base: JournalV7 {
+                        base: JournalV6 { base: JournalV5 { base: v4, confidence_bps }, reason },
+                        predicted_return_bps,
+                    },
+                    params_hash,
+                }),
+                JOURNAL_V9 => Journal::V9(JournalV9 {
+                    base: JournalV8 {
+                        base: JournalV7 {
+                            base: JournalV6 { base: JournalV5 { base: v4, confidence_bps }, reason },
+                            predicted_return_bps,
+                        },
+                        params_hash,
+                    },
+                    last_price,
+                }),
+                // The unit of the predicted price, so that the value stored on chain describes itself
+                _ => {
+                    let v10 = JournalV10 {
+                        base: JournalV9 {
+                            base: JournalV8 {
+                                base: JournalV7 {
+                                    base: JournalV6 { base: JournalV5 { base: v4, confidence_bps }, reason },
+                                    predicted_return_bps,
+                                },
+                                params_hash,
+                            },
+                            last_price,
+                        },
+                        decimals,
+                    };
+                    // Every point the guest fitted, trimmed ones included so that their outliers show,
+                    // against the line the regression model extrapolates
+                    let residuals = if commit_residuals {
+                        residuals(&history, line.0, line.1)
+                    } else {
+                        Vec::new()
+                    };
+                    match (commit_residuals, horizons, image_id.is_zero()) {
+                        (false, 0, true) => Journal::V10(v10),
+                        (true, 0, true) => Journal::V11(JournalV11 { base: v10, residuals }),
+                        (_, _, true) => Journal::V12(JournalV12 {
+                            base: JournalV11 { base: v10, residuals },
+                            horizons: horizon_signals,
+                        }),
+                        // Verifiers check the image ID against the one the proof verifies under
+                        (_, _, false) => Journal::V13(JournalV13 {
+                            base: JournalV12 { base: JournalV11 { base: v10, residuals }, horizons: horizon_signals },
+                            strategy_id: strategy_id(image_id, params_hash),
+                        }),
+                    }
+                }
+            }
+        }
+    };
+    env::commit_slice(&journal.encode());
+}
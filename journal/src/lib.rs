@@ -0,0 +1,1911 @@
+// Copyright 2024 RISC Zero, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Journal layouts committed by the trading signal guest.
+//!
+//! The guest encodes and the host decodes through this crate so the two cannot drift apart. Every
+//! layout is a flat `abi.encode` of static words that starts with
+//! `(uint8 signal, uint256 confidence, uint256 predicted_price, bytes32 input_hash)`, which is all
+//! the TradingSignal contract decodes, and ends with a `uint256` version word that selects the
+//! layout when decoding. The one exception is the unversioned legacy layout, kept while consumers
+//! of it migrate. [format_wei] displays the base-unit amounts the journals carry, and
+//! [PRICE_HISTORY] is the series the guest embeds.
+//!
+//! [GuestInput] is the other direction: what the host writes to the guest's stdin.
+
+use std::fmt;
+
+use alloy_primitives::{keccak256, B256, I256, U256};
+use alloy_sol_types::SolValue;
+use serde::{Deserialize, Serialize};
+
+/// The v2 words with no version word, hand-encoded the way the guest did before layouts were
+/// versioned; recognized by its length.
+pub const JOURNAL_LEGACY: u8 = 0;
+/// Compact layout carrying only what the contract stores.
+pub const JOURNAL_V1: u8 = 1;
+/// Full layout with the model diagnostics and the indicator panel.
+pub const JOURNAL_V2: u8 = 2;
+/// The v2 layout followed by the upside and downside confidence.
+pub const JOURNAL_V3: u8 = 3;
+/// The v3 layout followed by the number of price points the models used.
+pub const JOURNAL_V4: u8 = 4;
+/// The v4 layout followed by the confidence in basis points.
+pub const JOURNAL_V5: u8 = 5;
+/// The v5 layout followed by the [Reason] for the signal.
+pub const JOURNAL_V6: u8 = 6;
+/// The v6 layout followed by the predicted return in basis points.
+pub const JOURNAL_V7: u8 = 7;
+/// The v7 layout followed by the hash of the [StrategyParams].
+pub const JOURNAL_V8: u8 = 8;
+/// The v8 layout followed by the last price of the input series.
+pub const JOURNAL_V9: u8 = 9;
+/// The v9 layout followed by the decimals of the predicted price.
+pub const JOURNAL_V10: u8 = 10;
+/// The v10 layout followed by the regression residual of every price point. Only committed on
+/// request, since its length grows with the series.
+pub const JOURNAL_V11: u8 = 11;
+/// The v11 layout followed by the signal predicted at each of the horizons the signal had to
+/// agree with. Only committed on request.
+pub const JOURNAL_V12: u8 = 12;
+/// The v12 layout followed by the [strategy_id] of the run, for a contract to only accept the
+/// strategies it approved. Only committed on request.
+pub const JOURNAL_V13: u8 = 13;
+/// Layout the guest commits unless asked for another.
+pub const LATEST_JOURNAL_VERSION: u8 = JOURNAL_V10;
+
+/// The 30 days of (day_index, usd_price_per_eth) the guest fits when no history is supplied, in
+/// whole USD, e.g. 3200 means $3200 per ETH.
+pub const PRICE_HISTORY: [(u64, u64); 30] = [
+    (1, 3200),
+    (2, 3215),
+    (3, 3189),
+    (4, 3221),
+    (5, 3254),
+    (6, 3278),
+    (7, 3242),
+    (8, 3291),
+    (9, 3315),
+    (10, 3287),
+    (11, 3324),
+    (12, 3352),
+    (13, 3389),
+    (14, 3412),
+    (15, 3398),
+    (16, 3436),
+    (17, 3462),
+    (18, 3489),
+    (19, 3453),
+    (20, 3507),
+    (21, 3534),
+    (22, 3561),
+    (23, 3528),
+    (24, 3582),
+    (25, 3615),
+    (26, 3648),
+    (27, 3621),
+    (28, 3674),
+    (29, 3702),
+    (30, 3735),
+];
+
+/// Exit code of a guest that could not decode its input or rejected its parameters, in which case
+/// it commits no journal.
+pub const EXIT_INVALID_INPUT: u8 = 2;
+
+/// Leading input byte selecting an ABI-encoded [GuestInput].
+pub const INPUT_FORMAT_ABI: u8 = 0;
+/// Leading input byte selecting a JSON [GuestInput], whose omitted fields are zero.
+pub const INPUT_FORMAT_JSON: u8 = 1;
+
+/// Size of an ABI word.
+const WORD: usize = 32;
+
+type V1Words = (U256, U256, U256, B256);
+type V2Words = (
+    U256,
+    U256,
+    U256,
+    B256,
+    U256,
+    U256,
+    I256,
+    U256,
+    U256,
+    I256,
+    U256,
+    U256,
+    U256,
+    bool,
+);
+type V3Words = (U256, U256);
+type V4Words = (U256,);
+type V5Words = (U256,);
+type V6Words = (U256,);
+type V7Words = (I256,);
+type V8Words = (B256,);
+type V9Words = (U256,);
+type V10Words = (U256,);
+type ResidualWord = (I256,);
+type HorizonWords = (U256, U256);
+type V13Words = (B256,);
+type InputWords = (
+    U256,
+    Vec<(u64, u64)>,
+    u64,
+    u64,
+    i64,
+    u64,
+    u64,
+    u64,
+    bool,
+    bool,
+    u64,
+    u64,
+    u64,
+    u64,
+    bool,
+    u64,
+    bool,
+    u64,
+    u64,
+    u64,
+    bool,
+    u64,
+    u64,
+    u64,
+    u64,
+    u64,
+    bool,
+    bool,
+    u64,
+    Vec<(u64, u64, u64, u64, u64)>,
+    u64,
+    u64,
+    bool,
+    u64,
+    Vec<u64>,
+    u64,
+    B256,
+);
+
+/// Why the guest committed its signal. Every reason but [Reason::None] names the step that turned
+/// the signal into HOLD.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Reason {
+    /// The signal is BUY or SELL, or a HOLD from a journal too old to say why.
+    #[default]
+    None = 0,
+    /// Every model held: the predicted move is within the BUY/SELL thresholds.
+    DeadBand = 1,
+    /// The models of an ensemble disagreed.
+    ModelsDisagree = 2,
+    /// The regression slope and the last day-over-day change point different ways.
+    SlopeDisagrees = 3,
+    /// The regression fits the history with an R² below the requested minimum.
+    LowRSquared = 4,
+    /// The series is shorter than a model needs to warm up.
+    WarmingUp = 5,
+    /// The signal predicted at one of the requested horizons differs.
+    HorizonsDisagree = 6,
+    /// The windows voting under `vote_windows` did not back the signal of the whole series.
+    WindowsOutvoted = 7,
+}
+
+impl Reason {
+    /// Returns the reason committed as `code`, if there is one.
+    pub fn from_code(code: u8) -> Option<Self> {
+        [
+            Self::None,
+            Self::DeadBand,
+            Self::ModelsDisagree,
+            Self::SlopeDisagrees,
+            Self::LowRSquared,
+            Self::WarmingUp,
+            Self::HorizonsDisagree,
+            Self::WindowsOutvoted,
+        ]
+        .into_iter()
+        .find(|reason| *reason as u8 == code)
+    }
+}
+
+/// Action the guest commits as its `uint8 signal`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "UPPERCASE")]
+#[repr(u8)]
+pub enum Action {
+    Sell = 0,
+    Buy = 1,
+    /// Do nothing.
+    #[default]
+    Hold = 2,
+}
+
+/// A `uint8 signal` that names no [Action].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InvalidAction(pub u8);
+
+impl fmt::Display for InvalidAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid signal {}, expected 0 (SELL), 1 (BUY) or 2 (HOLD)",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for InvalidAction {}
+
+impl TryFrom<u8> for Action {
+    type Error = InvalidAction;
+
+    fn try_from(signal: u8) -> Result<Self, Self::Error> {
+        match signal {
+            0 => Ok(Self::Sell),
+            1 => Ok(Self::Buy),
+            2 => Ok(Self::Hold),
+            _ => Err(InvalidAction(signal)),
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Sell => "SELL",
+            Self::Buy => "BUY",
+            Self::Hold => "HOLD",
+        })
+    }
+}
+
+/// `abi.encode(uint8 signal, uint256 confidence, uint256 predicted_price, bytes32 input_hash,
+/// uint256 version)`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct JournalV1 {
+    /// 0 = SELL, 1 = BUY, 2 = HOLD.
+    pub signal: Action,
+    /// Confidence percentage (0-100).
+    pub confidence: u64,
+    /// Predicted price in base units, i.e. scaled by 10^decimals.
+    pub predicted_price: U256,
+    /// keccak256 of the ABI-encoded price history the guest received.
+    pub input_hash: B256,
+}
+
+/// Indicators committed in report mode so a consumer can derive its own action, all 0 otherwise.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IndicatorPanel {
+    /// Linear regression prediction in USD, whichever models drove the signal.
+    pub regression_price: u64,
+    /// 14-period relative strength index (0-100).
+    pub rsi: u64,
+    /// MACD (12, 26, 9) histogram in millionths of a USD.
+    pub macd_histogram: i64,
+    /// 5-point simple moving average in USD.
+    pub sma_fast: u64,
+    /// 20-point simple moving average in USD.
+    pub sma_slow: u64,
+}
+
+/// `abi.encode(uint8 signal, uint256 confidence, uint256 predicted_price, bytes32 input_hash,
+/// uint256 trimmed_points, uint256 models, int256 slope, uint256 regression_price, uint256 rsi,
+/// int256 macd_histogram, uint256 sma_fast, uint256 sma_slow, uint256 clamped_points,
+/// bool agreement, uint256 version)`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct JournalV2 {
+    /// 0 = SELL, 1 = BUY, 2 = HOLD.
+    pub signal: Action,
+    /// Confidence percentage (0-100).
+    pub confidence: u64,
+    /// Predicted price in base units, i.e. scaled by 10^decimals.
+    pub predicted_price: U256,
+    /// keccak256 of the ABI-encoded price history the guest received.
+    pub input_hash: B256,
+    /// Number of price points dropped by outlier trimming.
+    pub trimmed_points: u64,
+    /// Bitmask of the models the guest ran.
+    pub models: u64,
+    /// Regression slope in USD per day after blending in the prior, 0 if the regression did not
+    /// run.
+    pub slope: i64,
+    /// Indicators committed in report mode, all zero otherwise.
+    pub panel: IndicatorPanel,
+    /// Number of prices clamped towards the median before fitting.
+    pub clamped_points: u64,
+    /// Whether the regression slope points the same way as the last day-over-day change.
+    pub agreement: bool,
+}
+
+/// `abi.encode(<the v2 words>, uint256 up_confidence, uint256 down_confidence, uint256 version)`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct JournalV3 {
+    /// Everything the v2 layout commits, in the same words.
+    pub base: JournalV2,
+    /// Confidence (0-100) that the price moves up; sums to 100 with `down_confidence`.
+    pub up_confidence: u64,
+    /// Confidence (0-100) that the price moves down.
+    pub down_confidence: u64,
+}
+
+/// `abi.encode(<the v3 words>, uint256 data_points, uint256 version)`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct JournalV4 {
+    /// Everything the v3 layout commits, in the same words.
+    pub base: JournalV3,
+    /// Number of price points the models were fitted to, after clamping and outlier trimming.
+    pub data_points: u64,
+}
+
+/// `abi.encode(<the v4 words>, uint256 confidence_bps, uint256 version)`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct JournalV5 {
+    /// Everything the v4 layout commits, in the same words.
+    pub base: JournalV4,
+    /// Confidence in basis points (0-10000), of which the percentage in `base` is the truncation.
+    /// Only finer than the percentage if the guest was asked for basis-point confidence.
+    pub confidence_bps: u64,
+}
+
+/// `abi.encode(<the v5 words>, uint256 reason, uint256 version)`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct JournalV6 {
+    /// Everything the v5 layout commits, in the same words.
+    pub base: JournalV5,
+    /// Why the guest committed its signal, as its code.
+    pub reason: Reason,
+}
+
+/// `abi.encode(<the v6 words>, int256 predicted_return_bps, uint256 version)`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct JournalV7 {
+    /// Everything the v6 layout commits, in the same words.
+    pub base: JournalV6,
+    /// `(predicted - current) / current` in basis points, computed from the prices in base units
+    /// and truncated towards zero; 0 unless the guest was asked for it.
+    pub predicted_return_bps: i64,
+}
+
+/// `abi.encode(<the v7 words>, bytes32 params_hash, uint256 version)`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct JournalV8 {
+    /// Everything the v7 layout commits, in the same words.
+    pub base: JournalV7,
+    /// [StrategyParams::hash] of the parameters the guest received.
+    pub params_hash: B256,
+}
+
+/// `abi.encode(<the v8 words>, uint256 last_price, uint256 version)`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct JournalV9 {
+    /// Everything the v8 layout commits, in the same words.
+    pub base: JournalV8,
+    /// Last price in whole USD of the series the guest received, or of its embedded series, as
+    /// supplied: the observation the prediction extrapolates from.
+    pub last_price: u64,
+}
+
+/// `abi.encode(<the v9 words>, uint256 decimals, uint256 version)`. From this version on the
+/// decimals sit in the same word, so that a contract can read the unit of the predicted price
+/// without knowing the rest of the layout.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct JournalV10 {
+    /// Everything the v9 layout commits, in the same words.
+    pub base: JournalV9,
+    /// Decimals of the predicted price: it is in base units of 10^-decimals USD.
+    pub decimals: u64,
+}
+
+/// `abi.encodePacked(<the v10 words>, uint256 count, int256[count] residuals, uint256 version)`:
+/// the residuals are laid out word by word after their count rather than ABI-encoded as a dynamic
+/// array, so that the version stays the last word.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct JournalV11 {
+    /// Everything the v10 layout commits, in the same words.
+    pub base: JournalV10,
+    /// Residual in whole USD of each price point, in order, against the fitted regression line.
+    pub residuals: Vec<i64>,
+}
+
+/// `abi.encodePacked(<the v11 words>, uint256 count, (uint256 horizon, uint256 signal)[count],
+/// uint256 version)`, with the pairs laid out word by word like the residuals.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct JournalV12 {
+    /// Everything the v11 layout commits, in the same words; the residuals may be empty.
+    pub base: JournalV11,
+    /// Each horizon, in days after the last price point, with the signal predicted for that day,
+    /// from the nearest horizon.
+    pub horizons: Vec<(u64, Action)>,
+}
+
+/// `abi.encodePacked(<the v12 words>, bytes32 strategy_id, uint256 version)`, so that the strategy
+/// ID is the word before the version whatever the counts.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct JournalV13 {
+    /// Everything the v12 layout commits, in the same words; the residuals and horizons may be
+    /// empty.
+    pub base: JournalV12,
+    /// [strategy_id] of the image the guest was told it runs as and of its parameters.
+    pub strategy_id: B256,
+}
+
+/// Everything the guest reads from stdin after the format byte: an `abi.encode` of the fields as a
+/// tuple in declaration order, or a JSON object naming them.
+///
+/// A zero selects the default of any field that has one, so that a JSON input only names the
+/// fields it sets.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GuestInput {
+    /// Current price per ETH in base units, `price * 10^decimals`.
+    pub current_price: U256,
+    /// `(day, price)` points in whole USD; empty for the embedded [PRICE_HISTORY].
+    pub history: Vec<(u64, u64)>,
+    /// Standard deviations of the residuals beyond which points are trimmed, 0 to keep them all.
+    pub outlier_k: u64,
+    /// Bitmask of the models to run, 0 for the linear regression alone.
+    pub models: u64,
+    /// Prior regression slope in USD per day.
+    pub prior_slope: i64,
+    /// Confidence in the prior slope (0-100), 0 to ignore it.
+    pub prior_weight: u64,
+    /// BUY threshold in basis points.
+    pub buy_bps: u64,
+    /// SELL threshold in basis points.
+    pub sell_bps: u64,
+    /// Whether to commit the indicator panel.
+    pub report: bool,
+    /// Whether to commit only the raw prediction and its confidence.
+    pub raw_prediction: bool,
+    /// Number of decimals of the price unit.
+    pub decimals: u64,
+    /// Factor of the median beyond which prices are clamped, 0 to clamp none.
+    pub clamp_factor: u64,
+    /// Seed of every random draw.
+    pub seed: u64,
+    /// Number of bootstrap resamples behind the regression's confidence, 0 to keep its R².
+    pub bootstrap_samples: u64,
+    /// Whether BUY and SELL require the slope to agree with the last day-over-day change.
+    pub require_agreement: bool,
+    /// Journal layout to commit, 0 for [LATEST_JOURNAL_VERSION].
+    pub journal_version: u64,
+    /// Whether to commit the unversioned legacy layout instead.
+    pub legacy_encoding: bool,
+    /// R² (0-100) of the regression below which the signal is HOLD, 0 to disable the gate.
+    pub min_r2: u64,
+    /// Number of recent prices the percentile rank model ranks among, 0 for the whole series.
+    pub percentile_window: u64,
+    /// How an ensemble combines its confidences: 0 mean, 1 minimum, 2 product.
+    pub ensemble_confidence: u64,
+    /// Whether to keep the confidence in basis points, committed from [JOURNAL_V5] and in raw
+    /// mode.
+    pub bps_confidence: bool,
+    /// Holt level smoothing factor (0-100), 0 for the default.
+    pub holt_alpha: u64,
+    /// Holt trend smoothing factor (0-100), 0 for the default.
+    pub holt_beta: u64,
+    /// Predicted price in whole USD above which the models BUY in place of the threshold, 0 to
+    /// keep it.
+    pub buy_above_usd: u64,
+    /// Predicted price in whole USD below which the models SELL in place of the threshold, 0 to
+    /// keep it.
+    pub sell_below_usd: u64,
+    /// How the committed prediction is rounded to base units: 0 towards zero, 1 half up, 2 up.
+    pub rounding: u64,
+    /// Whether to HOLD until the series is long enough for every selected model to warm up.
+    pub warmup: bool,
+    /// Whether to commit the predicted return in basis points, from [JOURNAL_V7].
+    pub predicted_return: bool,
+    /// Number of recent points whose residuals the regression's confidence is based on, 0 for
+    /// the R² of the whole series.
+    pub residual_window: u64,
+    /// `(day, open, high, low, close)` candles in place of the price history.
+    pub candles: Vec<(u64, u64, u64, u64, u64)>,
+    /// Number of most recent windows whose majority signal is committed, 0 or 1 for the whole
+    /// series alone.
+    pub vote_windows: u64,
+    /// Cap on the committed confidence (0-100), 0 for none.
+    pub max_confidence: u64,
+    /// Whether to commit the regression residual of every point, on top of [JOURNAL_V10].
+    pub residuals: bool,
+    /// Bitmask of further horizons BUY and SELL must hold at, bit `h - 1` for `h` days after the
+    /// last point, 0 for the next day alone.
+    pub horizons: u64,
+    /// Unix timestamp of each history point, for the decay half-life.
+    pub timestamps: Vec<u64>,
+    /// Seconds over which the regression halves the weight of older points, 0 to weigh them all
+    /// alike.
+    pub half_life_secs: u64,
+    /// Image ID the guest is proven as, which it cannot know itself, to commit the [strategy_id]
+    /// of; zero commits none.
+    pub image_id: B256,
+}
+
+impl GuestInput {
+    /// The input as the guest reads it: [INPUT_FORMAT_ABI] followed by [GuestInput::abi_encode].
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = vec![INPUT_FORMAT_ABI];
+        bytes.extend(self.abi_encode());
+        bytes
+    }
+
+    /// `abi.encode` of the fields as a tuple in declaration order.
+    pub fn abi_encode(&self) -> Vec<u8> {
+        let input = self.clone();
+        let words: InputWords = (
+            input.current_price,
+            input.history,
+            input.outlier_k,
+            input.models,
+            input.prior_slope,
+            input.prior_weight,
+            input.buy_bps,
+            input.sell_bps,
+            input.report,
+            input.raw_prediction,
+            input.decimals,
+            input.clamp_factor,
+            input.seed,
+            input.bootstrap_samples,
+            input.require_agreement,
+            input.journal_version,
+            input.legacy_encoding,
+            input.min_r2,
+            input.percentile_window,
+            input.ensemble_confidence,
+            input.bps_confidence,
+            input.holt_alpha,
+            input.holt_beta,
+            input.buy_above_usd,
+            input.sell_below_usd,
+            input.rounding,
+            input.warmup,
+            input.predicted_return,
+            input.residual_window,
+            input.candles,
+            input.vote_windows,
+            input.max_confidence,
+            input.residuals,
+            input.horizons,
+            input.timestamps,
+            input.half_life_secs,
+            input.image_id,
+        );
+        words.abi_encode()
+    }
+
+    /// Decodes the fields from [GuestInput::abi_encode].
+    pub fn abi_decode(bytes: &[u8]) -> alloy_sol_types::Result<Self> {
+        let (
+            current_price,
+            history,
+            outlier_k,
+            models,
+            prior_slope,
+            prior_weight,
+            buy_bps,
+            sell_bps,
+            report,
+            raw_prediction,
+            decimals,
+            clamp_factor,
+            seed,
+            bootstrap_samples,
+            require_agreement,
+            journal_version,
+            legacy_encoding,
+            min_r2,
+            percentile_window,
+            ensemble_confidence,
+            bps_confidence,
+            holt_alpha,
+            holt_beta,
+            buy_above_usd,
+            sell_below_usd,
+            rounding,
+            warmup,
+            predicted_return,
+            residual_window,
+            candles,
+            vote_windows,
+            max_confidence,
+            residuals,
+            horizons,
+            timestamps,
+            half_life_secs,
+            image_id,
+        ) = InputWords::abi_decode(bytes)?;
+        Ok(Self {
+            current_price,
+            history,
+            outlier_k,
+            models,
+            prior_slope,
+            prior_weight,
+            buy_bps,
+            sell_bps,
+            report,
+            raw_prediction,
+            decimals,
+            clamp_factor,
+            seed,
+            bootstrap_samples,
+            require_agreement,
+            journal_version,
+            legacy_encoding,
+            min_r2,
+            percentile_window,
+            ensemble_confidence,
+            bps_confidence,
+            holt_alpha,
+            holt_beta,
+            buy_above_usd,
+            sell_below_usd,
+            rounding,
+            warmup,
+            predicted_return,
+            residual_window,
+            candles,
+            vote_windows,
+            max_confidence,
+            residuals,
+            horizons,
+            timestamps,
+            half_life_secs,
+            image_id,
+        })
+    }
+}
+
+/// Every guest input that shapes the signal apart from the prices, exactly as it was supplied, so
+/// that zeroes selecting a default stay zero. Committed as its [StrategyParams::hash] from journal
+/// version 8 for a verifier to check the configuration that produced the signal.
+///
+/// The moving average windows are not inputs: they are pinned by the image ID.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StrategyParams {
+    /// Bitmask of the models to run.
+    pub models: u64,
+    /// Outlier trimming factor.
+    pub outlier_k: u64,
+    /// Prior regression slope in USD per day.
+    pub prior_slope: i64,
+    /// Weight of the prior slope (0-100).
+    pub prior_weight: u64,
+    /// BUY threshold in basis points.
+    pub buy_bps: u64,
+    /// SELL threshold in basis points.
+    pub sell_bps: u64,
+    /// Factor of the median beyond which prices are clamped.
+    pub clamp_factor: u64,
+    /// Seed of every random draw.
+    pub seed: u64,
+    /// Number of bootstrap resamples behind the regression's confidence.
+    pub bootstrap_samples: u64,
+    /// Whether BUY and SELL require the slope to agree with the last change.
+    pub require_agreement: bool,
+    /// R² (0-100) below which the signal is HOLD.
+    pub min_r2: u64,
+    /// Number of recent prices the percentile rank model ranks among.
+    pub percentile_window: u64,
+    /// How an ensemble combines the confidences of its models.
+    pub ensemble_confidence: u64,
+    /// Holt level smoothing factor (0-100).
+    pub holt_alpha: u64,
+    /// Holt trend smoothing factor (0-100).
+    pub holt_beta: u64,
+    /// Predicted price in whole USD above which the models BUY.
+    pub buy_above_usd: u64,
+    /// Predicted price in whole USD below which the models SELL.
+    pub sell_below_usd: u64,
+    /// How the committed prediction is rounded to base units.
+    pub rounding: u64,
+    /// Whether to HOLD until every selected model has warmed up.
+    pub warmup: bool,
+    /// Number of recent points whose residuals the regression's confidence is based on.
+    pub residual_window: u64,
+    /// Number of most recent windows whose majority signal is committed.
+    pub vote_windows: u64,
+    /// Cap on the committed confidence (0-100).
+    pub max_confidence: u64,
+    /// Bitmask of the horizons the signal must agree with, bit `h - 1` for `h` days ahead.
+    pub horizons: u64,
+    /// Seconds over which the regression halves the weight of older points, 0 for no decay.
+    pub half_life_secs: u64,
+}
+
+impl StrategyParams {
+    /// keccak256 of the parameters `abi.encode`d in declaration order, with the integers as
+    /// `uint256`, the prior slope as `int256` and the flags as `bool`.
+    pub fn hash(&self) -> B256 {
+        keccak256(
+            (
+                U256::from(self.models),
+                U256::from(self.outlier_k),
+                I256::try_from(self.prior_slope).unwrap(),
+                U256::from(self.prior_weight),
+                U256::from(self.buy_bps),
+                U256::from(self.sell_bps),
+                U256::from(self.clamp_factor),
+                U256::from(self.seed),
+                U256::from(self.bootstrap_samples),
+                self.require_agreement,
+                U256::from(self.min_r2),
+                U256::from(self.percentile_window),
+                U256::from(self.ensemble_confidence),
+                U256::from(self.holt_alpha),
+                U256::from(self.holt_beta),
+                U256::from(self.buy_above_usd),
+                U256::from(self.sell_below_usd),
+                U256::from(self.rounding),
+                self.warmup,
+                U256::from(self.residual_window),
+                U256::from(self.vote_windows),
+                U256::from(self.max_confidence),
+                U256::from(self.horizons),
+                U256::from(self.half_life_secs),
+            )
+                .abi_encode(),
+        )
+    }
+}
+
+/// keccak256 of `abi.encode(bytes32 image_id, bytes32 params_hash)`: one ID for a guest image run
+/// with one set of [StrategyParams], the models among them, for a contract to whitelist.
+///
+/// A guest cannot embed its own image ID, so it hashes the one it is given; a contract checks it
+/// against the image ID it verifies the proof with.
+pub fn strategy_id(image_id: B256, params_hash: B256) -> B256 {
+    keccak256((image_id, params_hash).abi_encode())
+}
+
+/// A journal in any of the supported layouts.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Journal {
+    /// The legacy layout, see [JOURNAL_LEGACY].
+    Legacy(JournalV2),
+    /// Version 1, see [JournalV1].
+    V1(JournalV1),
+    /// Version 2, see [JournalV2].
+    V2(JournalV2),
+    /// Version 3, see [JournalV3].
+    V3(JournalV3),
+    /// Version 4, see [JournalV4].
+    V4(JournalV4),
+    /// Version 5, see [JournalV5].
+    V5(JournalV5),
+    /// Version 6, see [JournalV6].
+    V6(JournalV6),
+    /// Version 7, see [JournalV7].
+    V7(JournalV7),
+    /// Version 8, see [JournalV8].
+    V8(JournalV8),
+    /// Version 9, see [JournalV9].
+    V9(JournalV9),
+    /// Version 10, see [JournalV10].
+    V10(JournalV10),
+    /// Version 11, see [JournalV11].
+    V11(JournalV11),
+    /// Version 12, see [JournalV12].
+    V12(JournalV12),
+    /// Version 13, see [JournalV13].
+    V13(JournalV13),
+}
+
+/// Why a journal could not be decoded.
+#[derive(Debug)]
+pub enum DecodeError {
+    /// The journal is too short to carry a version word.
+    Truncated(usize),
+    /// The version word names no known layout.
+    UnknownVersion(U256),
+    /// The journal is not as long as the layout named by the version word.
+    Length {
+        version: u8,
+        expected: usize,
+        actual: usize,
+    },
+    /// The words do not match the layout named by the version word.
+    Layout(u8, alloy_sol_types::Error),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Truncated(len) => write!(f, "journal of {len} bytes has no version word"),
+            Self::UnknownVersion(version) => write!(f, "unknown journal version {version}"),
+            Self::Length {
+                version,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "v{version} journal should be {expected} bytes, got {actual}"
+            ),
+            Self::Layout(version, err) => {
+                write!(f, "journal does not match the v{version} layout: {err}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Layout(_, err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Formats an amount in base units as a decimal with `decimals` fractional digits, without going
+/// through `f64`, so every digit is exact. Trailing zeros of the fraction are dropped, e.g.
+/// `format_wei(U256::from(3_250_000_000u64), 6)` is `"3250"` and one wei is
+/// `"0.000000000000000001"` ETH.
+pub fn format_wei(value: U256, decimals: u8) -> String {
+    let digits = value.to_string();
+    let decimals = decimals as usize;
+    if decimals == 0 {
+        return digits;
+    }
+    let digits = format!("{digits:0>width$}", width = decimals + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - decimals);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        whole.to_string()
+    } else {
+        format!("{whole}.{fraction}")
+    }
+}
+
+impl JournalV1 {
+    const WORDS: usize = 4;
+
+    fn encode_words(&self) -> Vec<u8> {
+        (
+            U256::from(self.signal as u8),
+            U256::from(self.confidence),
+            self.predicted_price,
+            self.input_hash,
+        )
+            .abi_encode()
+    }
+
+    fn decode_words(words: &[u8], lenient: bool) -> alloy_sol_types::Result<Self> {
+        let words = V1Words::abi_decode(words)?;
+        Ok(Self {
+            signal: decode_action(words.0)?,
+            confidence: uint(words.1, "confidence", lenient)?,
+            predicted_price: words.2,
+            input_hash: words.3,
+        })
+    }
+}
+
+impl JournalV2 {
+    const WORDS: usize = 14;
+
+    fn encode_words(&self) -> Vec<u8> {
+        let panel = &self.panel;
+        (
+            U256::from(self.signal as u8),
+            U256::from(self.confidence),
+            self.predicted_price,
+            self.input_hash,
+            U256::from(self.trimmed_points),
+            U256::from(self.models),
+            I256::try_from(self.slope).unwrap(),
+            U256::from(panel.regression_price),
+            U256::from(panel.rsi),
+            I256::try_from(panel.macd_histogram).unwrap(),
+            U256::from(panel.sma_fast),
+            U256::from(panel.sma_slow),
+            U256::from(self.clamped_points),
+            self.agreement,
+        )
+            .abi_encode()
+    }
+
+    fn decode_words(words: &[u8], lenient: bool) -> alloy_sol_types::Result<Self> {
+        let words = V2Words::abi_decode(words)?;
+        Ok(Self {
+            signal: decode_action(words.0)?,
+            confidence: uint(words.1, "confidence", lenient)?,
+            predicted_price: words.2,
+            input_hash: words.3,
+            trimmed_points: uint(words.4, "trimmed_points", lenient)?,
+            models: uint(words.5, "models", lenient)?,
+            slope: int(words.6, "slope", lenient)?,
+            panel: IndicatorPanel {
+                regression_price: uint(words.7, "regression_price", lenient)?,
+                rsi: uint(words.8, "rsi", lenient)?,
+                macd_histogram: int(words.9, "macd_histogram", lenient)?,
+                sma_fast: uint(words.10, "sma_fast", lenient)?,
+                sma_slow: uint(words.11, "sma_slow", lenient)?,
+            },
+            clamped_points: uint(words.12, "clamped_points", lenient)?,
+            agreement: words.13,
+        })
+    }
+}
+
+impl JournalV3 {
+    const WORDS: usize = JournalV2::WORDS + 2;
+
+    fn encode_words(&self) -> Vec<u8> {
+        let mut bytes = self.base.encode_words();
+        bytes.extend(
+            (
+                U256::from(self.up_confidence),
+                U256::from(self.down_confidence),
+            )
+                .abi_encode(),
+        );
+        bytes
+    }
+
+    fn decode_words(words: &[u8], lenient: bool) -> alloy_sol_types::Result<Self> {
+        let (base, extra) = words.split_at(JournalV2::WORDS * WORD);
+        let (up_confidence, down_confidence) = V3Words::abi_decode(extra)?;
+        Ok(Self {
+            base: JournalV2::decode_words(base, lenient)?,
+            up_confidence: uint(up_confidence, "up_confidence", lenient)?,
+            down_confidence: uint(down_confidence, "down_confidence", lenient)?,
+        })
+    }
+}
+
+impl JournalV4 {
+    const WORDS: usize = JournalV3::WORDS + 1;
+
+    fn encode_words(&self) -> Vec<u8> {
+        let mut bytes = self.base.encode_words();
+        bytes.extend((U256::from(self.data_points),).abi_encode());
+        bytes
+    }
+
+    fn decode_words(words: &[u8], lenient: bool) -> alloy_sol_types::Result<Self> {
+        let (base, extra) = words.split_at(JournalV3::WORDS * WORD);
+        let (data_points,) = V4Words::abi_decode(extra)?;
+        Ok(Self {
+            base: JournalV3::decode_words(base, lenient)?,
+            data_points: uint(data_points, "data_points", lenient)?,
+        })
+    }
+}
+
+impl JournalV5 {
+    const WORDS: usize = JournalV4::WORDS + 1;
+
+    fn encode_words(&self) -> Vec<u8> {
+        let mut bytes = self.base.encode_words();
+        bytes.extend((U256::from(self.confidence_bps),).abi_encode());
+        bytes
+    }
+
+    fn decode_words(words: &[u8], lenient: bool) -> alloy_sol_types::Result<Self> {
+        let (base, extra) = words.split_at(JournalV4::WORDS * WORD);
+        let (confidence_bps,) = V5Words::abi_decode(extra)?;
+        Ok(Self {
+            base: JournalV4::decode_words(base, lenient)?,
+            confidence_bps: uint(confidence_bps, "confidence_bps", lenient)?,
+        })
+    }
+}
+
+impl JournalV6 {
+    const WORDS: usize = JournalV5::WORDS + 1;
+
+    fn encode_words(&self) -> Vec<u8> {
+        let mut bytes = self.base.encode_words();
+        bytes.extend((U256::from(self.reason as u8),).abi_encode());
+        bytes
+    }
+
+    fn decode_words(words: &[u8], lenient: bool) -> alloy_sol_types::Result<Self> {
+        let (base, extra) = words.split_at(JournalV5::WORDS * WORD);
+        let (reason,) = V6Words::abi_decode(extra)?;
+        let reason = Reason::from_code(reason.saturating_to()).ok_or_else(|| {
+            alloy_sol_types::Error::custom(format!("unknown reason code {reason}"))
+        })?;
+        Ok(Self {
+            base: JournalV5::decode_words(base, lenient)?,
+            reason,
+        })
+    }
+}
+
+impl JournalV7 {
+    const WORDS: usize = JournalV6::WORDS + 1;
+
+    fn encode_words(&self) -> Vec<u8> {
+        let mut bytes = self.base.encode_words();
+        bytes.extend((I256::try_from(self.predicted_return_bps).unwrap(),).abi_encode());
+        bytes
+    }
+
+    fn decode_words(words: &[u8], lenient: bool) -> alloy_sol_types::Result<Self> {
+        let (base, extra) = words.split_at(JournalV6::WORDS * WORD);
+        let (predicted_return_bps,) = V7Words::abi_decode(extra)?;
+        Ok(Self {
+            base: JournalV6::decode_words(base, lenient)?,
+            predicted_return_bps: int(predicted_return_bps, "predicted_return_bps", lenient)?,
+        })
+    }
+}
+
+impl JournalV8 {
+    const WORDS: usize = JournalV7::WORDS + 1;
+
+    fn encode_words(&self) -> Vec<u8> {
+        let mut bytes = self.base.encode_words();
+        bytes.extend((self.params_hash,).abi_encode());
+        bytes
+    }
+
+    fn decode_words(words: &[u8], lenient: bool) -> alloy_sol_types::Result<Self> {
+        let (base, extra) = words.split_at(JournalV7::WORDS * WORD);
+        let (params_hash,) = V8Words::abi_decode(extra)?;
+        Ok(Self {
+            base: JournalV7::decode_words(base, lenient)?,
+            params_hash,
+        })
+    }
+}
+
+impl JournalV9 {
+    const WORDS: usize = JournalV8::WORDS + 1;
+
+    fn encode_words(&self) -> Vec<u8> {
+        let mut bytes = self.base.encode_words();
+        bytes.extend((U256::from(self.last_price),).abi_encode());
+        bytes
+    }
+
+    fn decode_words(words: &[u8], lenient: bool) -> alloy_sol_types::Result<Self> {
+        let (base, extra) = words.split_at(JournalV8::WORDS * WORD);
+        let (last_price,) = V9Words::abi_decode(extra)?;
+        Ok(Self {
+            base: JournalV8::decode_words(base, lenient)?,
+            last_price: uint(last_price, "last_price", lenient)?,
+        })
+    }
+}
+
+impl JournalV10 {
+    const WORDS: usize = JournalV9::WORDS + 1;
+
+    fn encode_words(&self) -> Vec<u8> {
+        let mut bytes = self.base.encode_words();
+        bytes.extend((U256::from(self.decimals),).abi_encode());
+        bytes
+    }
+
+    fn decode_words(words: &[u8], lenient: bool) -> alloy_sol_types::Result<Self> {
+        let (base, extra) = words.split_at(JournalV9::WORDS * WORD);
+        let (decimals,) = V10Words::abi_decode(extra)?;
+        Ok(Self {
+            base: JournalV9::decode_words(base, lenient)?,
+            decimals: uint(decimals, "decimals", lenient)?,
+        })
+    }
+}
+
+impl JournalV11 {
+    /// Words before the residuals, their count included.
+    const FIXED_WORDS: usize = JournalV10::WORDS + 1;
+
+    // Number of words the count word of `words` calls for, capped so that an absurd count is a
+    // length mismatch rather than an overflow
+    fn words(words: &[u8]) -> usize {
+        let count = words
+            .get(JournalV10::WORDS * WORD..Self::FIXED_WORDS * WORD)
+            .map_or(0, |count| {
+                U256::from_be_slice(count).saturating_to::<usize>()
+            });
+        Self::FIXED_WORDS + count.min(words.len() / WORD)
+    }
+
+    fn encode_words(&self) -> Vec<u8> {
+        let mut bytes = self.base.encode_words();
+        bytes.extend((U256::from(self.residuals.len()),).abi_encode());
+        for residual in &self.residuals {
+            bytes.extend((I256::try_from(*residual).unwrap(),).abi_encode());
+        }
+        bytes
+    }
+
+    fn decode_words(words: &[u8], lenient: bool) -> alloy_sol_types::Result<Self> {
+        let (base, extra) = words.split_at(JournalV10::WORDS * WORD);
+        let residuals = extra[WORD..]
+            .chunks(WORD)
+            .map(|word| {
+                let (residual,) = ResidualWord::abi_decode(word)?;
+                int(residual, "residual", lenient)
+            })
+            .collect::<alloy_sol_types::Result<_>>()?;
+        Ok(Self {
+            base: JournalV10::decode_words(base, lenient)?,
+            residuals,
+        })
+    }
+}
+
+impl JournalV12 {
+    /// Words before the horizons, their count included, when there are no residuals.
+    const FIXED_WORDS: usize = JournalV11::FIXED_WORDS + 1;
+
+    // Number of words the residual and horizon counts of `words` call for, capped like the
+    // residual count alone
+    fn words(words: &[u8]) -> usize {
+        let base = JournalV11::words(words);
+        let count = words
+            .get(base * WORD..(base + 1) * WORD)
+            .map_or(0, |count| {
+                U256::from_be_slice(count).saturating_to::<usize>()
+            });
+        base + 1 + 2 * count.min(words.len() / WORD)
+    }
+
+    fn encode_words(&self) -> Vec<u8> {
+        let mut bytes = self.base.encode_words();
+        bytes.extend((U256::from(self.horizons.len()),).abi_encode());
+        for (horizon, signal) in &self.horizons {
+            bytes.extend((U256::from(*horizon), U256::from(*signal as u8)).abi_encode());
+        }
+        bytes
+    }
+
+    fn decode_words(words: &[u8], lenient: bool) -> alloy_sol_types::Result<Self> {
+        let (base, extra) = words.split_at(JournalV11::words(words) * WORD);
+        let horizons = extra[WORD..]
+            .chunks(2 * WORD)
+            .map(|words| {
+                let (horizon, signal) = HorizonWords::abi_decode(words)?;
+                Ok((uint(horizon, "horizon", lenient)?, decode_action(signal)?))
+            })
+            .collect::<alloy_sol_types::Result<_>>()?;
+        Ok(Self {
+            base: JournalV11::decode_words(base, lenient)?,
+            horizons,
+        })
+    }
+}
+
+impl JournalV13 {
+    /// Words before the strategy ID when there are no residuals or horizons.
+    const FIXED_WORDS: usize = JournalV12::FIXED_WORDS + 1;
+
+    fn words(words: &[u8]) -> usize {
+        JournalV12::words(words) + 1
+    }
+
+    fn encode_words(&self) -> Vec<u8> {
+        let mut bytes = self.base.encode_words();
+        bytes.extend((self.strategy_id,).abi_encode());
+        bytes
+    }
+
+    fn decode_words(words: &[u8], lenient: bool) -> alloy_sol_types::Result<Self> {
+        let (base, extra) = words.split_at(words.len() - WORD);
+        let (strategy_id,) = V13Words::abi_decode(extra)?;
+        Ok(Self {
+            base: JournalV12::decode_words(base, lenient)?,
+            strategy_id,
+        })
+    }
+}
+
+impl Journal {
+    /// Version committed in the last word of the journal.
+    pub fn version(&self) -> u8 {
+        match self {
+            Self::Legacy(_) => JOURNAL_LEGACY,
+            Self::V1(_) => JOURNAL_V1,
+            Self::V2(_) => JOURNAL_V2,
+            Self::V3(_) => JOURNAL_V3,
+            Self::V4(_) => JOURNAL_V4,
+            Self::V5(_) => JOURNAL_V5,
+            Self::V6(_) => JOURNAL_V6,
+            Self::V7(_) => JOURNAL_V7,
+            Self::V8(_) => JOURNAL_V8,
+            Self::V9(_) => JOURNAL_V9,
+            Self::V10(_) => JOURNAL_V10,
+            Self::V11(_) => JOURNAL_V11,
+            Self::V12(_) => JOURNAL_V12,
+            Self::V13(_) => JOURNAL_V13,
+        }
+    }
+
+    /// Encodes the journal exactly as Solidity's `abi.encode` would.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = match self {
+            Self::Legacy(journal) => return encode_legacy(journal),
+            Self::V1(journal) => journal.encode_words(),
+            Self::V2(journal) => journal.encode_words(),
+            Self::V3(journal) => journal.encode_words(),
+            Self::V4(journal) => journal.encode_words(),
+            Self::V5(journal) => journal.encode_words(),
+            Self::V6(journal) => journal.encode_words(),
+            Self::V7(journal) => journal.encode_words(),
+            Self::V8(journal) => journal.encode_words(),
+            Self::V9(journal) => journal.encode_words(),
+            Self::V10(journal) => journal.encode_words(),
+            Self::V11(journal) => journal.encode_words(),
+            Self::V12(journal) => journal.encode_words(),
+            Self::V13(journal) => journal.encode_words(),
+        };
+        bytes.extend(U256::from(self.version()).to_be_bytes::<WORD>());
+        bytes
+    }
+
+    /// Decodes a journal in the layout selected by its last word, or in the legacy layout if it
+    /// has that length, which no versioned layout shares.
+    ///
+    /// Every word must fit its field: a count, price or confidence beyond 64 bits is an error
+    /// rather than a value the guest never committed.
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Self::decode_with(bytes, false)
+    }
+
+    /// Decodes a journal like [Journal::decode], except that a word too large for its 64-bit
+    /// field saturates at the field's bounds instead of failing the decode. The signal, reason and
+    /// version words must still be valid.
+    pub fn decode_lenient(bytes: &[u8]) -> Result<Self, DecodeError> {
+        Self::decode_with(bytes, true)
+    }
+
+    fn decode_with(bytes: &[u8], lenient: bool) -> Result<Self, DecodeError> {
+        if bytes.len() == JournalV2::WORDS * WORD {
+            return decode_legacy(bytes, lenient)
+                .map(Self::Legacy)
+                .map_err(|err| DecodeError::Layout(JOURNAL_LEGACY, err));
+        }
+        let Some(words_len) = bytes.len().checked_sub(WORD) else {
+            return Err(DecodeError::Truncated(bytes.len()));
+        };
+        let (words, version) = bytes.split_at(words_len);
+        let version = U256::from_be_slice(version);
+        let Some((version, len)) = [
+            (JOURNAL_V1, JournalV1::WORDS),
+            (JOURNAL_V2, JournalV2::WORDS),
+            (JOURNAL_V3, JournalV3::WORDS),
+            (JOURNAL_V4, JournalV4::WORDS),
+            (JOURNAL_V5, JournalV5::WORDS),
+            (JOURNAL_V6, JournalV6::WORDS),
+            (JOURNAL_V7, JournalV7::WORDS),
+            (JOURNAL_V8, JournalV8::WORDS),
+            (JOURNAL_V9, JournalV9::WORDS),
+            (JOURNAL_V10, JournalV10::WORDS),
+            (JOURNAL_V11, JournalV11::FIXED_WORDS),
+            (JOURNAL_V12, JournalV12::FIXED_WORDS),
+            (JOURNAL_V13, JournalV13::FIXED_WORDS),
+        ]
+        .into_iter()
+        .find(|(known, _)| version == U256::from(*known)) else {
+            return Err(DecodeError::UnknownVersion(version));
+        };
+        // The residuals and horizons make the lengths from v11 on depend on their counts
+        let len = match version {
+            JOURNAL_V11 => JournalV11::words(words),
+            JOURNAL_V12 => JournalV12::words(words),
+            JOURNAL_V13 => JournalV13::words(words),
+            _ => len,
+        };
+        if words.len() != len * WORD {
+            return Err(DecodeError::Length {
+                version,
+                expected: (len + 1) * WORD,
+                actual: bytes.len(),
+            });
+        }
+        match version {
+            JOURNAL_V1 => JournalV1::decode_words(words, lenient).map(Self::V1),
+            JOURNAL_V2 => JournalV2::decode_words(words, lenient).map(Self::V2),
+            JOURNAL_V3 => JournalV3::decode_words(words, lenient).map(Self::V3),
+            JOURNAL_V4 => JournalV4::decode_words(words, lenient).map(Self::V4),
+            JOURNAL_V5 => JournalV5::decode_words(words, lenient).map(Self::V5),
+            JOURNAL_V6 => JournalV6::decode_words(words, lenient).map(Self::V6),
+            JOURNAL_V7 => JournalV7::decode_words(words, lenient).map(Self::V7),
+            JOURNAL_V8 => JournalV8::decode_words(words, lenient).map(Self::V8),
+            JOURNAL_V9 => JournalV9::decode_words(words, lenient).map(Self::V9),
+            JOURNAL_V10 => JournalV10::decode_words(words, lenient).map(Self::V10),
+            JOURNAL_V11 => JournalV11::decode_words(words, lenient).map(Self::V11),
+            JOURNAL_V12 => JournalV12::decode_words(words, lenient).map(Self::V12),
+            _ => JournalV13::decode_words(words, lenient).map(Self::V13),
+        }
+        .map_err(|err| DecodeError::Layout(version, err))
+    }
+}
+
+// Reads an unsigned word into its 64-bit field. A word that does not fit is an error, or saturates
+// at u64::MAX if `lenient`
+fn uint(word: U256, field: &str, lenient: bool) -> alloy_sol_types::Result<u64> {
+    match u64::try_from(word) {
+        Ok(value) => Ok(value),
+        Err(_) if lenient => Ok(u64::MAX),
+        Err(_) => Err(alloy_sol_types::Error::custom(format!(
+            "{field} word {word} does not fit in 64 bits"
+        ))),
+    }
+}
+
+// Reads a signed word into its 64-bit field. A word that does not fit is an error, or saturates at
+// i64::MIN or i64::MAX if `lenient`
+fn int(word: I256, field: &str, lenient: bool) -> alloy_sol_types::Result<i64> {
+    match i64::try_from(word) {
+        Ok(value) => Ok(value),
+        Err(_) if lenient && word.is_negative() => Ok(i64::MIN),
+        Err(_) if lenient => Ok(i64::MAX),
+        Err(_) => Err(alloy_sol_types::Error::custom(format!(
+            "{field} word {word} does not fit in 64 bits"
+        ))),
+    }
+}
+
+// Reads a `uint8 signal` word, rejecting values that name no action
+fn decode_action(word: U256) -> alloy_sol_types::Result<Action> {
+    Action::try_from(word.saturating_to::<u8>())
+        .map_err(|err| alloy_sol_types::Error::custom(err.to_string()))
+}
+
+// Manual encoding that exactly matches Solidity's abi.encode for
+// (uint8, uint256, uint256, bytes32, uint256, uint256, int256,
+//  uint256, uint256, int256, uint256, uint256, uint256, bool)
+fn encode_legacy(journal: &JournalV2) -> Vec<u8> {
+    let mut journal_data = Vec::new();
+
+    // For Solidity abi.encode, uint8 is right-aligned in 32 bytes (big-endian padding)
+    let mut action_bytes = [0u8; WORD];
+    action_bytes[WORD - 1] = journal.signal as u8;
+    journal_data.extend_from_slice(&action_bytes);
+
+    // U256 values are encoded as 32-byte big-endian
+    journal_data.extend_from_slice(&U256::from(journal.confidence).to_be_bytes::<WORD>());
+    journal_data.extend_from_slice(&journal.predicted_price.to_be_bytes::<WORD>());
+
+    // bytes32 values are encoded as-is
+    journal_data.extend_from_slice(journal.input_hash.as_slice());
+    journal_data.extend_from_slice(&U256::from(journal.trimmed_points).to_be_bytes::<WORD>());
+    journal_data.extend_from_slice(&U256::from(journal.models).to_be_bytes::<WORD>());
+
+    // int256 values are encoded as 32-byte big-endian two's complement
+    journal_data.extend_from_slice(&I256::try_from(journal.slope).unwrap().to_be_bytes::<WORD>());
+
+    let panel = &journal.panel;
+    journal_data.extend_from_slice(&U256::from(panel.regression_price).to_be_bytes::<WORD>());
+    journal_data.extend_from_slice(&U256::from(panel.rsi).to_be_bytes::<WORD>());
+    journal_data.extend_from_slice(
+        &I256::try_from(panel.macd_histogram)
+            .unwrap()
+            .to_be_bytes::<WORD>(),
+    );
+    journal_data.extend_from_slice(&U256::from(panel.sma_fast).to_be_bytes::<WORD>());
+    journal_data.extend_from_slice(&U256::from(panel.sma_slow).to_be_bytes::<WORD>());
+
+    journal_data.extend_from_slice(&U256::from(journal.clamped_points).to_be_bytes::<WORD>());
+
+    // bool values are encoded as 0 or 1 in a 32-byte word
+    journal_data.extend_from_slice(&U256::from(journal.agreement as u8).to_be_bytes::<WORD>());
+
+    journal_data
+}
+
+// Reads the words of a legacy journal back by position, mirroring [encode_legacy]
+fn decode_legacy(bytes: &[u8], lenient: bool) -> alloy_sol_types::Result<JournalV2> {
+    let word = |index: usize| &bytes[index * WORD..(index + 1) * WORD];
+    let uint_at = |index: usize, field| uint(U256::from_be_slice(word(index)), field, lenient);
+    let int_at = |index: usize, field| {
+        int(
+            I256::from_be_bytes::<WORD>(word(index).try_into().unwrap()),
+            field,
+            lenient,
+        )
+    };
+    Ok(JournalV2 {
+        signal: decode_action(U256::from_be_slice(word(0)))?,
+        confidence: uint_at(1, "confidence")?,
+        predicted_price: U256::from_be_slice(word(2)),
+        input_hash: B256::from_slice(word(3)),
+        trimmed_points: uint_at(4, "trimmed_points")?,
+        models: uint_at(5, "models")?,
+        slope: int_at(6, "slope")?,
+        panel: IndicatorPanel {
+            regression_price: uint_at(7, "regression_price")?,
+            rsi: uint_at(8, "rsi")?,
+            macd_histogram: int_at(9, "macd_histogram")?,
+            sma_fast: uint_at(10, "sma_fast")?,
+            sma_slow: uint_at(11, "sma_slow")?,
+        },
+        clamped_points: uint_at(12, "clamped_points")?,
+        agreement: !U256::from_be_slice(word(13)).is_zero(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_wei_exactly() {
+        assert_eq!(format_wei(U256::from(1), 18), "0.000000000000000001");
+        assert_eq!(format_wei(U256::from(3200), 0), "3200");
+        assert_eq!(format_wei(U256::from(3_250_000_000u64), 6), "3250");
+        assert_eq!(format_wei(U256::from(3_250_500_000u64), 6), "3250.5");
+        assert_eq!(format_wei(U256::ZERO, 18), "0");
+        // 2^53 + 1 is the first integer an f64 rounds
+        assert_eq!(
+            format_wei(U256::from(9_007_199_254_740_993u64), 18),
+            "0.009007199254740993"
+        );
+        assert_eq!(
+            format_wei(U256::MAX, 18),
+            "115792089237316195423570985008687907853269984665640564039457.584007913129639935"
+        );
+        // More decimals than U256::MAX has digits
+        assert_eq!(
+            format_wei(U256::from(5), 80),
+            format!("0.{}5", "0".repeat(79))
+        );
+    }
+
+    #[test]
+    fn v1_round_trips() {
+        let journal = Journal::V1(JournalV1 {
+            signal: Action::Buy,
+            confidence: 87,
+            predicted_price: U256::from(3_250_000_000u64),
+            input_hash: B256::repeat_byte(0xab),
+        });
+        let bytes = journal.encode();
+        assert_eq!(bytes.len(), 5 * 32);
+        assert_eq!(Journal::decode(&bytes).unwrap(), journal);
+    }
+
+    #[test]
+    fn v2_round_trips() {
+        let journal = Journal::V2(JournalV2 {
+            signal: Action::Sell,
+            confidence: 64,
+            predicted_price: U256::from(3100),
+            input_hash: B256::repeat_byte(0x42),
+            trimmed_points: 2,
+            models: 0b101,
+            slope: -12,
+            panel: IndicatorPanel {
+                regression_price: 3101,
+                rsi: 38,
+                macd_histogram: -4_250_000,
+                sma_fast: 3120,
+                sma_slow: 3180,
+            },
+            clamped_points: 1,
+            agreement: true,
+        });
+        let bytes = journal.encode();
+        assert_eq!(bytes.len(), 15 * 32);
+        assert_eq!(Journal::decode(&bytes).unwrap(), journal);
+    }
+
+    #[test]
+    fn v3_round_trips() {
+        let journal = Journal::V3(JournalV3 {
+            base: JournalV2 {
+                signal: Action::Buy,
+                confidence: 72,
+                predicted_price: U256::from(3300),
+                input_hash: B256::repeat_byte(0x07),
+                models: 1,
+                slope: 9,
+                agreement: true,
+                ..Default::default()
+            },
+            up_confidence: 72,
+            down_confidence: 28,
+        });
+        let bytes = journal.encode();
+        assert_eq!(bytes.len(), 17 * 32);
+        assert_eq!(Journal::decode(&bytes).unwrap(), journal);
+    }
+
+    #[test]
+    fn v4_round_trips() {
+        let journal = Journal::V4(JournalV4 {
+            base: JournalV3 {
+                base: JournalV2 {
+                    signal: Action::Hold,
+                    confidence: 40,
+                    predicted_price: U256::from(3205),
+                    input_hash: B256::repeat_byte(0x5c),
+                    trimmed_points: 3,
+                    models: 1,
+                    ..Default::default()
+                },
+                up_confidence: 40,
+                down_confidence: 60,
+            },
+            data_points: 27,
+        });
+        let bytes = journal.encode();
+        assert_eq!(bytes.len(), 18 * 32);
+        assert_eq!(Journal::decode(&bytes).unwrap(), journal);
+    }
+
+    #[test]
+    fn v5_round_trips() {
+        let journal = Journal::V5(JournalV5 {
+            base: JournalV4 {
+                base: JournalV3 {
+                    base: JournalV2 {
+                        signal: Action::Buy,
+                        confidence: 87,
+                        predicted_price: U256::from(3420),
+                        input_hash: B256::repeat_byte(0x3d),
+                        models: 1,
+                        slope: 19,
+                        ..Default::default()
+                    },
+                    up_confidence: 87,
+                    down_confidence: 13,
+                },
+                data_points: 20,
+            },
+            confidence_bps: 8_764,
+        });
+        let bytes = journal.encode();
+        assert_eq!(bytes.len(), 19 * 32);
+        assert_eq!(Journal::decode(&bytes).unwrap(), journal);
+    }
+
+    #[test]
+    fn v6_round_trips() {
+        let journal = Journal::V6(JournalV6 {
+            base: JournalV5 {
+                base: JournalV4 {
+                    base: JournalV3 {
+                        base: JournalV2 {
+                            signal: Action::Hold,
+                            confidence: 43,
+                            predicted_price: U256::from(3264),
+                            input_hash: B256::repeat_byte(0x61),
+                            models: 1,
+                            slope: 9,
+                            ..Default::default()
+                        },
+                        up_confidence: 43,
+                        down_confidence: 57,
+                    },
+                    data_points: 20,
+                },
+                confidence_bps: 4300,
+            },
+            reason: Reason::LowRSquared,
+        });
+        let mut bytes = journal.encode();
+        assert_eq!(bytes.len(), 20 * 32);
+        assert_eq!(Journal::decode(&bytes).unwrap(), journal);
+
+        bytes[607] = 9;
+        assert!(matches!(
+            Journal::decode(&bytes),
+            Err(DecodeError::Layout(JOURNAL_V6, _))
+        ));
+    }
+
+    #[test]
+    fn v7_round_trips() {
+        let journal = Journal::V7(JournalV7 {
+            base: JournalV6 {
+                base: JournalV5 {
+                    base: JournalV4 {
+                        base: JournalV3 {
+                            base: JournalV2 {
+                                signal: Action::Sell,
+                                confidence: 72,
+                                predicted_price: U256::from(3161),
+                                input_hash: B256::repeat_byte(0x71),
+                                models: 1,
+                                slope: -4,
+                                ..Default::default()
+                            },
+                            up_confidence: 28,
+                            down_confidence: 72,
+                        },
+                        data_points: 20,
+                    },
+                    confidence_bps: 7200,
+                },
+                reason: Reason::None,
+            },
+            predicted_return_bps: -121,
+        });
+        let bytes = journal.encode();
+        assert_eq!(bytes.len(), 21 * 32);
+        assert_eq!(
+            I256::from_be_bytes::<32>(bytes[608..640].try_into().unwrap()),
+            I256::try_from(-121).unwrap()
+        );
+        assert_eq!(Journal::decode(&bytes).unwrap(), journal);
+    }
+
+    #[test]
+    fn v8_round_trips() {
+        let params = StrategyParams {
+            models: 0b1001,
+            buy_bps: 150,
+            sell_bps: 150,
+            percentile_window: 30,
+            warmup: true,
+            ..Default::default()
+        };
+        let journal = Journal::V8(JournalV8 {
+            base: JournalV7 {
+                base: JournalV6 {
+                    reason: Reason::DeadBand,
+                    ..Default::default()
+                },
+                predicted_return_bps: 12,
+            },
+            params_hash: params.hash(),
+        });
+        let bytes = journal.encode();
+        assert_eq!(bytes.len(), 22 * 32);
+        assert_eq!(bytes[640..672], params.hash()[..]);
+        assert_eq!(Journal::decode(&bytes).unwrap(), journal);
+
+        // Any parameter moves the hash
+        let other = StrategyParams {
+            sell_bps: 151,
+            ..params.clone()
+        };
+        assert_ne!(other.hash(), params.hash());
+    }
+
+    #[test]
+    fn v9_round_trips() {
+        let journal = Journal::V9(JournalV9 {
+            base: JournalV8 {
+                params_hash: B256::repeat_byte(0x5a),
+                ..Default::default()
+            },
+            last_price: 3240,
+        });
+        let bytes = journal.encode();
+        assert_eq!(bytes.len(), 23 * 32);
+        assert_eq!(U256::from_be_slice(&bytes[672..704]), U256::from(3240));
+        assert_eq!(Journal::decode(&bytes).unwrap(), journal);
+    }
+
+    #[test]
+    fn v10_round_trips() {
+        let journal = Journal::V10(JournalV10 {
+            base: JournalV9 {
+                last_price: 3240,
+                ..Default::default()
+            },
+            decimals: 6,
+        });
+        let bytes = journal.encode();
+        assert_eq!(bytes.len(), 24 * 32);
+        assert_eq!(U256::from_be_slice(&bytes[704..736]), U256::from(6));
+        assert_eq!(Journal::decode(&bytes).unwrap(), journal);
+    }
+
+    #[test]
+    fn v11_round_trips_and_checks_the_residual_count() {
+        let journal = Journal::V11(JournalV11 {
+            base: JournalV10 {
+                decimals: 6,
+                ..Default::default()
+            },
+            residuals: vec![12, -7, 0, -5],
+        });
+        let mut bytes = journal.encode();
+        assert_eq!(bytes.len(), (23 + 1 + 4 + 1) * 32);
+        assert_eq!(U256::from_be_slice(&bytes[736..768]), U256::from(4));
+        assert_eq!(
+            I256::try_from_be_slice(&bytes[800..832]),
+            Some(I256::try_from(-7).unwrap())
+        );
+        assert_eq!(Journal::decode(&bytes).unwrap(), journal);
+
+        // A count that does not match the words that follow it
+        bytes[767] = 5;
+        assert!(matches!(
+            Journal::decode(&bytes),
+            Err(DecodeError::Length { version: 11, .. })
+        ));
+        bytes[736..768].copy_from_slice(&U256::MAX.to_be_bytes::<32>());
+        assert!(Journal::decode(&bytes).is_err());
+    }
+
+    #[test]
+    fn v12_round_trips_after_the_residuals() {
+        let journal = Journal::V12(JournalV12 {
+            base: JournalV11 {
+                residuals: vec![3, -3],
+                ..Default::default()
+            },
+            horizons: vec![(1, Action::Sell), (7, Action::Buy)],
+        });
+        let mut bytes = journal.encode();
+        assert_eq!(bytes.len(), (23 + 1 + 2 + 1 + 4 + 1) * 32);
+        assert_eq!(U256::from_be_slice(&bytes[832..864]), U256::from(2));
+        assert_eq!(U256::from_be_slice(&bytes[928..960]), U256::from(7));
+        assert_eq!(Journal::decode(&bytes).unwrap(), journal);
+
+        // A signal word that names no action
+        bytes[927] = 3;
+        assert!(Journal::decode(&bytes).is_err());
+    }
+
+    #[test]
+    fn v13_commits_the_strategy_id_before_the_version() {
+        let params_hash = StrategyParams::default().hash();
+        let id = strategy_id(B256::repeat_byte(0x11), params_hash);
+        assert_ne!(id, strategy_id(B256::repeat_byte(0x12), params_hash));
+        let journal = Journal::V13(JournalV13 {
+            base: JournalV12 {
+                horizons: vec![(3, Action::Hold)],
+                ..Default::default()
+            },
+            strategy_id: id,
+        });
+        let bytes = journal.encode();
+        assert_eq!(bytes.len(), (23 + 1 + 1 + 2 + 1 + 1) * 32);
+        assert_eq!(&bytes[bytes.len() - 64..bytes.len() - 32], id.as_slice());
+        assert_eq!(Journal::decode(&bytes).unwrap(), journal);
+        assert!(matches!(
+            Journal::decode(&bytes[32..]),
+            Err(DecodeError::Length { version: 13, .. })
+        ));
+    }
+
+    #[test]
+    fn legacy_and_abi_encodings_agree() {
+        let journal = JournalV2 {
+            signal: Action::Buy,
+            confidence: 91,
+            predicted_price: U256::from(3_412_000_000u64),
+            input_hash: B256::repeat_byte(0x9e),
+            trimmed_points: 1,
+            models: 0b11,
+            slope: -3,
+            panel: IndicatorPanel {
+                regression_price: 3410,
+                rsi: 61,
+                macd_histogram: -1_500_000,
+                sma_fast: 3400,
+                sma_slow: 3380,
+            },
+            clamped_points: 2,
+            agreement: true,
+        };
+        let legacy = Journal::Legacy(journal.clone()).encode();
+        let versioned = Journal::V2(journal.clone()).encode();
+        assert_eq!(legacy.len(), 14 * 32);
+        // The hand-built words are exactly abi.encode's, less the version word
+        assert_eq!(legacy[..], versioned[..14 * 32]);
+        assert_eq!(
+            Journal::decode(&legacy).unwrap(),
+            Journal::Legacy(journal.clone())
+        );
+        assert_eq!(Journal::decode(&versioned).unwrap(), Journal::V2(journal));
+    }
+
+    #[test]
+    fn contract_prefix_is_shared() {
+        let v1 = Journal::V1(JournalV1 {
+            signal: Action::Hold,
+            confidence: 50,
+            predicted_price: U256::from(3000),
+            input_hash: B256::repeat_byte(1),
+        });
+        let v2 = Journal::V2(JournalV2 {
+            signal: Action::Hold,
+            confidence: 50,
+            predicted_price: U256::from(3000),
+            input_hash: B256::repeat_byte(1),
+            ..Default::default()
+        });
+        assert_eq!(v1.encode()[..128], v2.encode()[..128]);
+    }
+
+    #[test]
+    fn rejects_unknown_versions() {
+        let mut bytes = Journal::V1(JournalV1::default()).encode();
+        bytes[159] = 14;
+        assert!(matches!(
+            Journal::decode(&bytes),
+            Err(DecodeError::UnknownVersion(version)) if version == U256::from(14)
+        ));
+        assert!(matches!(
+            Journal::decode(&[0; 16]),
+            Err(DecodeError::Truncated(16))
+        ));
+
+        // A v2 journal relabelled as v3 is two words short
+        let mut bytes = Journal::V2(JournalV2::default()).encode();
+        bytes[479] = JOURNAL_V3;
+        assert!(matches!(
+            Journal::decode(&bytes),
+            Err(DecodeError::Length {
+                version: JOURNAL_V3,
+                expected: 544,
+                actual: 480
+            })
+        ));
+    }
+    #[test]
+    fn strict_decoding_rejects_oversized_words() {
+        // A confidence word of 2^64
+        let mut bytes = Journal::V1(JournalV1::default()).encode();
+        bytes[55] = 1;
+        assert!(matches!(
+            Journal::decode(&bytes),
+            Err(DecodeError::Layout(JOURNAL_V1, _))
+        ));
+        let Ok(Journal::V1(journal)) = Journal::decode_lenient(&bytes) else {
+            panic!("lenient decoding should coerce the word");
+        };
+        assert_eq!(journal.confidence, u64::MAX);
+
+        // A slope below i64::MIN saturates towards it
+        let mut bytes = Journal::V2(JournalV2::default()).encode();
+        bytes[6 * 32..7 * 32].copy_from_slice(&I256::MIN.to_be_bytes::<32>());
+        assert!(Journal::decode(&bytes).is_err());
+        let Ok(Journal::V2(journal)) = Journal::decode_lenient(&bytes) else {
+            panic!("lenient decoding should coerce the word");
+        };
+        assert_eq!(journal.slope, i64::MIN);
+    }
+
+    #[test]
+    fn rejects_invalid_signals() {
+        for action in [Action::Sell, Action::Buy, Action::Hold] {
+            assert_eq!(Action::try_from(action as u8), Ok(action));
+        }
+        assert_eq!(Action::try_from(3), Err(InvalidAction(3)));
+
+        let mut bytes = Journal::V1(JournalV1::default()).encode();
+        bytes[31] = 3;
+        assert!(matches!(
+            Journal::decode(&bytes),
+            Err(DecodeError::Layout(JOURNAL_V1, _))
+        ));
+    }
+
+    #[test]
+    fn guest_input_round_trips() {
+        let input = GuestInput {
+            current_price: U256::from(3_200_000_000u64),
+            history: vec![(1, 3100), (2, 3150)],
+            prior_slope: -5,
+            decimals: 6,
+            candles: vec![(1, 3100, 3160, 3090, 3150)],
+            horizons: 0b101,
+            timestamps: vec![100, 200],
+            image_id: B256::repeat_byte(0x1d),
+            ..Default::default()
+        };
+        let bytes = input.encode();
+        assert_eq!(bytes[0], INPUT_FORMAT_ABI);
+        assert_eq!(GuestInput::abi_decode(&bytes[1..]).unwrap(), input);
+        // The current price leads the tuple, after the offset of its dynamic encoding
+        assert_eq!(U256::from_be_slice(&bytes[33..65]), input.current_price);
+    }
+}